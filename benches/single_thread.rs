@@ -72,5 +72,105 @@ fn compare(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, compare);
+fn probe(c: &mut Criterion) {
+    let mut group = c.benchmark_group("probe");
+
+    // A map filled close to its load factor, with long probe sequences.
+    let full = |load_factor| {
+        let m = papaya::HashMap::<usize, usize>::builder()
+            .capacity(SIZE)
+            .load_factor(load_factor)
+            .collector(seize::Collector::new().epoch_frequency(None))
+            .build();
+
+        let capacity = m.pin().stats().capacity;
+        for i in 0..(capacity as f64 * load_factor) as usize {
+            m.pin().insert(i, i);
+        }
+
+        m
+    };
+
+    for load_factor in [0.5, 0.9] {
+        let m = full(load_factor);
+        let len = m.len();
+
+        group.bench_function(format!("hit/{load_factor}"), |b| {
+            let m = m.pin();
+            b.iter(|| {
+                for i in 0..len {
+                    assert_eq!(black_box(m.get(&i)), Some(&i));
+                }
+            });
+        });
+
+        group.bench_function(format!("miss/{load_factor}"), |b| {
+            let m = m.pin();
+            b.iter(|| {
+                for i in len..len * 2 {
+                    assert_eq!(black_box(m.get(&i)), None);
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn probe_write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("probe_write");
+
+    // A map filled close to its load factor. Writes retire entries, so reclamation is enabled.
+    let full = |load_factor| {
+        let m = papaya::HashMap::<usize, usize>::builder()
+            .capacity(SIZE)
+            .load_factor(load_factor)
+            .build();
+
+        let capacity = m.pin().stats().capacity;
+        for i in 0..(capacity as f64 * load_factor) as usize {
+            m.pin().insert(i, i);
+        }
+
+        m
+    };
+
+    for load_factor in [0.5, 0.9] {
+        let m = full(load_factor);
+        let len = m.len();
+
+        group.bench_function(format!("insert/{load_factor}"), |b| {
+            let m = m.pin();
+            b.iter(|| {
+                for i in 0..len {
+                    assert!(black_box(m.insert(i, i)).is_some());
+                }
+            });
+        });
+
+        group.bench_function(format!("update/{load_factor}"), |b| {
+            let m = m.pin();
+            b.iter(|| {
+                for i in 0..len {
+                    assert!(black_box(m.update(i, |v| v + 1)).is_some());
+                }
+            });
+        });
+
+        // Removing and reinserting a key reuses its tombstone.
+        group.bench_function(format!("remove/{load_factor}"), |b| {
+            let m = m.pin();
+            b.iter(|| {
+                for i in 0..len {
+                    assert!(black_box(m.remove(&i)).is_some());
+                    m.insert(i, i);
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, compare, probe, probe_write);
 criterion_main!(benches);
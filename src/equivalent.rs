@@ -0,0 +1,60 @@
+use std::borrow::Borrow;
+
+/// Key equivalence trait.
+///
+/// This trait allows looking up entries in a map or set by a type other than the key
+/// type, as long as the lookup type hashes identically to the key it is equivalent to.
+/// Unlike [`Borrow`], this allows looking up composite keys by borrowed parts, such as
+/// a `(u32, &str)` tuple for a `(u32, String)` key.
+///
+/// `Equivalent<K>` is implemented for every type `Q` where `K: Borrow<Q>`.
+///
+/// # Correctness
+///
+/// Equivalent values must hash identically. If `Q::equivalent(q, k)` returns `true`,
+/// then `q` and `k` must produce the same hash. Violating this will cause lookups to
+/// miss entries, but will not result in undefined behavior.
+///
+/// # Examples
+///
+/// ```
+/// use papaya::{Equivalent, HashMap};
+///
+/// #[derive(Hash, PartialEq, Eq)]
+/// struct Key {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// #[derive(Hash)]
+/// struct KeyRef<'a> {
+///     id: u32,
+///     name: &'a str,
+/// }
+///
+/// impl Equivalent<Key> for KeyRef<'_> {
+///     fn equivalent(&self, key: &Key) -> bool {
+///         self.id == key.id && self.name == key.name
+///     }
+/// }
+///
+/// let map = HashMap::new();
+/// map.pin().insert(Key { id: 1, name: "a".to_owned() }, "value");
+///
+/// assert_eq!(map.pin().get(&KeyRef { id: 1, name: "a" }), Some(&"value"));
+/// ```
+pub trait Equivalent<K: ?Sized> {
+    /// Returns `true` if this value is equivalent to the given key.
+    fn equivalent(&self, key: &K) -> bool;
+}
+
+impl<Q: ?Sized, K: ?Sized> Equivalent<K> for Q
+where
+    Q: Eq,
+    K: Borrow<Q>,
+{
+    #[inline]
+    fn equivalent(&self, key: &K) -> bool {
+        PartialEq::eq(self, key.borrow())
+    }
+}
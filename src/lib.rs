@@ -73,6 +73,19 @@ std::thread::scope(|s| {
 });
 ```
 
+A concurrent [`HashSet`] is also provided, built on the same table and exposing the same pinning API:
+
+```rust
+use papaya::HashSet;
+
+let set = HashSet::new();
+let set = set.pin();
+
+set.insert('A');
+assert!(set.contains(&'A'));
+assert!(!set.insert('A'));
+```
+
 It is important to note that as long as you are holding on to a guard, you are preventing the map from performing garbage collection. Pinning and unpinning the table is relatively cheap but not free, similar to the cost of locking and unlocking an uncontended or lightly contended `Mutex`. Thus guard reuse is encouraged, within reason. See the [`seize`] crate for advanced usage and specifics of the garbage collection algorithm.
 
 # Consistency
@@ -220,11 +233,24 @@ The `Guard` trait supports both local and owned guards. Note the `'guard` lifeti
 // Stylistic preferences.
 #![allow(clippy::multiple_bound_locations, clippy::single_match)]
 
+mod equivalent;
 mod map;
 mod raw;
+pub mod set;
 
+#[cfg(feature = "rayon")]
+mod rayon_impls;
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+pub use equivalent::Equivalent;
 pub use map::{
-    Compute, HashMap, HashMapBuilder, HashMapRef, Iter, Keys, OccupiedError, Operation, ResizeMode,
-    Values,
+    CompactMode, CompareExchangeError, Compute, Cursor, Drain, EntryPool, EntryRef, ExtractIf,
+    HashMap, HashMapBuilder, HashMapRef, IncrementalIter, IntoIter, Iter, IterMut, Keys, MapStats,
+    OccupiedError, Operation, ResizeMode, Shards, ShrinkMode, Values, ValuesMut, Watch,
 };
 pub use seize::{Collector, Guard};
+
+#[cfg(feature = "rayon")]
+pub use rayon_impls::{ParIter, ParKeys, ParValues};
+pub use set::{HashSet, HashSetBuilder, HashSetRef};
@@ -1,33 +1,44 @@
-use crate::raw::{self, InsertResult};
+use crate::equivalent::Equivalent;
+use crate::raw::{self, Allocator, Global, InsertResult, ProbeConfig};
 use seize::{Collector, Guard, LocalGuard, OwnedGuard};
 
-use std::borrow::Borrow;
 use std::collections::hash_map::RandomState;
+use std::convert::Infallible;
 use std::fmt;
+use std::future::{self, Future};
 use std::hash::{BuildHasher, Hash};
 use std::marker::PhantomData;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 /// A concurrent hash table.
 ///
 /// Most hash table operations require a [`Guard`](crate::Guard), which can be acquired through
 /// [`HashMap::guard`] or using the [`HashMap::pin`] API. See the [crate-level documentation](crate#usage)
 /// for details.
-pub struct HashMap<K, V, S = RandomState> {
-    raw: raw::HashMap<K, V, S>,
+pub struct HashMap<K, V, S = RandomState, A: Allocator = Global> {
+    pub(crate) raw: raw::HashMap<K, V, S, A>,
 }
 
 // Safety: We only ever hand out &K/V through shared references to the map,
 // so normal Send/Sync rules apply. We never expose owned or mutable references
-// to keys or values.
-unsafe impl<K: Send, V: Send, S: Send> Send for HashMap<K, V, S> {}
-unsafe impl<K: Sync, V: Sync, S: Sync> Sync for HashMap<K, V, S> {}
+// to keys or values. The allocator may be used to reclaim entries from any
+// thread that shares the map's collector, so it must be `Send` and `Sync`.
+unsafe impl<K: Send, V: Send, S: Send, A> Send for HashMap<K, V, S, A> where
+    A: Allocator + Send + Sync
+{
+}
+unsafe impl<K: Sync, V: Sync, S: Sync, A> Sync for HashMap<K, V, S, A> where
+    A: Allocator + Send + Sync
+{
+}
 
 /// A builder for a [`HashMap`].
 ///
 /// # Examples
 ///
 /// ```rust
-/// use papaya::{HashMap, ResizeMode};
+/// use papaya::{CompactMode, EntryPool, HashMap, ResizeMode, ShrinkMode};
 /// use seize::Collector;
 /// use std::collections::hash_map::RandomState;
 ///
@@ -38,20 +49,35 @@ unsafe impl<K: Sync, V: Sync, S: Sync> Sync for HashMap<K, V, S> {}
 ///     .hasher(RandomState::new())
 ///     // Set the resize mode.
 ///     .resize_mode(ResizeMode::Blocking)
+///     // Set the shrink mode.
+///     .shrink_mode(ShrinkMode::Threshold(0.25))
+///     // Set the compaction mode.
+///     .compact_mode(CompactMode::Threshold(0.25))
+///     // Pool entry allocations.
+///     .entry_pool(EntryPool::PerThread(1024))
+///     // Trade memory for shorter probe sequences.
+///     .load_factor(0.5)
+///     .max_probe(16)
 ///     // Set a custom garbage collector.
 ///     .collector(Collector::new().batch_size(128))
 ///     // Construct the hash map.
 ///     .build();
 /// ```
-pub struct HashMapBuilder<K, V, S = RandomState> {
+pub struct HashMapBuilder<K, V, S = RandomState, A = Global> {
     hasher: S,
+    alloc: A,
     capacity: usize,
     collector: Collector,
     resize_mode: ResizeMode,
+    shrink_mode: ShrinkMode,
+    compact_mode: CompactMode,
+    entry_pool: EntryPool,
+    load_factor: f64,
+    max_probe: Option<usize>,
     _kv: PhantomData<(K, V)>,
 }
 
-impl<K, V> HashMapBuilder<K, V> {
+impl<K, V, A> HashMapBuilder<K, V, RandomState, A> {
     /// Set the hash builder used to hash keys.
     ///
     /// Warning: `hash_builder` is normally randomly generated, and is designed
@@ -61,29 +87,65 @@ impl<K, V> HashMapBuilder<K, V> {
     ///
     /// The `hash_builder` passed should implement the [`BuildHasher`] trait for
     /// the HashMap to be useful, see its documentation for details.
-    pub fn hasher<S>(self, hasher: S) -> HashMapBuilder<K, V, S> {
+    pub fn hasher<S>(self, hasher: S) -> HashMapBuilder<K, V, S, A> {
         HashMapBuilder {
             hasher,
+            alloc: self.alloc,
             capacity: self.capacity,
             collector: self.collector,
             resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
             _kv: PhantomData,
         }
     }
 }
 
 impl<K, V, S> HashMapBuilder<K, V, S> {
+    /// Set the allocator used for the map's tables and entries.
+    ///
+    /// Memory is returned to the allocator when it is reclaimed, which may happen on any
+    /// thread that shares the map's [`Collector`], and never after the map is dropped.
+    ///
+    /// Custom allocators require the `allocator-api2` feature.
+    pub fn allocator<A>(self, alloc: A) -> HashMapBuilder<K, V, S, A> {
+        HashMapBuilder {
+            alloc,
+            hasher: self.hasher,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _kv: PhantomData,
+        }
+    }
+}
+
+impl<K, V, S, A> HashMapBuilder<K, V, S, A> {
     /// Set the initial capacity of the map.
     ///
     /// The table should be able to hold at least `capacity` elements before resizing.
     /// However, the capacity is an estimate, and the table may prematurely resize due
     /// to poor hash distribution. If `capacity` is 0, the hash map will not allocate.
-    pub fn capacity(self, capacity: usize) -> HashMapBuilder<K, V, S> {
+    pub fn capacity(self, capacity: usize) -> HashMapBuilder<K, V, S, A> {
         HashMapBuilder {
             capacity,
             hasher: self.hasher,
+            alloc: self.alloc,
             collector: self.collector,
             resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
             _kv: PhantomData,
         }
     }
@@ -93,8 +155,140 @@ impl<K, V, S> HashMapBuilder<K, V, S> {
         HashMapBuilder {
             resize_mode,
             hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _kv: PhantomData,
+        }
+    }
+
+    /// Set the shrinking mode of the map. See [`ShrinkMode`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`ShrinkMode::Threshold`] is not between `0.0` and `0.5`, exclusive.
+    pub fn shrink_mode(self, shrink_mode: ShrinkMode) -> Self {
+        shrink_mode.validate();
+
+        HashMapBuilder {
+            shrink_mode,
+            hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _kv: PhantomData,
+        }
+    }
+
+    /// Set the compaction mode of the map. See [`CompactMode`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`CompactMode::Threshold`] is not between `0.0` and `1.0`, exclusive.
+    pub fn compact_mode(self, compact_mode: CompactMode) -> Self {
+        compact_mode.validate();
+
+        HashMapBuilder {
+            compact_mode,
+            hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _kv: PhantomData,
+        }
+    }
+
+    /// Set the entry pooling mode of the map. See [`EntryPool`] for details.
+    pub fn entry_pool(self, entry_pool: EntryPool) -> Self {
+        HashMapBuilder {
+            entry_pool,
+            hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _kv: PhantomData,
+        }
+    }
+
+    /// Set the load factor of the map.
+    ///
+    /// The load factor is the target ratio of entries to table capacity. Tables are sized
+    /// to hold entries up to the load factor before resizing, and the probe limit used to
+    /// detect a full table scales with it. Lower load factors trade memory for shorter
+    /// probe sequences, while higher load factors use less memory at the cost of longer ones.
+    ///
+    /// The default load factor is `0.75`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `load_factor` is not between `0.0` and `1.0`, exclusive.
+    pub fn load_factor(self, load_factor: f64) -> Self {
+        assert!(
+            load_factor > 0.0 && load_factor < 1.0,
+            "load factor must be between 0.0 and 1.0"
+        );
+
+        HashMapBuilder {
+            load_factor,
+            hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            max_probe: self.max_probe,
+            _kv: PhantomData,
+        }
+    }
+
+    /// Set the maximum probe length of the map.
+    ///
+    /// Operations probe at most this many entries past an entry's initial position before
+    /// the table is considered full and resized, bounding the latency of lookups. By default,
+    /// the probe limit is only determined by the table capacity and load factor.
+    ///
+    /// Note that a small maximum probe length can cause the table to resize before reaching
+    /// its load factor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_probe` is `0`.
+    pub fn max_probe(self, max_probe: usize) -> Self {
+        assert!(max_probe > 0, "maximum probe length must be non-zero");
+
+        HashMapBuilder {
+            max_probe: Some(max_probe),
+            hasher: self.hasher,
+            alloc: self.alloc,
             capacity: self.capacity,
             collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
             _kv: PhantomData,
         }
     }
@@ -109,26 +303,50 @@ impl<K, V, S> HashMapBuilder<K, V, S> {
         HashMapBuilder {
             collector,
             hasher: self.hasher,
+            alloc: self.alloc,
             capacity: self.capacity,
             resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
             _kv: PhantomData,
         }
     }
 
     /// Construct a [`HashMap`] from the builder, using the configured options.
-    pub fn build(self) -> HashMap<K, V, S> {
+    pub fn build(self) -> HashMap<K, V, S, A>
+    where
+        A: Allocator,
+    {
         HashMap {
-            raw: raw::HashMap::new(self.capacity, self.hasher, self.collector, self.resize_mode),
+            raw: raw::HashMap::new(
+                self.capacity,
+                self.hasher,
+                self.alloc,
+                self.collector,
+                self.resize_mode,
+                self.shrink_mode,
+                self.compact_mode,
+                ProbeConfig::new(self.load_factor, self.max_probe),
+                self.entry_pool,
+            ),
         }
     }
 }
 
-impl<K, V, S> fmt::Debug for HashMapBuilder<K, V, S> {
+impl<K, V, S, A> fmt::Debug for HashMapBuilder<K, V, S, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("HashMapBuilder")
             .field("capacity", &self.capacity)
             .field("collector", &self.collector)
             .field("resize_mode", &self.resize_mode)
+            .field("shrink_mode", &self.shrink_mode)
+            .field("compact_mode", &self.compact_mode)
+            .field("entry_pool", &self.entry_pool)
+            .field("load_factor", &self.load_factor)
+            .field("max_probe", &self.max_probe)
             .finish()
     }
 }
@@ -170,6 +388,179 @@ impl Default for ResizeMode {
     }
 }
 
+/// Shrink behavior for a [`HashMap`].
+///
+/// Hash maps never shrink on their own by default, so a map that briefly held many entries
+/// keeps its peak capacity after they are removed. This type allows you to configure automatic
+/// shrinking when passed to [`HashMapBuilder::shrink_mode`]. Tables may also be shrunk explicitly
+/// with [`HashMap::shrink_to_fit`] or [`HashMap::shrink_to`].
+///
+/// Shrinking migrates all key and value pairs to a smaller table using the configured [`ResizeMode`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ShrinkMode {
+    /// The table is only shrunk explicitly.
+    ///
+    /// This is the default shrink mode, as shrinking requires a full migration of the table.
+    #[default]
+    Manual,
+    /// The table is shrunk once the ratio of entries to table capacity falls below the given
+    /// threshold.
+    ///
+    /// Removals periodically sample the length of the map, and migrate to a table that fits the
+    /// remaining entries once the ratio falls below the threshold. In incremental resize mode, the
+    /// migration is performed incrementally by future writers.
+    ///
+    /// The threshold must be between `0.0` and `0.5`, exclusive, to avoid oscillating between growing
+    /// and shrinking the table. A threshold of `0.125` is a reasonable choice for most workloads.
+    Threshold(f64),
+}
+
+impl ShrinkMode {
+    // Ensures the shrink threshold is in a valid range.
+    pub(crate) fn validate(&self) {
+        if let ShrinkMode::Threshold(threshold) = *self {
+            assert!(
+                threshold > 0.0 && threshold < 0.5,
+                "shrink threshold must be between 0.0 and 0.5"
+            );
+        }
+    }
+}
+
+/// Compaction behavior for a [`HashMap`].
+///
+/// Removing an entry leaves behind a deleted slot, or tombstone, that must be skipped by
+/// later operations probing past it. While inserts reuse tombstones found earlier in their
+/// probe sequence, workloads that frequently remove keys can still accumulate tombstones,
+/// lengthening probe sequences until the table is eventually resized.
+/// This type allows you to configure automatic compaction when passed to
+/// [`HashMapBuilder::compact_mode`]. Tables may also be compacted explicitly with
+/// [`HashMap::compact`].
+///
+/// Compaction migrates all key and value pairs to a new table of the same capacity using the
+/// configured [`ResizeMode`], leaving the tombstones behind.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum CompactMode {
+    /// The table is only compacted explicitly.
+    ///
+    /// This is the default compaction mode.
+    #[default]
+    Manual,
+    /// The table is compacted once the ratio of tombstones to table capacity rises above
+    /// the given threshold.
+    ///
+    /// Removals periodically sample the number of tombstones in the table, and migrate to a
+    /// new table once the ratio rises above the threshold. In incremental resize mode, the
+    /// migration is performed incrementally by future writers.
+    ///
+    /// The threshold must be between `0.0` and `1.0`, exclusive. A threshold of `0.25` is a
+    /// reasonable choice for most workloads.
+    Threshold(f64),
+}
+
+impl CompactMode {
+    // Ensures the compaction threshold is in a valid range.
+    pub(crate) fn validate(&self) {
+        if let CompactMode::Threshold(threshold) = *self {
+            assert!(
+                threshold > 0.0 && threshold < 1.0,
+                "compaction threshold must be between 0.0 and 1.0"
+            );
+        }
+    }
+}
+
+/// Entry allocation behavior for a [`HashMap`].
+///
+/// Every insert or update allocates a new entry, and replaced or removed entries are
+/// deallocated once they are reclaimed by the garbage collector. For write-heavy workloads,
+/// this can make the allocator a bottleneck. This type allows you to configure pooling of
+/// entry allocations when passed to [`HashMapBuilder::entry_pool`].
+///
+/// Pooled entries are allocated from the map's allocator, and returned to it when the map
+/// is dropped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EntryPool {
+    /// Entries are allocated and deallocated individually.
+    ///
+    /// This is the default pooling mode.
+    #[default]
+    Disabled,
+    /// Reclaimed entries are cached in per-thread free lists, and reused by later inserts
+    /// on the same thread.
+    ///
+    /// Each free list caches up to the given number of entries, after which reclaimed entries
+    /// are deallocated.
+    PerThread(usize),
+}
+
+/// Statistics about the internal state of a [`HashMap`].
+///
+/// This type is returned by [`HashMap::stats`]. Statistics are read from counters maintained by
+/// the map without synchronizing with concurrent operations, so they may be slightly inconsistent
+/// with each other if the map is being modified.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct MapStats {
+    /// The number of entries in the map.
+    pub len: usize,
+    /// The number of slots in the table.
+    pub capacity: usize,
+    /// The ratio of entries in the map to slots in the table.
+    pub load_factor: f64,
+    /// The number of slots in the table holding a deleted entry.
+    ///
+    /// Deleted slots lengthen the probe sequences of other operations until they are reused
+    /// by a new entry, or the table is resized or compacted.
+    pub tombstones: usize,
+    /// The maximum probe length of the table.
+    ///
+    /// The table is resized when an insert operation reaches this limit.
+    pub probe_limit: usize,
+    /// A histogram of probe lengths for the entries in the table.
+    ///
+    /// The entry at index `i` is the number of entries that are found after probing `i`
+    /// other slots, with one bucket for each probe length up to and including
+    /// [`MapStats::probe_limit`]. Long probe sequences can be caused by a poor hash function
+    /// or an accumulation of deleted entries.
+    ///
+    /// The histogram is exact for tables of up to 1024 slots. For larger tables, it is
+    /// estimated from a sample of 1024 slots spread evenly across the table, so that
+    /// collecting statistics takes bounded time regardless of the size of the map. Entries
+    /// that have already been copied to a new table by an in-progress resize are not
+    /// included.
+    pub probe_lengths: Vec<usize>,
+    /// The number of tables that the table is being resized to.
+    ///
+    /// This is zero if there is no resize in progress, and may be greater than one if a
+    /// new table is itself resized before the first resize completes.
+    pub pending_tables: usize,
+    /// The number of slots in the table that have been copied to the next table.
+    ///
+    /// This is zero if there is no resize in progress.
+    pub copied: usize,
+    /// The number of slots in the table that have been claimed for copying to the next table,
+    /// but not necessarily copied.
+    ///
+    /// This is zero if there is no resize in progress.
+    pub claimed: usize,
+    /// The number of times the table has been grown.
+    pub resizes: usize,
+    /// The number of times the table has been shrunk, explicitly or by the configured
+    /// [`ShrinkMode`].
+    pub shrinks: usize,
+    /// The number of times the table has been migrated to a new table of the same capacity,
+    /// either to clear deleted entries or because the probe limit was reached.
+    pub compactions: usize,
+    /// The memory, in bytes, held by old tables and entries that have been retired and are
+    /// waiting to be reclaimed.
+    ///
+    /// Old tables and removed or replaced entries are retired to the garbage collector, and
+    /// reclaimed once no guards could be accessing them. Note that only the entry allocations
+    /// are included, not any memory owned by the keys and values themselves.
+    pub retired_bytes: usize,
+}
+
 impl<K, V> HashMap<K, V> {
     /// Creates an empty `HashMap`.
     ///
@@ -210,19 +601,29 @@ impl<K, V> HashMap<K, V> {
         HashMapBuilder {
             capacity: 0,
             hasher: RandomState::default(),
+            alloc: Global,
             collector: Collector::new(),
             resize_mode: ResizeMode::default(),
+            shrink_mode: ShrinkMode::default(),
+            compact_mode: CompactMode::default(),
+            entry_pool: EntryPool::default(),
+            load_factor: ProbeConfig::LOAD_FACTOR,
+            max_probe: None,
             _kv: PhantomData,
         }
     }
 }
 
-impl<K, V, S> Default for HashMap<K, V, S>
+impl<K, V, S, A> Default for HashMap<K, V, S, A>
 where
     S: Default,
+    A: Allocator + Default,
 {
     fn default() -> Self {
-        HashMap::with_hasher(S::default())
+        HashMap::builder()
+            .hasher(S::default())
+            .allocator(A::default())
+            .build()
     }
 }
 
@@ -282,18 +683,40 @@ impl<K, V, S> HashMap<K, V, S> {
             raw: raw::HashMap::new(
                 capacity,
                 hash_builder,
+                Global,
                 Collector::default(),
                 ResizeMode::default(),
+                ShrinkMode::default(),
+                CompactMode::default(),
+                ProbeConfig::default(),
+                EntryPool::default(),
             ),
         }
     }
+}
+
+impl<K, V, S, A: Allocator> HashMap<K, V, S, A> {
+    /// Returns a reference to the allocator used for the map's tables and entries.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.raw.allocator()
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    ///
+    /// This can be used to compute hashes for [`get_with_hash`](HashMap::get_with_hash) and
+    /// related methods.
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        &self.raw.hasher
+    }
 
     /// Returns a pinned reference to the map.
     ///
     /// The returned reference manages a guard internally, preventing garbage collection
     /// for as long as it is held. See the [crate-level documentation](crate#usage) for details.
     #[inline]
-    pub fn pin(&self) -> HashMapRef<'_, K, V, S, LocalGuard<'_>> {
+    pub fn pin(&self) -> HashMapRef<'_, K, V, S, LocalGuard<'_>, A> {
         HashMapRef {
             guard: self.guard(),
             map: self,
@@ -309,7 +732,7 @@ impl<K, V, S> HashMap<K, V, S> {
     /// The returned reference manages a guard internally, preventing garbage collection
     /// for as long as it is held. See the [crate-level documentation](crate#usage) for details.
     #[inline]
-    pub fn pin_owned(&self) -> HashMapRef<'_, K, V, S, OwnedGuard<'_>> {
+    pub fn pin_owned(&self) -> HashMapRef<'_, K, V, S, OwnedGuard<'_>, A> {
         HashMapRef {
             guard: self.owned_guard(),
             map: self,
@@ -339,10 +762,11 @@ impl<K, V, S> HashMap<K, V, S> {
     }
 }
 
-impl<K, V, S> HashMap<K, V, S>
+impl<K, V, S, A> HashMap<K, V, S, A>
 where
     K: Hash + Eq,
     S: BuildHasher,
+    A: Allocator,
 {
     /// Returns the number of entries in the map.
     ///
@@ -379,11 +803,45 @@ where
         self.len() == 0
     }
 
+    /// Returns statistics about the internal state of the map.
+    ///
+    /// Statistics describe the current root table and any in-progress resize, which can help
+    /// diagnose performance issues, such as long probe sequences caused by a poor hash function
+    /// or an accumulation of deleted entries.
+    ///
+    /// Statistics are read from counters maintained by the map, along with a bounded sample of
+    /// the table for the probe length histogram, so collecting them takes time independent of
+    /// the size of the map and does not take any locks or help any resizes. This makes it safe
+    /// to sample periodically, such as from a metrics thread, without blocking other operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// for i in 0..100 {
+    ///     map.pin().insert(i, i);
+    /// }
+    ///
+    /// // Complete any in-progress resizes.
+    /// assert_eq!(map.pin().iter().count(), 100);
+    ///
+    /// let stats = map.stats(&map.guard());
+    /// assert_eq!(stats.len, 100);
+    /// assert!(stats.capacity >= 100);
+    /// assert_eq!(stats.probe_lengths.iter().sum::<usize>(), 100);
+    /// ```
+    #[inline]
+    pub fn stats(&self, guard: &impl Guard) -> MapStats {
+        self.raw.root(guard).stats(guard)
+    }
+
     /// Returns `true` if the map contains a value for the specified key.
     ///
-    /// The key may be any borrowed form of the map's key type, but
-    /// [`Hash`] and [`Eq`] on the borrowed form *must* match those for
-    /// the key type.
+    /// The key may be any borrowed form of the map's key type, or any other
+    /// [`Equivalent`] type, but [`Hash`] and [`Eq`] on the borrowed form *must*
+    /// match those for the key type.
     ///
     /// [`Eq`]: std::cmp::Eq
     /// [`Hash`]: std::hash::Hash
@@ -403,17 +861,16 @@ where
     #[inline]
     pub fn contains_key<Q>(&self, key: &Q, guard: &impl Guard) -> bool
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.get(key, guard).is_some()
     }
 
     /// Returns a reference to the value corresponding to the key.
     ///
-    /// The key may be any borrowed form of the map's key type, but
-    /// [`Hash`] and [`Eq`] on the borrowed form *must* match those for
-    /// the key type.
+    /// The key may be any borrowed form of the map's key type, or any other
+    /// [`Equivalent`] type, but [`Hash`] and [`Eq`] on the borrowed form *must*
+    /// match those for the key type.
     ///
     /// [`Eq`]: std::cmp::Eq
     /// [`Hash`]: std::hash::Hash
@@ -432,17 +889,17 @@ where
     #[inline]
     pub fn get<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> Option<&'g V>
     where
-        K: Borrow<Q> + 'g, // TODO: this bound is necessary because `raw::HashMap::get` returns the full entry.
-        Q: Hash + Eq + ?Sized,
+        K: 'g, // TODO: this bound is necessary because `raw::HashMap::get` returns the full entry.
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.raw.root(guard).get(key, guard).map(|(_, v)| v)
     }
 
     /// Returns the key-value pair corresponding to the supplied key.
     ///
-    /// The supplied key may be any borrowed form of the map's key type, but
-    /// [`Hash`] and [`Eq`] on the borrowed form *must* match those for
-    /// the key type.
+    /// The supplied key may be any borrowed form of the map's key type, or any other
+    /// [`Equivalent`] type, but [`Hash`] and [`Eq`] on the borrowed form *must*
+    /// match those for the key type.
     ///
     /// [`Eq`]: std::cmp::Eq
     /// [`Hash`]: std::hash::Hash
@@ -461,12 +918,119 @@ where
     #[inline]
     pub fn get_key_value<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> Option<(&'g K, &'g V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.raw.root(guard).get(key, guard)
     }
 
+    /// Returns a reference to the value corresponding to the key with the given hash,
+    /// using `eq` to find the matching key.
+    ///
+    /// This skips hashing the key, and allows matching keys with an arbitrary predicate instead
+    /// of an [`Equivalent`] type. The hash *must* match the hash produced by the map's
+    /// [`hasher`](HashMap::hasher) for the key, otherwise the key may not be found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    /// use std::hash::BuildHasher;
+    ///
+    /// #[derive(Hash, PartialEq, Eq)]
+    /// struct Key {
+    ///     id: u32,
+    ///     name: String,
+    /// }
+    ///
+    /// let map = HashMap::new();
+    /// let m = map.pin();
+    /// m.insert(Key { id: 1, name: "a".to_owned() }, "value");
+    ///
+    /// // Look up the key without allocating a `String`.
+    /// //
+    /// // The tuple hashes the same way as the key, field by field.
+    /// let hash = map.hasher().hash_one((1_u32, "a"));
+    /// assert_eq!(m.get_with_hash(hash, |k| k.id == 1 && k.name == "a"), Some(&"value"));
+    /// assert_eq!(m.get_with_hash(hash, |k| k.id == 2), None);
+    /// ```
+    #[inline]
+    pub fn get_with_hash<'g, F>(&self, hash: u64, mut eq: F, guard: &'g impl Guard) -> Option<&'g V>
+    where
+        K: 'g,
+        F: FnMut(&K) -> bool,
+    {
+        self.raw
+            .root(guard)
+            .get_hashed(hash, &mut eq, guard)
+            .map(|(_, v)| v)
+    }
+
+    /// Returns a handle to the entry corresponding to the supplied key.
+    ///
+    /// The returned [`EntryRef`] identifies the exact entry that was observed, and can be
+    /// passed to [`replace_if_unchanged`](HashMap::replace_if_unchanged) or
+    /// [`remove_if_unchanged`](HashMap::remove_if_unchanged) to update the entry only if
+    /// it has not been modified since. Unlike [`compare_exchange`](HashMap::compare_exchange),
+    /// this does not require `V: PartialEq`.
+    ///
+    /// The key may be any borrowed form of the map's key type, or any other
+    /// [`Equivalent`] type, but [`Hash`] and [`Eq`] on the borrowed form *must*
+    /// match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let m = map.pin();
+    /// m.insert(1, "a");
+    ///
+    /// let entry = m.get_entry(&1).unwrap();
+    /// assert_eq!(entry.key(), &1);
+    /// assert_eq!(entry.value(), &"a");
+    /// assert!(m.get_entry(&2).is_none());
+    /// ```
+    #[inline]
+    pub fn get_entry<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> Option<EntryRef<'g, K, V>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let (key, value, slot) = self.raw.root(guard).get_slot(key, guard)?;
+        Some(EntryRef { key, value, slot })
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    ///
+    /// Because this method requires unique access to the map, it does not
+    /// require a guard and reads the table without any synchronization. This
+    /// is useful for updating values while loading or tearing down a map.
+    ///
+    /// The key may be any borrowed form of the map's key type, or any other
+    /// [`Equivalent`] type, but [`Hash`] and [`Eq`] on the borrowed form *must*
+    /// match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let mut map = HashMap::new();
+    /// map.pin().insert(1, "a");
+    ///
+    /// if let Some(x) = map.get_mut(&1) {
+    ///     *x = "b";
+    /// }
+    /// assert_eq!(map.pin().get(&1), Some(&"b"));
+    /// ```
+    #[inline]
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.raw.get_mut(key)
+    }
+
     /// Inserts a key-value pair into the map.
     ///
     /// If the map did not have this key present, [`None`] is returned.
@@ -503,6 +1067,46 @@ where
         }
     }
 
+    /// Inserts a key-value pair into the map using a precomputed hash.
+    ///
+    /// This behaves like [`insert`](HashMap::insert), but skips hashing the key. The hash
+    /// *must* match the hash produced by the map's [`hasher`](HashMap::hasher) for the key.
+    /// Otherwise, the key may not be found by later operations, and the map may end up
+    /// containing duplicate keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    /// use std::hash::BuildHasher;
+    ///
+    /// let map = HashMap::new();
+    /// let m = map.pin();
+    ///
+    /// let hash = map.hasher().hash_one(37);
+    /// assert_eq!(m.insert_with_hash(hash, 37, "a"), None);
+    /// assert_eq!(m.insert_with_hash(hash, 37, "b"), Some(&"a"));
+    /// assert_eq!(m.get(&37), Some(&"b"));
+    /// ```
+    #[inline]
+    pub fn insert_with_hash<'g>(
+        &self,
+        hash: u64,
+        key: K,
+        value: V,
+        guard: &'g impl Guard,
+    ) -> Option<&'g V> {
+        match self
+            .raw
+            .root(guard)
+            .insert_hashed(hash, key, value, true, guard)
+        {
+            InsertResult::Inserted(_) => None,
+            InsertResult::Replaced(value) => Some(value),
+            InsertResult::Error { .. } => unreachable!(),
+        }
+    }
+
     /// Tries to insert a key-value pair into the map, and returns
     /// a reference to the value that was inserted.
     ///
@@ -605,84 +1209,99 @@ where
         }
     }
 
-    /// Updates an existing entry atomically.
-    ///
-    /// If the value for the specified `key` is present, the new value is computed and stored the
-    /// using the provided update function, and the new value is returned. Otherwise, `None`
-    /// is returned.
-    ///
-    ///
-    /// The update function is given the current value associated with the given key and returns the
-    /// new value to be stored. The operation is applied atomically only if the state of the entry remains
-    /// the same, meaning that it is not concurrently modified in any way. If the entry is
-    /// modified, the operation is retried with the new entry, similar to a traditional [compare-and-swap](https://en.wikipedia.org/wiki/Compare-and-swap)
-    /// operation.
-    ///
-    /// Note that the `update` function should be pure as it may be called multiple times, and the output
-    /// for a given entry may be memoized across retries.
+    /// Returns a reference to the value corresponding to the key, or inserts a default value,
+    /// looking up the key by reference.
     ///
+    /// This behaves like [`HashMap::get_or_insert`], but the key may be any borrowed form of the
+    /// map's key type, or any other [`Equivalent`] type. The key is only converted to an owned key
+    /// with `to_owned` if the value is inserted, so hits never allocate.
     ///
     /// # Examples
     ///
     /// ```
     /// use papaya::HashMap;
     ///
-    /// let map = HashMap::new();
-    /// map.pin().insert("a", 1);
-    /// assert_eq!(map.pin().get(&"a"), Some(&1));
-    ///
-    /// map.pin().update("a", |v| v + 1);
-    /// assert_eq!(map.pin().get(&"a"), Some(&2));
+    /// let map = HashMap::<String, i32>::new();
+    /// assert_eq!(map.pin().get_or_insert_ref("a", str::to_owned, 3), &3);
+    /// assert_eq!(map.pin().get_or_insert_ref("a", |_| unreachable!(), 6), &3);
     /// ```
     #[inline]
-    pub fn update<'g, F>(&self, key: K, update: F, guard: &'g impl Guard) -> Option<&'g V>
+    pub fn get_or_insert_ref<'g, Q, C>(
+        &self,
+        key: &Q,
+        to_owned: C,
+        value: V,
+        guard: &'g impl Guard,
+    ) -> &'g V
     where
-        F: Fn(&V) -> V,
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
         K: 'g,
     {
-        self.raw.root(guard).update(key, update, guard)
+        self.get_or_insert_with_ref(key, to_owned, || value, guard)
     }
 
-    /// Updates an existing entry or inserts a default value.
-    ///
-    /// If the value for the specified `key` is present, the new value is computed and stored the
-    /// using the provided update function, and the new value is returned. Otherwise, the provided
-    /// `value` is inserted into the map, and a reference to the newly inserted value is returned.
+    /// Returns a reference to the value corresponding to the key, or inserts a default value
+    /// computed from a closure, looking up the key by reference.
     ///
-    /// See [`HashMap::update`] for details about how atomic updates are performed.
+    /// This behaves like [`HashMap::get_or_insert_with`], but the key may be any borrowed form of
+    /// the map's key type, or any other [`Equivalent`] type. The key is only converted to an
+    /// owned key with `to_owned` if the value is inserted, so hits never allocate.
     ///
     /// # Examples
     ///
     /// ```
     /// use papaya::HashMap;
     ///
-    /// let map = HashMap::new();
-    /// assert_eq!(*map.pin().update_or_insert("a", |i| i + 1, 0), 0);
-    /// assert_eq!(*map.pin().update_or_insert("a", |i| i + 1, 0), 1);
+    /// let map = HashMap::<String, i32>::new();
+    /// assert_eq!(map.pin().get_or_insert_with_ref("a", str::to_owned, || 3), &3);
+    /// assert_eq!(map.pin().get_or_insert_with_ref("a", str::to_owned, || 6), &3);
     /// ```
     #[inline]
-    pub fn update_or_insert<'g, F>(
+    pub fn get_or_insert_with_ref<'g, Q, C, F>(
         &self,
-        key: K,
-        update: F,
-        value: V,
+        key: &Q,
+        to_owned: C,
+        f: F,
         guard: &'g impl Guard,
     ) -> &'g V
     where
-        F: Fn(&V) -> V,
-        K: 'g, // TODO: this bound is necessary because `HashMap::compute` returns the full entry.
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+        F: FnOnce() -> V,
+        K: 'g,
     {
-        self.update_or_insert_with(key, update, || value, guard)
+        let mut f = Some(f);
+        let compute = |entry| match entry {
+            // Return the existing value.
+            Some((_, current)) => Operation::Abort(current),
+            // Insert the initial value.
+            None => Operation::Insert((f.take().unwrap())()),
+        };
+
+        match self.compute_ref(key, to_owned, compute, guard) {
+            Compute::Aborted(value) => value,
+            Compute::Inserted(_, value) => value,
+            _ => unreachable!(),
+        }
     }
 
-    /// Updates an existing entry or inserts a default value computed from a closure.
+    /// Returns a reference to the value corresponding to the key, or inserts a value
+    /// computed from a closure, calling the closure at most once across all threads.
     ///
-    /// If the value for the specified `key` is present, the new value is computed and stored the
-    /// using the provided update function, and the new value is returned. Otherwise, the value
-    /// computed by `f` is inserted into the map, and a reference to the newly inserted value is
-    /// returned.
+    /// Unlike [`get_or_insert_with`](HashMap::get_or_insert_with), which may call the closure
+    /// on every thread racing to insert the same key, this method installs a placeholder for
+    /// the key while the closure runs. Any other calls to `get_or_init_once` for the same key
+    /// block until the value is inserted, and then return it. This is useful when the
+    /// initializer is expensive or has side effects.
     ///
-    /// See [`HashMap::update`] for details about how atomic updates are performed.
+    /// If the closure panics, the placeholder is removed and one of the waiting threads
+    /// retries the initialization with its own closure.
+    ///
+    /// Note that only calls to `get_or_init_once` wait for the placeholder; other operations
+    /// treat the key as absent until the value is inserted. If the key is inserted by another
+    /// operation while the closure runs, the existing value is returned and the computed value
+    /// is dropped.
     ///
     /// # Examples
     ///
@@ -690,103 +1309,361 @@ where
     /// use papaya::HashMap;
     ///
     /// let map = HashMap::new();
-    /// assert_eq!(*map.pin().update_or_insert_with("a", |i| i + 1, || 0), 0);
-    /// assert_eq!(*map.pin().update_or_insert_with("a", |i| i + 1, || 0), 1);
+    /// assert_eq!(map.pin().get_or_init_once("a", || 3), &3);
+    /// assert_eq!(map.pin().get_or_init_once("a", || unreachable!()), &3);
     /// ```
     #[inline]
-    pub fn update_or_insert_with<'g, U, F>(
+    pub fn get_or_init_once<'g, F>(&self, key: K, f: F, guard: &'g impl Guard) -> &'g V
+    where
+        F: FnOnce() -> V,
+        K: 'g, // TODO: this bound is necessary because `raw::HashMap::get` returns the full entry.
+    {
+        self.raw.get_or_init_once(key, f, guard)
+    }
+
+    /// Returns a reference to the value corresponding to the key, or inserts a value
+    /// resolved from a future, running at most one future across all concurrent callers.
+    ///
+    /// This is the asynchronous counterpart of [`get_or_init_once`](HashMap::get_or_init_once).
+    /// The first caller for a missing key installs a placeholder and runs the future returned
+    /// by `f`, while any other callers for the same key wait for the value to be inserted.
+    /// Waiting does not block the thread, and only relies on the standard [`Waker`] API, so it
+    /// can be used with any async runtime.
+    ///
+    /// If the leading future is cancelled by being dropped before it completes, the placeholder
+    /// is removed and one of the waiting callers takes over the initialization with its own
+    /// future.
+    ///
+    /// To hold a reference to the map across `.await` points in a multi-threaded runtime, use
+    /// [`HashMap::pin_owned`].
+    ///
+    /// [`Waker`]: std::task::Waker
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// # async fn load(key: &str) -> usize { key.len() }
+    /// # async fn run() {
+    /// let map = HashMap::new();
+    /// let m = map.pin_owned();
+    ///
+    /// let value = m.get_or_insert_async("a", || load("a")).await;
+    /// assert_eq!(value, &1);
+    /// # }
+    /// ```
+    #[inline]
+    pub async fn get_or_insert_async<'g, F, Fut>(
         &self,
         key: K,
-        update: U,
         f: F,
         guard: &'g impl Guard,
     ) -> &'g V
     where
-        F: FnOnce() -> V,
-        U: Fn(&V) -> V,
-        K: 'g, // TODO: this bound is necessary because `HashMap::compute` returns the full entry.
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+        K: 'g, // TODO: this bound is necessary because `raw::HashMap::get` returns the full entry.
+        V: 'g,
     {
-        let mut f = Some(f);
-        let compute = |entry| match entry {
-            // Perform the update.
-            Some((_, value)) => Operation::Insert::<_, ()>(update(value)),
-            // Insert the initial value.
-            None => Operation::Insert((f.take().unwrap())()),
+        let result = self
+            .try_get_or_insert_async(key, || async { Ok::<_, Infallible>(f().await) }, guard)
+            .await;
+
+        match result {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key, or inserts a value
+    /// resolved from a fallible future, running at most one future across all concurrent callers.
+    ///
+    /// If the future returns an error, the error is returned, the placeholder is removed,
+    /// and one of the waiting callers retries the initialization with its own future.
+    ///
+    /// See [`HashMap::get_or_insert_async`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// # async fn run() {
+    /// let map = HashMap::new();
+    /// let m = map.pin_owned();
+    ///
+    /// let result = m.try_get_or_insert_async("a", || async { Err("failed") }).await;
+    /// assert_eq!(result, Err("failed"));
+    ///
+    /// let result = m.try_get_or_insert_async("a", || async { Ok::<_, &str>(1) }).await;
+    /// assert_eq!(result, Ok(&1));
+    /// # }
+    /// ```
+    #[inline]
+    pub async fn try_get_or_insert_async<'g, F, Fut, E>(
+        &self,
+        key: K,
+        f: F,
+        guard: &'g impl Guard,
+    ) -> Result<&'g V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+        K: 'g, // TODO: this bound is necessary because `raw::HashMap::get` returns the full entry.
+        V: 'g,
+    {
+        let mut key = key;
+
+        let initializer = loop {
+            match self.raw.claim(key, guard) {
+                raw::Claim::Present(value) => return Ok(value),
+                raw::Claim::Claimed(initializer) => break initializer,
+
+                // Another thread or task is initializing the value, wait for it to complete.
+                //
+                // If the initializer was abandoned, the value will not be present and we
+                // can retry the initialization.
+                raw::Claim::Pending(returned, status) => {
+                    status.wait_async().await;
+                    key = returned;
+                }
+            }
         };
 
-        match self.compute(key, compute, guard) {
-            Compute::Updated {
-                new: (_, value), ..
-            } => value,
-            Compute::Inserted(_, value) => value,
-            _ => unreachable!(),
+        // If the future fails or is dropped, the initializer is dropped and the placeholder
+        // is removed, allowing a waiting task to retry.
+        let value = f().await?;
+
+        Ok(self.raw.complete(initializer, value, guard))
+    }
+
+    /// Blocks the current thread until the key is present in the map, returning a
+    /// reference to its value.
+    ///
+    /// This is useful for using the map as a rendezvous point between producers and consumers.
+    /// If the key is already present, its value is returned immediately. Otherwise, the thread
+    /// is parked until another thread inserts the key.
+    ///
+    /// Note that the guard is held while blocking, which prevents the reclamation of any
+    /// objects retired in the meantime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    ///
+    /// std::thread::scope(|s| {
+    ///     s.spawn(|| {
+    ///         map.pin().insert("ready", 1);
+    ///     });
+    ///
+    ///     assert_eq!(map.pin().wait_for(&"ready"), &1);
+    /// });
+    /// ```
+    #[inline]
+    pub fn wait_for<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> &'g V
+    where
+        K: 'g, // TODO: this bound is necessary because `raw::HashMap::get` returns the full entry.
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.raw.wait_for(key, None, guard).unwrap()
+    }
+
+    /// Blocks the current thread until the key is present in the map or the timeout
+    /// elapses, returning a reference to its value.
+    ///
+    /// Returns `None` if the key was not inserted before the timeout.
+    ///
+    /// See [`HashMap::wait_for`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    /// use std::time::Duration;
+    ///
+    /// let map = HashMap::<&str, i32>::new();
+    /// let m = map.pin();
+    ///
+    /// assert_eq!(m.wait_for_timeout(&"ready", Duration::from_millis(10)), None);
+    /// ```
+    #[inline]
+    pub fn wait_for_timeout<'g, Q>(
+        &self,
+        key: &Q,
+        timeout: Duration,
+        guard: &'g impl Guard,
+    ) -> Option<&'g V>
+    where
+        K: 'g, // TODO: this bound is necessary because `raw::HashMap::get` returns the full entry.
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        // An overflowing deadline is treated as no deadline.
+        let deadline = Instant::now().checked_add(timeout);
+        self.raw.wait_for(key, deadline, guard)
+    }
+
+    /// Returns a [`Watch`] that is notified whenever the key is inserted, updated, or removed.
+    ///
+    /// The watch observes any changes made after this method returns. Awaiting
+    /// [`Watch::changed`] resolves once the key has changed since the last notification, after
+    /// which the current value can be read from the map.
+    ///
+    /// The returned watch does not hold a guard, so it can be held across `.await` points
+    /// alongside a map reference pinned with [`HashMap::pin_owned`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// # async fn run() {
+    /// let map = HashMap::new();
+    /// let m = map.pin_owned();
+    ///
+    /// let mut watch = m.watch(&"key");
+    /// m.insert("key", 1);
+    ///
+    /// watch.changed().await;
+    /// assert_eq!(m.get(&"key"), Some(&1));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn watch<Q>(&self, key: &Q) -> Watch<'_>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        Watch {
+            registration: self.raw.watch(key),
         }
     }
 
-    /// Updates an entry with a compare-and-swap (CAS) function.
+    /// Updates an existing entry atomically.
     ///
-    /// This method allows you to perform complex operations on the map atomically. The `compute`
-    /// closure is given the current state of the entry and returns the operation that should be
-    /// performed. The operation is applied atomically only if the state of the entry remains the same,
-    /// meaning it is not concurrently modified in any way.
+    /// If the value for the specified `key` is present, the new value is computed and stored the
+    /// using the provided update function, and the new value is returned. Otherwise, `None`
+    /// is returned.
     ///
-    /// Note that the `compute` function should be pure as it may be called multiple times, and
-    /// the output for a given entry may be memoized across retries.
     ///
-    /// In most cases you can avoid this method and instead use a higher-level atomic operation.
-    /// See the [crate-level documentation](crate#atomic-operations) for details.
+    /// The update function is given the current value associated with the given key and returns the
+    /// new value to be stored. The operation is applied atomically only if the state of the entry remains
+    /// the same, meaning that it is not concurrently modified in any way. If the entry is
+    /// modified, the operation is retried with the new entry, similar to a traditional [compare-and-swap](https://en.wikipedia.org/wiki/Compare-and-swap)
+    /// operation.
+    ///
+    /// Note that the `update` function should be pure as it may be called multiple times, and the output
+    /// for a given entry may be memoized across retries.
+    ///
     ///
     /// # Examples
     ///
-    /// ```rust
-    /// use papaya::{HashMap, Operation, Compute};
+    /// ```
+    /// use papaya::HashMap;
     ///
     /// let map = HashMap::new();
-    /// let map = map.pin();
+    /// map.pin().insert("a", 1);
+    /// assert_eq!(map.pin().get(&"a"), Some(&1));
     ///
-    /// let compute = |entry| match entry {
-    ///     // Remove the value if it is even.
-    ///     Some((_key, value)) if value % 2 == 0 => {
-    ///         Operation::Remove
-    ///     }
+    /// map.pin().update("a", |v| v + 1);
+    /// assert_eq!(map.pin().get(&"a"), Some(&2));
+    /// ```
+    #[inline]
+    pub fn update<'g, F>(&self, key: K, update: F, guard: &'g impl Guard) -> Option<&'g V>
+    where
+        F: Fn(&V) -> V,
+        K: 'g,
+    {
+        self.raw.root(guard).update(key, update, guard)
+    }
+
+    /// Updates an existing entry atomically, looking up the key by reference.
     ///
-    ///     // Increment the value if it is odd.
-    ///     Some((_key, value)) => {
-    ///         Operation::Insert(value + 1)
-    ///     }
+    /// This behaves like [`HashMap::update`], but the key may be any borrowed form of the map's
+    /// key type, or any other [`Equivalent`] type. The key is only converted to an owned key with
+    /// `to_owned` if the entry is present and must be replaced, so missing keys never allocate.
     ///
-    ///     // Do nothing if the key does not exist
-    ///     None => Operation::Abort(()),
-    /// };
+    /// # Examples
     ///
-    /// assert_eq!(map.compute('A', compute), Compute::Aborted(()));
+    /// ```
+    /// use papaya::HashMap;
     ///
-    /// map.insert('A', 1);
-    /// assert_eq!(map.compute('A', compute), Compute::Updated {
-    ///     old: (&'A', &1),
-    ///     new: (&'A', &2),
-    /// });
-    /// assert_eq!(map.compute('A', compute), Compute::Removed(&'A', &2));
+    /// let map = HashMap::new();
+    /// map.pin().insert("a".to_owned(), 1);
+    ///
+    /// assert_eq!(map.pin().update_ref("a", str::to_owned, |v| v + 1), Some(&2));
+    /// assert_eq!(map.pin().update_ref("b", |_| unreachable!(), |v| v + 1), None);
     /// ```
     #[inline]
-    pub fn compute<'g, F, T>(
+    pub fn update_ref<'g, Q, C, F>(
+        &self,
+        key: &Q,
+        to_owned: C,
+        update: F,
+        guard: &'g impl Guard,
+    ) -> Option<&'g V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+        F: Fn(&V) -> V,
+        K: 'g,
+    {
+        let compute = |entry| match entry {
+            Some((_, value)) => Operation::Insert(update(value)),
+            None => Operation::Abort(()),
+        };
+
+        match self.compute_ref(key, to_owned, compute, guard) {
+            Compute::Updated {
+                new: (_, value), ..
+            } => Some(value),
+            Compute::Aborted(_) => None,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Updates an existing entry or inserts a default value.
+    ///
+    /// If the value for the specified `key` is present, the new value is computed and stored the
+    /// using the provided update function, and the new value is returned. Otherwise, the provided
+    /// `value` is inserted into the map, and a reference to the newly inserted value is returned.
+    ///
+    /// See [`HashMap::update`] for details about how atomic updates are performed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// assert_eq!(*map.pin().update_or_insert("a", |i| i + 1, 0), 0);
+    /// assert_eq!(*map.pin().update_or_insert("a", |i| i + 1, 0), 1);
+    /// ```
+    #[inline]
+    pub fn update_or_insert<'g, F>(
         &self,
         key: K,
-        compute: F,
+        update: F,
+        value: V,
         guard: &'g impl Guard,
-    ) -> Compute<'g, K, V, T>
+    ) -> &'g V
     where
-        F: FnMut(Option<(&'g K, &'g V)>) -> Operation<V, T>,
+        F: Fn(&V) -> V,
+        K: 'g, // TODO: this bound is necessary because `HashMap::compute` returns the full entry.
     {
-        self.raw.root(guard).compute(key, compute, guard)
+        self.update_or_insert_with(key, update, || value, guard)
     }
 
-    /// Removes a key from the map, returning the value at the key if the key
-    /// was previously in the map.
+    /// Updates an existing entry or inserts a default value computed from a closure.
+    ///
+    /// If the value for the specified `key` is present, the new value is computed and stored the
+    /// using the provided update function, and the new value is returned. Otherwise, the value
+    /// computed by `f` is inserted into the map, and a reference to the newly inserted value is
+    /// returned.
     ///
-    /// The key may be any borrowed form of the map's key type, but
-    /// [`Hash`] and [`Eq`] on the borrowed form *must* match those for
-    /// the key type.
+    /// See [`HashMap::update`] for details about how atomic updates are performed.
     ///
     /// # Examples
     ///
@@ -794,116 +1671,884 @@ where
     /// use papaya::HashMap;
     ///
     /// let map = HashMap::new();
-    /// map.pin().insert(1, "a");
+    /// assert_eq!(*map.pin().update_or_insert_with("a", |i| i + 1, || 0), 0);
+    /// assert_eq!(*map.pin().update_or_insert_with("a", |i| i + 1, || 0), 1);
+    /// ```
+    #[inline]
+    pub fn update_or_insert_with<'g, U, F>(
+        &self,
+        key: K,
+        update: U,
+        f: F,
+        guard: &'g impl Guard,
+    ) -> &'g V
+    where
+        F: FnOnce() -> V,
+        U: Fn(&V) -> V,
+        K: 'g, // TODO: this bound is necessary because `HashMap::compute` returns the full entry.
+    {
+        let mut f = Some(f);
+        let compute = |entry| match entry {
+            // Perform the update.
+            Some((_, value)) => Operation::Insert::<_, ()>(update(value)),
+            // Insert the initial value.
+            None => Operation::Insert((f.take().unwrap())()),
+        };
+
+        match self.compute(key, compute, guard) {
+            Compute::Updated {
+                new: (_, value), ..
+            } => value,
+            Compute::Inserted(_, value) => value,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Updates an existing entry or inserts a default value, looking up the key by reference.
+    ///
+    /// This behaves like [`HashMap::update_or_insert`], but the key may be any borrowed form of
+    /// the map's key type, or any other [`Equivalent`] type. The key is only converted to an
+    /// owned key with `to_owned` when a new entry is written to the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::<String, i32>::new();
+    /// assert_eq!(*map.pin().update_or_insert_ref("a", str::to_owned, |i| i + 1, 0), 0);
+    /// assert_eq!(*map.pin().update_or_insert_ref("a", str::to_owned, |i| i + 1, 0), 1);
+    /// ```
+    #[inline]
+    pub fn update_or_insert_ref<'g, Q, C, F>(
+        &self,
+        key: &Q,
+        to_owned: C,
+        update: F,
+        value: V,
+        guard: &'g impl Guard,
+    ) -> &'g V
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+        F: Fn(&V) -> V,
+        K: 'g,
+    {
+        self.update_or_insert_with_ref(key, to_owned, update, || value, guard)
+    }
+
+    /// Updates an existing entry or inserts a default value computed from a closure, looking up
+    /// the key by reference.
+    ///
+    /// This behaves like [`HashMap::update_or_insert_with`], but the key may be any borrowed form
+    /// of the map's key type, or any other [`Equivalent`] type. The key is only converted to an
+    /// owned key with `to_owned` when a new entry is written to the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::<String, i32>::new();
+    /// assert_eq!(*map.pin().update_or_insert_with_ref("a", str::to_owned, |i| i + 1, || 0), 0);
+    /// assert_eq!(*map.pin().update_or_insert_with_ref("a", str::to_owned, |i| i + 1, || 0), 1);
+    /// ```
+    #[inline]
+    pub fn update_or_insert_with_ref<'g, Q, C, U, F>(
+        &self,
+        key: &Q,
+        to_owned: C,
+        update: U,
+        f: F,
+        guard: &'g impl Guard,
+    ) -> &'g V
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+        F: FnOnce() -> V,
+        U: Fn(&V) -> V,
+        K: 'g,
+    {
+        let mut f = Some(f);
+        let compute = |entry| match entry {
+            // Perform the update.
+            Some((_, value)) => Operation::Insert::<_, ()>(update(value)),
+            // Insert the initial value.
+            None => Operation::Insert((f.take().unwrap())()),
+        };
+
+        match self.compute_ref(key, to_owned, compute, guard) {
+            Compute::Updated {
+                new: (_, value), ..
+            } => value,
+            Compute::Inserted(_, value) => value,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Updates an entry with a compare-and-swap (CAS) function.
+    ///
+    /// This method allows you to perform complex operations on the map atomically. The `compute`
+    /// closure is given the current state of the entry and returns the operation that should be
+    /// performed. The operation is applied atomically only if the state of the entry remains the same,
+    /// meaning it is not concurrently modified in any way.
+    ///
+    /// Note that the `compute` function should be pure as it may be called multiple times, and
+    /// the output for a given entry may be memoized across retries.
+    ///
+    /// In most cases you can avoid this method and instead use a higher-level atomic operation.
+    /// See the [crate-level documentation](crate#atomic-operations) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use papaya::{HashMap, Operation, Compute};
+    ///
+    /// let map = HashMap::new();
+    /// let map = map.pin();
+    ///
+    /// let compute = |entry| match entry {
+    ///     // Remove the value if it is even.
+    ///     Some((_key, value)) if value % 2 == 0 => {
+    ///         Operation::Remove
+    ///     }
+    ///
+    ///     // Increment the value if it is odd.
+    ///     Some((_key, value)) => {
+    ///         Operation::Insert(value + 1)
+    ///     }
+    ///
+    ///     // Do nothing if the key does not exist
+    ///     None => Operation::Abort(()),
+    /// };
+    ///
+    /// assert_eq!(map.compute('A', compute), Compute::Aborted(()));
+    ///
+    /// map.insert('A', 1);
+    /// assert_eq!(map.compute('A', compute), Compute::Updated {
+    ///     old: (&'A', &1),
+    ///     new: (&'A', &2),
+    /// });
+    /// assert_eq!(map.compute('A', compute), Compute::Removed(&'A', &2));
+    /// ```
+    #[inline]
+    pub fn compute<'g, F, T>(
+        &self,
+        key: K,
+        compute: F,
+        guard: &'g impl Guard,
+    ) -> Compute<'g, K, V, T>
+    where
+        F: FnMut(Option<(&'g K, &'g V)>) -> Operation<V, T>,
+    {
+        self.raw.root(guard).compute(key, compute, guard)
+    }
+
+    /// Updates an entry with a compare-and-swap (CAS) function, looking up the key by reference.
+    ///
+    /// This behaves like [`HashMap::compute`], but the key may be any borrowed form of the map's
+    /// key type, or any other [`Equivalent`] type. The key is only converted to an owned key with
+    /// `to_owned` if a new entry must be written to the map, meaning `compute` did not return
+    /// [`Operation::Abort`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use papaya::{HashMap, Operation, Compute};
+    ///
+    /// let map = HashMap::new();
+    /// let map = map.pin();
+    ///
+    /// // Insert the value only if the key is missing.
+    /// let compute = |entry| match entry {
+    ///     Some(_) => Operation::Abort(()),
+    ///     None => Operation::Insert(1),
+    /// };
+    ///
+    /// assert_eq!(
+    ///     map.compute_ref("a", str::to_owned, compute),
+    ///     Compute::Inserted(&"a".to_owned(), &1)
+    /// );
+    ///
+    /// // The key is already present, so it is never converted.
+    /// assert_eq!(
+    ///     map.compute_ref("a", |_| unreachable!(), compute),
+    ///     Compute::Aborted(())
+    /// );
+    /// ```
+    #[inline]
+    pub fn compute_ref<'g, Q, C, F, T>(
+        &self,
+        key: &Q,
+        to_owned: C,
+        compute: F,
+        guard: &'g impl Guard,
+    ) -> Compute<'g, K, V, T>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+        F: FnMut(Option<(&'g K, &'g V)>) -> Operation<V, T>,
+    {
+        self.raw
+            .root(guard)
+            .compute_ref(key, to_owned, compute, guard)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    ///
+    /// The key may be any borrowed form of the map's key type, or any other
+    /// [`Equivalent`] type, but [`Hash`] and [`Eq`] on the borrowed form *must*
+    /// match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// map.pin().insert(1, "a");
     /// assert_eq!(map.pin().remove(&1), Some(&"a"));
     /// assert_eq!(map.pin().remove(&1), None);
     /// ```
     #[inline]
-    pub fn remove<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> Option<&'g V>
-    where
-        K: Borrow<Q> + 'g, // TODO: this bound is necessary because `raw::HashMap::remove` returns the full entry.
-        Q: Hash + Eq + ?Sized,
-    {
-        match self.raw.root(guard).remove(key, guard) {
-            Some((_, value)) => Some(value),
-            None => None,
+    pub fn remove<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> Option<&'g V>
+    where
+        K: 'g, // TODO: this bound is necessary because `raw::HashMap::remove` returns the full entry.
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        match self.raw.root(guard).remove(key, guard) {
+            Some((_, value)) => Some(value),
+            None => None,
+        }
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the
+    /// key was previously in the map.
+    ///
+    /// The key may be any borrowed form of the map's key type, or any other
+    /// [`Equivalent`] type, but [`Hash`] and [`Eq`] on the borrowed form *must*
+    /// match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// map.pin().insert(1, "a");
+    /// assert_eq!(map.pin().get(&1), Some(&"a"));
+    /// assert_eq!(map.pin().remove_entry(&1), Some((&1, &"a")));
+    /// assert_eq!(map.pin().remove(&1), None);
+    /// ```
+    #[inline]
+    pub fn remove_entry<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> Option<(&'g K, &'g V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.raw.root(guard).remove(key, guard)
+    }
+
+    /// Removes the key with the given hash from the map, using `eq` to find the matching key,
+    /// and returns the value at the key if the key was previously in the map.
+    ///
+    /// This skips hashing the key, and allows removing keys by views that cannot implement
+    /// of an [`Equivalent`] type. The hash *must* match the hash produced by the map's
+    /// [`hasher`](HashMap::hasher) for the key, otherwise the key may not be found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    /// use std::hash::BuildHasher;
+    ///
+    /// let map = HashMap::new();
+    /// map.pin().insert((1, "a".to_owned()), "value");
+    ///
+    /// let hash = map.hasher().hash_one((1, "a"));
+    /// let eq = |k: &(i32, String)| k.0 == 1 && k.1 == "a";
+    /// assert_eq!(map.pin().remove_with_hash(hash, eq), Some(&"value"));
+    /// assert_eq!(map.pin().remove_with_hash(hash, eq), None);
+    /// ```
+    #[inline]
+    pub fn remove_with_hash<'g, F>(
+        &self,
+        hash: u64,
+        mut eq: F,
+        guard: &'g impl Guard,
+    ) -> Option<&'g V>
+    where
+        K: 'g,
+        F: FnMut(&K) -> bool,
+    {
+        self.raw
+            .root(guard)
+            .remove_hashed(hash, &mut eq, guard)
+            .map(|(_, v)| v)
+    }
+
+    /// Removes a key from the map if its current value satisfies the given predicate.
+    ///
+    /// Returns `Ok(Some(value))` with the removed value if the key was removed, `Ok(None)`
+    /// if the key was not in the map, or `Err(value)` with the current value if it did
+    /// not satisfy the predicate.
+    ///
+    /// The predicate is evaluated atomically with the removal: the entry is only removed
+    /// if it was not modified after being passed to the predicate. If it was, the predicate
+    /// is called again with the new value.
+    ///
+    /// The key may be any borrowed form of the map's key type, or any other
+    /// [`Equivalent`] type, but [`Hash`] and [`Eq`] on the borrowed form *must*
+    /// match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// map.pin().insert(1, 10);
+    ///
+    /// assert_eq!(map.pin().remove_if(&1, |v| *v > 10), Err(&10));
+    /// assert_eq!(map.pin().remove_if(&1, |v| *v == 10), Ok(Some(&10)));
+    /// assert_eq!(map.pin().remove_if(&1, |_| true), Ok(None));
+    /// ```
+    #[inline]
+    pub fn remove_if<'g, Q, F>(
+        &self,
+        key: &Q,
+        should_remove: F,
+        guard: &'g impl Guard,
+    ) -> Result<Option<&'g V>, &'g V>
+    where
+        K: 'g, // TODO: this bound is necessary because `raw::HashMap::remove_if` returns the full entry.
+        Q: Hash + Equivalent<K> + ?Sized,
+        F: FnMut(&V) -> bool,
+    {
+        match self.raw.root(guard).remove_if(key, should_remove, guard) {
+            Ok(entry) => Ok(entry.map(|(_, value)| value)),
+            Err((_, value)) => Err(value),
+        }
+    }
+
+    /// Replaces the value for a key if it is currently equal to `current`.
+    ///
+    /// On success, returns a reference to the new value. Otherwise, returns a
+    /// [`CompareExchangeError`] containing the current value, or `None` if the key is
+    /// not in the map, along with the value that was not inserted.
+    ///
+    /// This is equivalent to a [`compute`](HashMap::compute) operation that checks the
+    /// current value, but avoids the overhead of the more general API.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let m = map.pin();
+    /// m.insert("a", 1);
+    ///
+    /// assert_eq!(m.compare_exchange("a", &1, 2), Ok(&2));
+    ///
+    /// let err = m.compare_exchange("a", &1, 3).unwrap_err();
+    /// assert_eq!(err.current, Some(&2));
+    /// assert_eq!(err.new, 3);
+    ///
+    /// let err = m.compare_exchange("b", &1, 3).unwrap_err();
+    /// assert_eq!(err.current, None);
+    /// ```
+    #[inline]
+    pub fn compare_exchange<'g>(
+        &self,
+        key: K,
+        current: &V,
+        new: V,
+        guard: &'g impl Guard,
+    ) -> Result<&'g V, CompareExchangeError<'g, V>>
+    where
+        V: PartialEq,
+    {
+        self.raw
+            .root(guard)
+            .replace_if(key, new, |value| value == current, guard)
+            .map_err(|(current, new)| CompareExchangeError { current, new })
+    }
+
+    /// Replaces the value of an entry if it has not been modified since it was
+    /// returned by [`get_entry`](HashMap::get_entry).
+    ///
+    /// The replacement entry owns its key, so an owned key equal to the entry's key
+    /// must be provided. On success, returns a reference to the new value. If the entry
+    /// was replaced or removed concurrently, the value that was not inserted is returned
+    /// as an error.
+    ///
+    /// Entries are compared by identity rather than by value, so this succeeds only if
+    /// the map still holds the exact entry that was observed. Because the entry cannot
+    /// be reclaimed while its guard is held, this is not susceptible to the ABA problem.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not equal to the key of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let m = map.pin();
+    /// m.insert(1, "a");
+    ///
+    /// let entry = m.get_entry(&1).unwrap();
+    /// m.insert(1, "a");
+    ///
+    /// // The entry was replaced by an equal value, but is no longer the same entry.
+    /// assert_eq!(m.replace_if_unchanged(entry, 1, "b"), Err("b"));
+    ///
+    /// let entry = m.get_entry(&1).unwrap();
+    /// assert_eq!(m.replace_if_unchanged(entry, 1, "b"), Ok(&"b"));
+    /// ```
+    #[inline]
+    pub fn replace_if_unchanged<'g>(
+        &self,
+        entry: EntryRef<'_, K, V>,
+        key: K,
+        value: V,
+        guard: &'g impl Guard,
+    ) -> Result<&'g V, V> {
+        self.raw
+            .root(guard)
+            .replace_slot(entry.slot, key, value, guard)
+    }
+
+    /// Removes an entry if it has not been modified since it was returned by
+    /// [`get_entry`](HashMap::get_entry).
+    ///
+    /// Returns `true` if the entry was removed. See [`HashMap::replace_if_unchanged`]
+    /// for details about how entries are compared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let m = map.pin();
+    /// m.insert(1, "a");
+    ///
+    /// let entry = m.get_entry(&1).unwrap();
+    /// m.insert(1, "b");
+    /// assert!(!m.remove_if_unchanged(entry));
+    ///
+    /// let entry = m.get_entry(&1).unwrap();
+    /// assert!(m.remove_if_unchanged(entry));
+    /// assert!(m.is_empty());
+    /// ```
+    #[inline]
+    pub fn remove_if_unchanged(&self, entry: EntryRef<'_, K, V>, guard: &impl Guard) -> bool {
+        self.raw.root(guard).remove_slot(entry.slot, guard)
+    }
+
+    /// Tries to reserve capacity for `additional` more elements to be inserted
+    /// in the `HashMap`.
+    ///
+    /// After calling this method, the table should be able to hold at least `capacity` elements
+    /// before resizing. However, the capacity is an estimate, and the table may prematurely resize
+    /// due to poor hash distribution. The collection may also reserve more space to avoid frequent
+    /// reallocations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new allocation size overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map: HashMap<&str, i32> = HashMap::new();
+    /// map.pin().reserve(10);
+    /// ```
+    #[inline]
+    pub fn reserve(&self, additional: usize, guard: &impl Guard) {
+        self.raw.root(guard).reserve(additional, guard);
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    ///
+    /// The table is migrated to a smaller table that should be able to hold the current
+    /// number of elements without resizing. Note that if a resize is already in-progress,
+    /// the resulting capacity may be larger than requested.
+    ///
+    /// See [`ShrinkMode`] for configuring automatic shrinking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// for i in 0..1000 {
+    ///     map.pin().insert(i, i);
+    /// }
+    ///
+    /// for i in 10..1000 {
+    ///     map.pin().remove(&i);
+    /// }
+    ///
+    /// map.pin().shrink_to_fit();
+    /// assert_eq!(map.pin().len(), 10);
+    /// ```
+    #[inline]
+    pub fn shrink_to_fit(&self, guard: &impl Guard) {
+        self.raw.root(guard).shrink_to(0, guard);
+    }
+
+    /// Shrinks the capacity of the map with a lower limit.
+    ///
+    /// The table is migrated to a smaller table that should be able to hold at least
+    /// `min_capacity` elements, or the current number of elements if it is larger, without
+    /// resizing. If the current capacity is already smaller than the requested capacity,
+    /// this method does nothing.
+    ///
+    /// See [`ShrinkMode`] for configuring automatic shrinking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map: HashMap<i32, i32> = HashMap::with_capacity(1000);
+    /// map.pin().insert(1, 2);
+    /// map.pin().shrink_to(10);
+    /// assert_eq!(map.pin().get(&1), Some(&2));
+    /// ```
+    #[inline]
+    pub fn shrink_to(&self, min_capacity: usize, guard: &impl Guard) {
+        self.raw.root(guard).shrink_to(min_capacity, guard);
+    }
+
+    /// Compacts the map, removing any deleted slots from the table.
+    ///
+    /// Removed entries leave behind tombstones that lengthen the probe sequences of later
+    /// operations. If the table has any tombstones, it is migrated to a new table of the same
+    /// capacity that does not contain them. Note that if a resize is already in-progress, it is
+    /// completed instead, so the resulting capacity may differ.
+    ///
+    /// See [`CompactMode`] for configuring automatic compaction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// for i in 0..1000 {
+    ///     map.pin().insert(i, i);
+    /// }
+    ///
+    /// for i in 0..500 {
+    ///     map.pin().remove(&i);
+    /// }
+    ///
+    /// map.pin().compact();
+    /// assert_eq!(map.pin().stats().tombstones, 0);
+    /// assert_eq!(map.pin().len(), 500);
+    /// ```
+    #[inline]
+    pub fn compact(&self, guard: &impl Guard) {
+        self.raw.root(guard).compact(guard);
+    }
+
+    /// Clears the map, removing all key-value pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    ///
+    /// map.pin().insert(1, "a");
+    /// map.pin().clear();
+    /// assert!(map.pin().is_empty());
+    /// ```
+    #[inline]
+    pub fn clear(&self, guard: &impl Guard) {
+        self.raw.root(guard).clear(guard)
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// In other words, remove all pairs `(k, v)` for which `f(&k, &v)` returns `false`.
+    /// The elements are visited in unordered (and unspecified) order.
+    ///
+    /// An entry is only removed if it was not modified concurrently after being passed
+    /// to the predicate. If it was, the predicate is called again with the new value.
+    /// Note that the predicate may also be called more than once for a given entry if
+    /// the map is resized concurrently, so it should not rely on being called exactly
+    /// once per key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map: HashMap<i32, i32> = (0..8).map(|x| (x, x * 10)).collect();
+    /// map.pin().retain(|&k, _| k % 2 == 0);
+    /// assert_eq!(map.len(), 4);
+    /// ```
+    #[inline]
+    pub fn retain<F>(&self, mut f: F, guard: &impl Guard)
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        self.raw
+            .root(guard)
+            .extract_if(|key, value| !f(key, value), guard)
+            .for_each(drop)
+    }
+
+    /// Returns an iterator that removes and yields all entries for which the
+    /// predicate returns `true`.
+    ///
+    /// The iterator element type is `(&K, &V)`. As with [`HashMap::retain`], an entry is
+    /// only removed if it was not modified concurrently after being passed to the predicate,
+    /// and the predicate may be called more than once for a given entry if the map is
+    /// resized concurrently.
+    ///
+    /// Entries are removed lazily as the iterator is advanced. If the iterator is
+    /// dropped before being fully consumed, the remaining entries are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map: HashMap<i32, i32> = (0..8).map(|x| (x, x)).collect();
+    ///
+    /// let m = map.pin();
+    /// let mut evens: Vec<_> = m.extract_if(|&k, _| k % 2 == 0).map(|(&k, _)| k).collect();
+    /// evens.sort();
+    ///
+    /// assert_eq!(evens, vec![0, 2, 4, 6]);
+    /// assert_eq!(m.len(), 4);
+    /// ```
+    #[inline]
+    pub fn extract_if<'g, F, G>(&self, f: F, guard: &'g G) -> ExtractIf<'g, K, V, S, F, G, A>
+    where
+        F: FnMut(&K, &V) -> bool,
+        G: Guard,
+    {
+        ExtractIf {
+            raw: self.raw.root(guard).extract_if(f, guard),
+        }
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator.
+    ///
+    /// Because this method requires unique access to the map, entries are
+    /// moved out of the table directly, without deferred reclamation.
+    ///
+    /// If the returned iterator is dropped before being fully consumed, it
+    /// drops the remaining key-value pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let mut map = HashMap::from([(1, "a"), (2, "b")]);
+    ///
+    /// let mut drained: Vec<_> = map.drain().collect();
+    /// drained.sort();
+    ///
+    /// assert_eq!(drained, [(1, "a"), (2, "b")]);
+    /// assert!(map.is_empty());
+    /// ```
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, K, V, A> {
+        Drain {
+            raw: self.raw.drain(),
         }
     }
 
-    /// Removes a key from the map, returning the stored key and value if the
-    /// key was previously in the map.
-    ///
-    /// The key may be any borrowed form of the map's key type, but
-    /// [`Hash`] and [`Eq`] on the borrowed form *must* match those for
-    /// the key type.
+    /// An iterator visiting all key-value pairs in arbitrary order.
+    /// The iterator element type is `(&K, &V)`.
     ///
     /// # Examples
     ///
     /// ```
     /// use papaya::HashMap;
     ///
-    /// let map = HashMap::new();
-    /// map.pin().insert(1, "a");
-    /// assert_eq!(map.pin().get(&1), Some(&"a"));
-    /// assert_eq!(map.pin().remove_entry(&1), Some((&1, &"a")));
-    /// assert_eq!(map.pin().remove(&1), None);
-    /// ```
+    /// let map = HashMap::from([
+    ///     ("a", 1),
+    ///     ("b", 2),
+    ///     ("c", 3),
+    /// ]);
+    ///
+    /// for (key, val) in map.pin().iter() {
+    ///     println!("key: {key} val: {val}");
+    /// }
     #[inline]
-    pub fn remove_entry<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> Option<(&'g K, &'g V)>
+    pub fn iter<'g, G>(&self, guard: &'g G) -> Iter<'g, K, V, G>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        G: Guard,
     {
-        self.raw.root(guard).remove(key, guard)
+        Iter {
+            raw: self.raw.root(guard).iter(guard),
+        }
     }
 
-    /// Tries to reserve capacity for `additional` more elements to be inserted
-    /// in the `HashMap`.
+    /// Splits the map into `shard_count` iterators over disjoint subsets of key-value pairs.
     ///
-    /// After calling this method, the table should be able to hold at least `capacity` elements
-    /// before resizing. However, the capacity is an estimate, and the table may prematurely resize
-    /// due to poor hash distribution. The collection may also reserve more space to avoid frequent
-    /// reallocations.
+    /// The slots of the table are split into `shard_count` contiguous ranges, and the returned
+    /// [`Shards`] yields an iterator over each range. This allows scanning the map in parallel
+    /// across multiple threads, without any further coordination.
+    ///
+    /// Like [`HashMap::iter`], any resize that is in progress is completed before the shards are
+    /// created. Every shard iterates over the same table, so together the shards visit every
+    /// entry that is present throughout the scan exactly once, even if the map is resized
+    /// concurrently. Like [`HashMap::iter`], entries inserted or removed concurrently may or may
+    /// not be visited.
+    ///
+    /// The shards borrow the guard, so sending them to other threads requires a guard that
+    /// is [`Sync`], such as one returned by [`HashMap::owned_guard`].
     ///
     /// # Panics
     ///
-    /// Panics if the new allocation size overflows `usize`.
+    /// Panics if `shard_count` is zero.
     ///
     /// # Examples
     ///
     /// ```
     /// use papaya::HashMap;
     ///
-    /// let map: HashMap<&str, i32> = HashMap::new();
-    /// map.pin().reserve(10);
+    /// let map: HashMap<usize, usize> = (0..1000).map(|x| (x, x)).collect();
+    /// let guard = map.owned_guard();
+    ///
+    /// let sum = std::thread::scope(|s| {
+    ///     let shards: Vec<_> = map
+    ///         .iter_shards(4, &guard)
+    ///         .map(|shard| s.spawn(move || shard.map(|(_, v)| v).sum::<usize>()))
+    ///         .collect();
+    ///
+    ///     shards.into_iter().map(|shard| shard.join().unwrap()).sum::<usize>()
+    /// });
+    ///
+    /// assert_eq!(sum, (0..1000).sum());
     /// ```
     #[inline]
-    pub fn reserve(&self, additional: usize, guard: &impl Guard) {
-        self.raw.root(guard).reserve(additional, guard);
+    pub fn iter_shards<'g, G>(&self, shard_count: usize, guard: &'g G) -> Shards<'g, K, V, G>
+    where
+        G: Guard,
+    {
+        assert!(shard_count > 0, "`shard_count` must be greater than zero");
+
+        Shards {
+            raw: self.raw.root(guard).iter(guard),
+            index: 0,
+            count: shard_count,
+        }
     }
 
-    /// Clears the map, removing all key-value pairs.
+    /// An iterator visiting all key-value pairs in arbitrary order, resuming from a [`Cursor`].
+    ///
+    /// This allows a long scan to be split into multiple steps, each using a fresh guard,
+    /// so that a single guard does not prevent memory from being reclaimed for the
+    /// duration of the scan. The position of an iterator can be recorded with
+    /// [`Iter::cursor`], and iteration continued later with a different guard. A
+    /// [`Cursor::default`] starts from the beginning of the map.
+    ///
+    /// Cursors record a position in a specific table. If the map is not resized between
+    /// steps, iteration continues from the recorded slot, and every entry that is present
+    /// throughout the scan is visited exactly once. If the map was resized, slots of the old
+    /// table cannot be mapped to the new table, so iteration restarts from the beginning of
+    /// the new table. Every entry present throughout the scan is still visited at least once,
+    /// but entries visited before the resize may be visited again. Like [`HashMap::iter`],
+    /// entries inserted or removed concurrently may or may not be visited.
+    ///
+    /// Resuming from a cursor created by a different map is not supported, and may skip
+    /// entries or visit entries multiple times.
     ///
     /// # Examples
     ///
     /// ```
-    /// use papaya::HashMap;
+    /// use papaya::{Cursor, HashMap};
+    ///
+    /// let map: HashMap<usize, usize> = (0..1000).map(|x| (x, x)).collect();
+    ///
+    /// let mut sum = 0;
+    /// let mut cursor = Cursor::default();
+    /// loop {
+    ///     // Visit up to 100 entries with each guard.
+    ///     let guard = map.guard();
+    ///     let mut iter = map.iter_from(cursor, &guard);
+    ///     let step: Vec<_> = iter.by_ref().take(100).map(|(_, &v)| v).collect();
+    ///     if step.is_empty() {
+    ///         break;
+    ///     }
     ///
-    /// let map = HashMap::new();
+    ///     sum += step.iter().sum::<usize>();
+    ///     cursor = iter.cursor();
+    /// }
     ///
-    /// map.pin().insert(1, "a");
-    /// map.pin().clear();
-    /// assert!(map.pin().is_empty());
+    /// assert_eq!(sum, (0..1000).sum());
     /// ```
     #[inline]
-    pub fn clear(&self, guard: &impl Guard) {
-        self.raw.root(guard).clear(guard)
+    pub fn iter_from<'g, G>(&self, cursor: Cursor, guard: &'g G) -> Iter<'g, K, V, G>
+    where
+        G: Guard,
+    {
+        Iter {
+            raw: self.raw.root(guard).iter_from(cursor.raw, guard),
+        }
     }
 
-    /// An iterator visiting all key-value pairs in arbitrary order.
-    /// The iterator element type is `(&K, &V)`.
+    /// An iterator visiting all key-value pairs in arbitrary order, without completing
+    /// any in-progress resize.
+    ///
+    /// [`HashMap::iter`] completes any in-progress resize before iterating, which in
+    /// [`ResizeMode::Incremental`] means copying the remaining entries of the table on the
+    /// calling thread. This iterator instead visits both the old table and any tables it is
+    /// being resized to, reading entries that have already been copied from the new table.
+    /// It never copies entries itself, so it does not introduce the latency spike of a full
+    /// migration.
+    ///
+    /// Every entry that is present throughout the iteration is visited exactly once, even
+    /// if it is copied to a new table concurrently. Like [`HashMap::iter`], entries inserted
+    /// or removed concurrently may or may not be visited. Entries that are copied by the
+    /// resize are skipped in the new table without a lookup, but entries that were inserted
+    /// or updated in the new table are checked against previous tables, so iteration is
+    /// somewhat slower while a resize is in progress.
+    ///
+    /// In [`ResizeMode::Blocking`], this is equivalent to [`HashMap::iter`].
     ///
     /// # Examples
     ///
     /// ```
-    /// use papaya::HashMap;
+    /// use papaya::{HashMap, ResizeMode};
     ///
-    /// let map = HashMap::from([
-    ///     ("a", 1),
-    ///     ("b", 2),
-    ///     ("c", 3),
-    /// ]);
+    /// let map = HashMap::builder()
+    ///     .resize_mode(ResizeMode::Incremental(64))
+    ///     .build();
     ///
-    /// for (key, val) in map.pin().iter() {
-    ///     println!("key: {key} val: {val}");
+    /// let map = map.pin();
+    /// for i in 0..1000 {
+    ///     map.insert(i, i);
     /// }
+    ///
+    /// let sum: usize = map.iter_incremental().map(|(_, v)| v).sum();
+    /// assert_eq!(sum, (0..1000).sum());
+    /// ```
     #[inline]
-    pub fn iter<'g, G>(&self, guard: &'g G) -> Iter<'g, K, V, G>
+    pub fn iter_incremental<'g, G>(&self, guard: &'g G) -> IncrementalIter<'g, K, V, S, G, A>
     where
         G: Guard,
     {
-        Iter {
-            raw: self.raw.root(guard).iter(guard),
+        IncrementalIter {
+            raw: self.raw.root(guard).iter_incremental(guard),
         }
     }
 
@@ -962,6 +2607,66 @@ where
             iter: self.iter(guard),
         }
     }
+
+    /// An iterator visiting all key-value pairs in arbitrary order,
+    /// with mutable references to the values.
+    /// The iterator element type is `(&K, &mut V)`.
+    ///
+    /// Because this method requires unique access to the map, it does not
+    /// require a guard and reads the table without any synchronization.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let mut map = HashMap::from([
+    ///     ("a", 1),
+    ///     ("b", 2),
+    ///     ("c", 3),
+    /// ]);
+    ///
+    /// for (_, val) in map.iter_mut() {
+    ///     *val *= 2;
+    /// }
+    ///
+    /// assert_eq!(map.pin().get("b"), Some(&4));
+    /// ```
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            raw: self.raw.iter_mut(),
+        }
+    }
+
+    /// An iterator visiting all values mutably in arbitrary order.
+    /// The iterator element type is `&mut V`.
+    ///
+    /// See [`HashMap::iter_mut`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let mut map = HashMap::from([
+    ///     ("a", 1),
+    ///     ("b", 2),
+    ///     ("c", 3),
+    /// ]);
+    ///
+    /// for val in map.values_mut() {
+    ///     *val += 10;
+    /// }
+    ///
+    /// assert_eq!(map.pin().get("a"), Some(&11));
+    /// ```
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            iter: self.iter_mut(),
+        }
+    }
 }
 
 /// An operation to perform on given entry in a [`HashMap`].
@@ -1003,6 +2708,107 @@ pub enum Compute<'g, K, V, T> {
     Aborted(T),
 }
 
+/// A watch for changes to a key in a [`HashMap`].
+///
+/// This struct is created by the [`watch`](HashMap::watch) method on [`HashMap`].
+/// The watch stays registered until it is dropped, so no changes are missed between
+/// notifications.
+pub struct Watch<'map> {
+    registration: raw::Registration<'map>,
+}
+
+impl Watch<'_> {
+    /// Waits until the key is inserted, updated, or removed.
+    ///
+    /// Resolves immediately if the key changed since the watch was created or the last
+    /// notification was received. Multiple changes may be coalesced into a single notification,
+    /// and notifications may occasionally be spurious.
+    ///
+    /// See [`HashMap::watch`] for details.
+    #[inline]
+    pub async fn changed(&mut self) {
+        future::poll_fn(|cx| self.poll_changed(cx)).await
+    }
+
+    /// Polls for a change to the key.
+    ///
+    /// Returns [`Poll::Ready`] if the key changed since the watch was created or the last
+    /// notification was received. This can be used to adapt the watch into a stream of
+    /// notifications.
+    ///
+    /// See [`Watch::changed`] for details.
+    #[inline]
+    pub fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.registration.poll(cx)
+    }
+}
+
+impl fmt::Debug for Watch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watch").finish_non_exhaustive()
+    }
+}
+
+/// A handle to an entry in a [`HashMap`].
+///
+/// This struct is created by the [`get_entry`](HashMap::get_entry) method on [`HashMap`].
+/// It identifies the exact entry that was observed, and can be used to update the entry
+/// only if it has not been modified since.
+pub struct EntryRef<'g, K, V> {
+    key: &'g K,
+    value: &'g V,
+    // The observed entry and the table slot it was found in.
+    slot: raw::Slot<K, V>,
+}
+
+impl<'g, K, V> EntryRef<'g, K, V> {
+    /// Returns a reference to the entry's key.
+    #[inline]
+    pub fn key(&self) -> &'g K {
+        self.key
+    }
+
+    /// Returns a reference to the entry's value.
+    #[inline]
+    pub fn value(&self) -> &'g V {
+        self.value
+    }
+}
+
+impl<K, V> Clone for EntryRef<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for EntryRef<'_, K, V> {}
+
+impl<K, V> fmt::Debug for EntryRef<'_, K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntryRef")
+            .field("key", self.key)
+            .field("value", self.value)
+            .finish()
+    }
+}
+
+/// An error returned by [`compare_exchange`](HashMap::compare_exchange) when the current
+/// value does not match.
+///
+/// Contains the value that was found, and the value that was not inserted.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CompareExchangeError<'a, V: 'a> {
+    /// The value in the map, or `None` if the key was not present.
+    pub current: Option<&'a V>,
+    /// The value which was not inserted, because the current value did not match.
+    pub new: V,
+}
+
 /// An error returned by [`try_insert`](HashMap::try_insert) when the key already exists.
 ///
 /// Contains the existing value, and the value that was not inserted.
@@ -1014,11 +2820,12 @@ pub struct OccupiedError<'a, V: 'a> {
     pub not_inserted: V,
 }
 
-impl<K, V, S> PartialEq for HashMap<K, V, S>
+impl<K, V, S, A> PartialEq for HashMap<K, V, S, A>
 where
     K: Hash + Eq,
     V: PartialEq,
     S: BuildHasher,
+    A: Allocator,
 {
     fn eq(&self, other: &Self) -> bool {
         if self.len() != other.len() {
@@ -1032,19 +2839,21 @@ where
     }
 }
 
-impl<K, V, S> Eq for HashMap<K, V, S>
+impl<K, V, S, A> Eq for HashMap<K, V, S, A>
 where
     K: Hash + Eq,
     V: Eq,
     S: BuildHasher,
+    A: Allocator,
 {
 }
 
-impl<K, V, S> fmt::Debug for HashMap<K, V, S>
+impl<K, V, S, A> fmt::Debug for HashMap<K, V, S, A>
 where
     K: Hash + Eq + fmt::Debug,
     V: fmt::Debug,
     S: BuildHasher,
+    A: Allocator,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let guard = self.guard();
@@ -1052,10 +2861,11 @@ where
     }
 }
 
-impl<K, V, S> Extend<(K, V)> for &HashMap<K, V, S>
+impl<K, V, S, A> Extend<(K, V)> for &HashMap<K, V, S, A>
 where
     K: Hash + Eq,
     S: BuildHasher,
+    A: Allocator,
 {
     fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
         // from `hashbrown::HashMap::extend`:
@@ -1079,11 +2889,12 @@ where
     }
 }
 
-impl<'a, K, V, S> Extend<(&'a K, &'a V)> for &HashMap<K, V, S>
+impl<'a, K, V, S, A> Extend<(&'a K, &'a V)> for &HashMap<K, V, S, A>
 where
     K: Copy + Hash + Eq,
     V: Copy,
     S: BuildHasher,
+    A: Allocator,
 {
     fn extend<T: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: T) {
         self.extend(iter.into_iter().map(|(&key, &value)| (key, value)));
@@ -1099,10 +2910,11 @@ where
     }
 }
 
-impl<K, V, S> FromIterator<(K, V)> for HashMap<K, V, S>
+impl<K, V, S, A> FromIterator<(K, V)> for HashMap<K, V, S, A>
 where
     K: Hash + Eq,
     S: BuildHasher + Default,
+    A: Allocator + Default,
 {
     fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
         let mut iter = iter.into_iter();
@@ -1112,7 +2924,11 @@ where
             let guard = unsafe { seize::unprotected() };
 
             let (lower, _) = iter.size_hint();
-            let map = HashMap::with_capacity_and_hasher(lower.saturating_add(1), S::default());
+            let map = HashMap::builder()
+                .capacity(lower.saturating_add(1))
+                .hasher(S::default())
+                .allocator(A::default())
+                .build();
 
             map.insert(key, value, &guard);
 
@@ -1127,16 +2943,37 @@ where
     }
 }
 
-impl<K, V, S> Clone for HashMap<K, V, S>
+impl<K, V, S, A> IntoIterator for HashMap<K, V, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    A: Allocator,
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, S, A>;
+
+    /// Creates a consuming iterator visiting all key-value pairs in arbitrary order.
+    ///
+    /// Entries are moved out of the table directly, without deferred reclamation.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            raw: self.raw.into_iter(),
+        }
+    }
+}
+
+impl<K, V, S, A> Clone for HashMap<K, V, S, A>
 where
     K: Clone + Hash + Eq,
     V: Clone,
     S: BuildHasher + Clone,
+    A: Allocator + Clone,
 {
-    fn clone(&self) -> HashMap<K, V, S> {
+    fn clone(&self) -> HashMap<K, V, S, A> {
         let other = HashMap::builder()
             .capacity(self.len())
             .hasher(self.raw.hasher.clone())
+            .allocator(self.allocator().clone())
             .collector(self.raw.collector().clone())
             .build();
 
@@ -1155,20 +2992,21 @@ where
 ///
 /// This type is created with [`HashMap::pin`] and can be used to easily access a [`HashMap`]
 /// without explicitly managing a guard. See the [crate-level documentation](crate#usage) for details.
-pub struct HashMapRef<'map, K, V, S, G> {
-    guard: G,
-    map: &'map HashMap<K, V, S>,
+pub struct HashMapRef<'map, K, V, S, G, A: Allocator = Global> {
+    pub(crate) guard: G,
+    pub(crate) map: &'map HashMap<K, V, S, A>,
 }
 
-impl<'map, K, V, S, G> HashMapRef<'map, K, V, S, G>
+impl<'map, K, V, S, G, A> HashMapRef<'map, K, V, S, G, A>
 where
     K: Hash + Eq,
     S: BuildHasher,
     G: Guard,
+    A: Allocator,
 {
     /// Returns a reference to the inner [`HashMap`].
     #[inline]
-    pub fn map(&self) -> &'map HashMap<K, V, S> {
+    pub fn map(&self) -> &'map HashMap<K, V, S, A> {
         self.map
     }
 
@@ -1188,14 +3026,21 @@ where
         self.map.is_empty()
     }
 
+    /// Returns statistics about the internal state of the map.
+    ///
+    /// See [`HashMap::stats`] for details.
+    #[inline]
+    pub fn stats(&self) -> MapStats {
+        self.map.stats(&self.guard)
+    }
+
     /// Returns `true` if the map contains a value for the specified key.
     ///
     /// See [`HashMap::contains_key`] for details.
     #[inline]
     pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.map.contains_key(key, &self.guard)
     }
@@ -1206,8 +3051,7 @@ where
     #[inline]
     pub fn get<Q>(&self, key: &Q) -> Option<&V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.map.get(key, &self.guard)
     }
@@ -1218,12 +3062,23 @@ where
     #[inline]
     pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.map.get_key_value(key, &self.guard)
     }
 
+    /// Returns a reference to the value corresponding to the key with the given hash,
+    /// using `eq` to find the matching key.
+    ///
+    /// See [`HashMap::get_with_hash`] for details.
+    #[inline]
+    pub fn get_with_hash<F>(&self, hash: u64, eq: F) -> Option<&V>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        self.map.get_with_hash(hash, eq, &self.guard)
+    }
+
     /// Inserts a key-value pair into the map.
     ///
     /// See [`HashMap::insert`] for details.
@@ -1232,6 +3087,14 @@ where
         self.map.insert(key, value, &self.guard)
     }
 
+    /// Inserts a key-value pair into the map using a precomputed hash.
+    ///
+    /// See [`HashMap::insert_with_hash`] for details.
+    #[inline]
+    pub fn insert_with_hash(&self, hash: u64, key: K, value: V) -> Option<&V> {
+        self.map.insert_with_hash(hash, key, value, &self.guard)
+    }
+
     /// Tries to insert a key-value pair into the map, and returns
     /// a reference to the value that was inserted.
     ///
@@ -1251,12 +3114,112 @@ where
     /// Returns a reference to the value corresponding to the key, or inserts a default value
     /// computed from a closure.
     ///
-    /// See [`HashMap::get_or_insert_with`] for details.
-    pub fn get_or_insert_with<F>(&self, key: K, f: F) -> &V
+    /// See [`HashMap::get_or_insert_with`] for details.
+    pub fn get_or_insert_with<F>(&self, key: K, f: F) -> &V
+    where
+        F: FnOnce() -> V,
+    {
+        self.map.get_or_insert_with(key, f, &self.guard)
+    }
+
+    /// Returns a reference to the value corresponding to the key, or inserts a default value,
+    /// looking up the key by reference.
+    ///
+    /// See [`HashMap::get_or_insert_ref`] for details.
+    pub fn get_or_insert_ref<Q, C>(&self, key: &Q, to_owned: C, value: V) -> &V
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+    {
+        self.map
+            .get_or_insert_ref(key, to_owned, value, &self.guard)
+    }
+
+    /// Returns a reference to the value corresponding to the key, or inserts a default value
+    /// computed from a closure, looking up the key by reference.
+    ///
+    /// See [`HashMap::get_or_insert_with_ref`] for details.
+    pub fn get_or_insert_with_ref<Q, C, F>(&self, key: &Q, to_owned: C, f: F) -> &V
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+        F: FnOnce() -> V,
+    {
+        self.map
+            .get_or_insert_with_ref(key, to_owned, f, &self.guard)
+    }
+
+    /// Returns a reference to the value corresponding to the key, or inserts a value
+    /// computed from a closure, calling the closure at most once across all threads.
+    ///
+    /// See [`HashMap::get_or_init_once`] for details.
+    #[inline]
+    pub fn get_or_init_once<F>(&self, key: K, f: F) -> &V
+    where
+        F: FnOnce() -> V,
+    {
+        self.map.get_or_init_once(key, f, &self.guard)
+    }
+
+    /// Returns a reference to the value corresponding to the key, or inserts a value
+    /// resolved from a future, running at most one future across all concurrent callers.
+    ///
+    /// See [`HashMap::get_or_insert_async`] for details.
+    #[inline]
+    pub async fn get_or_insert_async<F, Fut>(&self, key: K, f: F) -> &V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        self.map.get_or_insert_async(key, f, &self.guard).await
+    }
+
+    /// Returns a reference to the value corresponding to the key, or inserts a value
+    /// resolved from a fallible future, running at most one future across all concurrent callers.
+    ///
+    /// See [`HashMap::try_get_or_insert_async`] for details.
+    #[inline]
+    pub async fn try_get_or_insert_async<F, Fut, E>(&self, key: K, f: F) -> Result<&V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        self.map.try_get_or_insert_async(key, f, &self.guard).await
+    }
+
+    /// Blocks the current thread until the key is present in the map, returning a
+    /// reference to its value.
+    ///
+    /// See [`HashMap::wait_for`] for details.
+    #[inline]
+    pub fn wait_for<Q>(&self, key: &Q) -> &V
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.map.wait_for(key, &self.guard)
+    }
+
+    /// Blocks the current thread until the key is present in the map or the timeout
+    /// elapses, returning a reference to its value.
+    ///
+    /// See [`HashMap::wait_for_timeout`] for details.
+    #[inline]
+    pub fn wait_for_timeout<Q>(&self, key: &Q, timeout: Duration) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.map.wait_for_timeout(key, timeout, &self.guard)
+    }
+
+    /// Returns a [`Watch`] that is notified whenever the key is inserted, updated, or removed.
+    ///
+    /// See [`HashMap::watch`] for details.
+    #[inline]
+    pub fn watch<Q>(&self, key: &Q) -> Watch<'_>
     where
-        F: FnOnce() -> V,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
-        self.map.get_or_insert_with(key, f, &self.guard)
+        self.map.watch(key)
     }
 
     /// Updates an existing entry atomically.
@@ -1269,6 +3232,18 @@ where
         self.map.update(key, update, &self.guard)
     }
 
+    /// Updates an existing entry atomically, looking up the key by reference.
+    ///
+    /// See [`HashMap::update_ref`] for details.
+    pub fn update_ref<Q, C, F>(&self, key: &Q, to_owned: C, update: F) -> Option<&V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+        F: Fn(&V) -> V,
+    {
+        self.map.update_ref(key, to_owned, update, &self.guard)
+    }
+
     /// Updates an existing entry or inserts a default value.
     ///
     /// See [`HashMap::update_or_insert`] for details.
@@ -1290,6 +3265,34 @@ where
         self.map.update_or_insert_with(key, update, f, &self.guard)
     }
 
+    /// Updates an existing entry or inserts a default value, looking up the key by reference.
+    ///
+    /// See [`HashMap::update_or_insert_ref`] for details.
+    pub fn update_or_insert_ref<Q, C, F>(&self, key: &Q, to_owned: C, update: F, value: V) -> &V
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+        F: Fn(&V) -> V,
+    {
+        self.map
+            .update_or_insert_ref(key, to_owned, update, value, &self.guard)
+    }
+
+    /// Updates an existing entry or inserts a default value computed from a closure, looking up
+    /// the key by reference.
+    ///
+    /// See [`HashMap::update_or_insert_with_ref`] for details.
+    pub fn update_or_insert_with_ref<Q, C, U, F>(&self, key: &Q, to_owned: C, update: U, f: F) -> &V
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+        F: FnOnce() -> V,
+        U: Fn(&V) -> V,
+    {
+        self.map
+            .update_or_insert_with_ref(key, to_owned, update, f, &self.guard)
+    }
+
     // Updates an entry with a compare-and-swap (CAS) function.
     //
     /// See [`HashMap::compute`] for details.
@@ -1301,6 +3304,24 @@ where
         self.map.compute(key, compute, &self.guard)
     }
 
+    /// Updates an entry with a compare-and-swap (CAS) function, looking up the key by reference.
+    ///
+    /// See [`HashMap::compute_ref`] for details.
+    #[inline]
+    pub fn compute_ref<'g, Q, C, F, T>(
+        &'g self,
+        key: &Q,
+        to_owned: C,
+        compute: F,
+    ) -> Compute<'g, K, V, T>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+        F: FnMut(Option<(&'g K, &'g V)>) -> Operation<V, T>,
+    {
+        self.map.compute_ref(key, to_owned, compute, &self.guard)
+    }
+
     /// Removes a key from the map, returning the value at the key if the key
     /// was previously in the map.
     ///
@@ -1308,8 +3329,7 @@ where
     #[inline]
     pub fn remove<Q>(&self, key: &Q) -> Option<&V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.map.remove(key, &self.guard)
     }
@@ -1321,12 +3341,86 @@ where
     #[inline]
     pub fn remove_entry<Q>(&self, key: &Q) -> Option<(&K, &V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
         self.map.remove_entry(key, &self.guard)
     }
 
+    /// Removes the key with the given hash from the map, using `eq` to find the matching key,
+    /// and returns the value at the key if the key was previously in the map.
+    ///
+    /// See [`HashMap::remove_with_hash`] for details.
+    #[inline]
+    pub fn remove_with_hash<F>(&self, hash: u64, eq: F) -> Option<&V>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        self.map.remove_with_hash(hash, eq, &self.guard)
+    }
+
+    /// Removes a key from the map if its current value satisfies the given predicate.
+    ///
+    /// See [`HashMap::remove_if`] for details.
+    #[inline]
+    pub fn remove_if<Q, F>(&self, key: &Q, should_remove: F) -> Result<Option<&V>, &V>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        F: FnMut(&V) -> bool,
+    {
+        self.map.remove_if(key, should_remove, &self.guard)
+    }
+
+    /// Replaces the value for a key if it is currently equal to `current`.
+    ///
+    /// See [`HashMap::compare_exchange`] for details.
+    #[inline]
+    pub fn compare_exchange(
+        &self,
+        key: K,
+        current: &V,
+        new: V,
+    ) -> Result<&V, CompareExchangeError<'_, V>>
+    where
+        V: PartialEq,
+    {
+        self.map.compare_exchange(key, current, new, &self.guard)
+    }
+
+    /// Returns a handle to the entry corresponding to the supplied key.
+    ///
+    /// See [`HashMap::get_entry`] for details.
+    #[inline]
+    pub fn get_entry<Q>(&self, key: &Q) -> Option<EntryRef<'_, K, V>>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.map.get_entry(key, &self.guard)
+    }
+
+    /// Replaces the value of an entry if it has not been modified since it was
+    /// returned by [`get_entry`](HashMapRef::get_entry).
+    ///
+    /// See [`HashMap::replace_if_unchanged`] for details.
+    #[inline]
+    pub fn replace_if_unchanged(
+        &self,
+        entry: EntryRef<'_, K, V>,
+        key: K,
+        value: V,
+    ) -> Result<&V, V> {
+        self.map
+            .replace_if_unchanged(entry, key, value, &self.guard)
+    }
+
+    /// Removes an entry if it has not been modified since it was returned by
+    /// [`get_entry`](HashMapRef::get_entry).
+    ///
+    /// See [`HashMap::remove_if_unchanged`] for details.
+    #[inline]
+    pub fn remove_if_unchanged(&self, entry: EntryRef<'_, K, V>) -> bool {
+        self.map.remove_if_unchanged(entry, &self.guard)
+    }
+
     /// Clears the map, removing all key-value pairs.
     ///
     /// See [`HashMap::clear`] for details.
@@ -1335,6 +3429,29 @@ where
         self.map.clear(&self.guard)
     }
 
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// See [`HashMap::retain`] for details.
+    #[inline]
+    pub fn retain<F>(&self, f: F)
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        self.map.retain(f, &self.guard)
+    }
+
+    /// Returns an iterator that removes and yields all entries for which the
+    /// predicate returns `true`.
+    ///
+    /// See [`HashMap::extract_if`] for details.
+    #[inline]
+    pub fn extract_if<F>(&self, f: F) -> ExtractIf<'_, K, V, S, F, G, A>
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        self.map.extract_if(f, &self.guard)
+    }
+
     /// Tries to reserve capacity for `additional` more elements to be inserted
     /// in the map.
     ///
@@ -1344,6 +3461,30 @@ where
         self.map.reserve(additional, &self.guard)
     }
 
+    /// Shrinks the capacity of the map as much as possible.
+    ///
+    /// See [`HashMap::shrink_to_fit`] for details.
+    #[inline]
+    pub fn shrink_to_fit(&self) {
+        self.map.shrink_to_fit(&self.guard)
+    }
+
+    /// Shrinks the capacity of the map with a lower limit.
+    ///
+    /// See [`HashMap::shrink_to`] for details.
+    #[inline]
+    pub fn shrink_to(&self, min_capacity: usize) {
+        self.map.shrink_to(min_capacity, &self.guard)
+    }
+
+    /// Compacts the map, removing any deleted slots from the table.
+    ///
+    /// See [`HashMap::compact`] for details.
+    #[inline]
+    pub fn compact(&self) {
+        self.map.compact(&self.guard)
+    }
+
     /// An iterator visiting all key-value pairs in arbitrary order.
     /// The iterator element type is `(&K, &V)`.
     ///
@@ -1353,6 +3494,31 @@ where
         self.map.iter(&self.guard)
     }
 
+    /// Splits the map into `shard_count` iterators over disjoint subsets of key-value pairs.
+    ///
+    /// See [`HashMap::iter_shards`] for details.
+    #[inline]
+    pub fn iter_shards(&self, shard_count: usize) -> Shards<'_, K, V, G> {
+        self.map.iter_shards(shard_count, &self.guard)
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order, resuming from a [`Cursor`].
+    ///
+    /// See [`HashMap::iter_from`] for details.
+    #[inline]
+    pub fn iter_from(&self, cursor: Cursor) -> Iter<'_, K, V, G> {
+        self.map.iter_from(cursor, &self.guard)
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order, without completing
+    /// any in-progress resize.
+    ///
+    /// See [`HashMap::iter_incremental`] for details.
+    #[inline]
+    pub fn iter_incremental(&self) -> IncrementalIter<'_, K, V, S, G, A> {
+        self.map.iter_incremental(&self.guard)
+    }
+
     /// An iterator visiting all keys in arbitrary order.
     /// The iterator element type is `&K`.
     ///
@@ -1372,23 +3538,25 @@ where
     }
 }
 
-impl<K, V, S, G> fmt::Debug for HashMapRef<'_, K, V, S, G>
+impl<K, V, S, G, A> fmt::Debug for HashMapRef<'_, K, V, S, G, A>
 where
     K: Hash + Eq + fmt::Debug,
     V: fmt::Debug,
     S: BuildHasher,
     G: Guard,
+    A: Allocator,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_map().entries(self.iter()).finish()
     }
 }
 
-impl<'a, K, V, S, G> IntoIterator for &'a HashMapRef<'_, K, V, S, G>
+impl<'a, K, V, S, G, A> IntoIterator for &'a HashMapRef<'_, K, V, S, G, A>
 where
     K: Hash + Eq,
     S: BuildHasher,
     G: Guard,
+    A: Allocator,
 {
     type Item = (&'a K, &'a V);
     type IntoIter = Iter<'a, K, V, G>;
@@ -1417,6 +3585,53 @@ where
     }
 }
 
+impl<K, V, G> Iter<'_, K, V, G> {
+    /// Splits the remaining entries of this iterator into two disjoint iterators.
+    ///
+    /// This iterator will visit the first half of its remaining table slots, and the
+    /// returned iterator will visit the second half. Both iterators observe the same table,
+    /// so together they visit every remaining entry exactly once, even if the map is resized
+    /// concurrently. The iterators can be split further and sent to other threads to
+    /// scan the map in parallel.
+    ///
+    /// Returns `None` if the remaining range is too small to be worth splitting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map: HashMap<usize, usize> = (0..1000).map(|x| (x, x)).collect();
+    /// let guard = map.owned_guard();
+    ///
+    /// let mut first = map.iter(&guard);
+    /// let second = first.split().unwrap();
+    ///
+    /// let sum = std::thread::scope(|s| {
+    ///     let second = s.spawn(move || second.map(|(_, v)| v).sum::<usize>());
+    ///     first.map(|(_, v)| v).sum::<usize>() + second.join().unwrap()
+    /// });
+    ///
+    /// assert_eq!(sum, (0..1000).sum());
+    /// ```
+    #[inline]
+    pub fn split(&mut self) -> Option<Self> {
+        self.raw.split().map(|raw| Iter { raw })
+    }
+
+    /// Returns the position of this iterator in the map.
+    ///
+    /// The returned cursor can be used to resume iteration with a different guard using
+    /// [`HashMap::iter_from`]. Resuming from the cursor of a split iterator visits the
+    /// remaining entries of the whole map, not just those of the split range.
+    #[inline]
+    pub fn cursor(&self) -> Cursor {
+        Cursor {
+            raw: self.raw.cursor(),
+        }
+    }
+}
+
 impl<K, V, G> fmt::Debug for Iter<'_, K, V, G>
 where
     K: fmt::Debug,
@@ -1432,6 +3647,206 @@ where
     }
 }
 
+/// An iterator over disjoint shards of a map's entries.
+///
+/// This struct is created by the [`iter_shards`](HashMap::iter_shards) method on [`HashMap`].
+/// See its documentation for details.
+pub struct Shards<'g, K, V, G> {
+    raw: raw::Iter<'g, K, V, G>,
+    index: usize,
+    count: usize,
+}
+
+impl<'g, K: 'g, V: 'g, G> Iterator for Shards<'g, K, V, G>
+where
+    G: Guard,
+{
+    type Item = Iter<'g, K, V, G>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.count {
+            return None;
+        }
+
+        let raw = self.raw.shard(self.index, self.count);
+        self.index += 1;
+        Some(Iter { raw })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'g, K: 'g, V: 'g, G> ExactSizeIterator for Shards<'g, K, V, G> where G: Guard {}
+
+impl<K, V, G> fmt::Debug for Shards<'_, K, V, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shards")
+            .field("remaining", &(self.count - self.index))
+            .finish()
+    }
+}
+
+/// An iterator over a map's entries that does not complete in-progress resizes.
+///
+/// This struct is created by the [`iter_incremental`](HashMap::iter_incremental) method on [`HashMap`]. See its documentation for details.
+pub struct IncrementalIter<'g, K, V, S, G, A: Allocator = Global> {
+    raw: raw::IncrementalIter<'g, K, V, S, A, G>,
+}
+
+impl<'g, K: 'g, V: 'g, S, G, A> Iterator for IncrementalIter<'g, K, V, S, G, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: Guard,
+    A: Allocator,
+{
+    type Item = (&'g K, &'g V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw.next()
+    }
+}
+
+impl<K, V, S, G, A: Allocator> fmt::Debug for IncrementalIter<'_, K, V, S, G, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IncrementalIter").finish_non_exhaustive()
+    }
+}
+
+/// A position in a map, used to resume iteration with a different guard.
+///
+/// Cursors are created by [`Iter::cursor`] and resumed with [`HashMap::iter_from`].
+/// The default cursor starts from the beginning of the map.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Cursor {
+    raw: raw::Cursor,
+}
+
+/// An iterator that removes entries from a map.
+///
+/// This struct is created by the [`extract_if`](HashMap::extract_if) method on [`HashMap`]. See its documentation for details.
+pub struct ExtractIf<'g, K, V, S, F, G, A: Allocator = Global> {
+    raw: raw::ExtractIf<'g, 'g, K, V, S, A, F, G>,
+}
+
+impl<'g, K: 'g, V: 'g, S, F, G, A> Iterator for ExtractIf<'g, K, V, S, F, G, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    F: FnMut(&K, &V) -> bool,
+    G: Guard,
+    A: Allocator,
+{
+    type Item = (&'g K, &'g V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw.next()
+    }
+}
+
+impl<K, V, S, F, G, A: Allocator> fmt::Debug for ExtractIf<'_, K, V, S, F, G, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractIf").finish_non_exhaustive()
+    }
+}
+
+/// A mutable iterator over a map's entries.
+///
+/// This struct is created by the [`iter_mut`](HashMap::iter_mut) method on [`HashMap`]. See its documentation for details.
+pub struct IterMut<'a, K, V> {
+    raw: raw::IterMut<'a, K, V>,
+}
+
+impl<'a, K: 'a, V: 'a> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw.next()
+    }
+}
+
+impl<K, V> fmt::Debug for IterMut<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IterMut").finish_non_exhaustive()
+    }
+}
+
+/// A mutable iterator over a map's values.
+///
+/// This struct is created by the [`values_mut`](HashMap::values_mut) method on [`HashMap`]. See its documentation for details.
+pub struct ValuesMut<'a, K, V> {
+    iter: IterMut<'a, K, V>,
+}
+
+impl<'a, K: 'a, V: 'a> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (_, value) = self.iter.next()?;
+        Some(value)
+    }
+}
+
+impl<K, V> fmt::Debug for ValuesMut<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ValuesMut").field(&self.iter).finish()
+    }
+}
+
+/// A draining iterator over a map's entries.
+///
+/// This struct is created by the [`drain`](HashMap::drain) method on [`HashMap`]. See its documentation for details.
+pub struct Drain<'a, K, V, A: Allocator = Global> {
+    raw: raw::Drain<'a, K, V, A>,
+}
+
+impl<K, V, A: Allocator> Iterator for Drain<'_, K, V, A> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw.next()
+    }
+}
+
+impl<K, V, A: Allocator> fmt::Debug for Drain<'_, K, V, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Drain").finish_non_exhaustive()
+    }
+}
+
+/// An owning iterator over a map's entries.
+///
+/// This struct is created by the [`into_iter`](IntoIterator::into_iter) method on [`HashMap`]
+/// (provided by the [`IntoIterator`] trait). See its documentation for details.
+pub struct IntoIter<K, V, S, A: Allocator = Global> {
+    raw: raw::IntoIter<K, V, S, A>,
+}
+
+impl<K, V, S, A: Allocator> Iterator for IntoIter<K, V, S, A> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw.next()
+    }
+}
+
+impl<K, V, S, A: Allocator> fmt::Debug for IntoIter<K, V, S, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntoIter").finish_non_exhaustive()
+    }
+}
+
 /// An iterator over a map's keys.
 ///
 /// This struct is created by the [`keys`](HashMap::keys) method on [`HashMap`]. See its documentation for details.
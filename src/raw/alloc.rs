@@ -1,12 +1,397 @@
-use std::alloc::Layout;
+use std::alloc::{handle_alloc_error, Layout};
 use std::marker::PhantomData;
-use std::sync::atomic::{AtomicPtr, AtomicU8};
-use std::{alloc, mem, ptr};
+use std::mem;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
 
 use seize::Collector;
 
+use super::group::Group;
+use super::utils::{self, CachePadded, Counter};
 use super::{probe, State};
 
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2::alloc::{AllocError, Allocator, Global};
+
+#[cfg(not(feature = "allocator-api2"))]
+pub use self::global::{AllocError, Allocator, Global};
+
+// A minimal version of the `Allocator` API, used when the `allocator-api2` feature is disabled.
+//
+// Only the global allocator is supported in this case.
+#[cfg(not(feature = "allocator-api2"))]
+mod global {
+    use std::alloc::{self, Layout};
+    use std::ptr::NonNull;
+
+    // An allocation failure.
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub struct AllocError;
+
+    // An allocator, mirroring `allocator_api2::alloc::Allocator`.
+    //
+    // Note that zero-sized allocations are not supported.
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe trait Allocator {
+        // Attempts to allocate a block of memory.
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError>;
+
+        // Attempts to allocate a zero-initialized block of memory.
+        fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let ptr = self.allocate(layout)?;
+            unsafe { ptr.cast::<u8>().as_ptr().write_bytes(0, layout.size()) };
+            Ok(ptr)
+        }
+
+        // Deallocates a block of memory allocated by this allocator.
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+    }
+
+    // The global memory allocator.
+    #[derive(Clone, Copy, Default, Debug)]
+    pub struct Global;
+
+    unsafe impl Allocator for Global {
+        #[inline]
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let ptr = NonNull::new(unsafe { alloc::alloc(layout) }).ok_or(AllocError)?;
+            Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+        }
+
+        #[inline]
+        fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let ptr = NonNull::new(unsafe { alloc::alloc_zeroed(layout) }).ok_or(AllocError)?;
+            Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+        }
+
+        #[inline]
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            unsafe { alloc::dealloc(ptr.as_ptr(), layout) }
+        }
+    }
+}
+
+// Allocates memory with the given allocator, aborting on allocation failure.
+#[inline]
+pub fn allocate<A: Allocator>(alloc: &A, layout: Layout, zeroed: bool) -> NonNull<u8> {
+    let ptr = if zeroed {
+        alloc.allocate_zeroed(layout)
+    } else {
+        alloc.allocate(layout)
+    };
+
+    match ptr {
+        Ok(ptr) => ptr.cast::<u8>(),
+        Err(_) => handle_alloc_error(layout),
+    }
+}
+
+// A pool of allocations with a fixed layout, cached in free lists sharded by thread.
+//
+// Allocations of any other layout are passed through to the underlying allocator.
+pub struct Pool<A: Allocator> {
+    // The free lists.
+    shards: Box<[CachePadded<FreeList>]>,
+    // The maximum number of allocations cached by each free list.
+    //
+    // A limit of zero disables caching.
+    limit: usize,
+    // The number of allocations that were retired, but not yet reclaimed.
+    retired: Counter,
+    // The layout of pooled allocations.
+    layout: Layout,
+    // A pointer to the underlying allocator, valid as long as the pool is alive.
+    alloc: *const A,
+}
+
+// A lock-free free list of pooled allocations.
+//
+// Allocations are pushed individually, but only ever removed by taking the entire list,
+// which avoids the ABA problem without needing to protect allocations that are in use.
+#[derive(Default)]
+struct FreeList {
+    // The top of the list, linked through the first word of each allocation.
+    head: AtomicPtr<u8>,
+    // The approximate number of allocations in the list.
+    len: AtomicUsize,
+}
+
+impl FreeList {
+    // Pushes an allocation onto the list, returning `false` if the list is full.
+    #[inline]
+    fn push(&self, ptr: NonNull<u8>, limit: usize) -> bool {
+        if self.len.load(Ordering::Relaxed) >= limit {
+            return false;
+        }
+
+        self.len.fetch_add(1, Ordering::Relaxed);
+
+        // Safety: We own the allocation.
+        unsafe { self.push_chain(ptr.as_ptr(), ptr.as_ptr()) };
+        true
+    }
+
+    // Pops an allocation from the list.
+    #[inline]
+    fn pop(&self) -> Option<NonNull<u8>> {
+        // Fast-path, the list is empty.
+        if self.head.load(Ordering::Relaxed).is_null() {
+            return None;
+        }
+
+        // Take the entire list, giving us exclusive access to it.
+        let first = NonNull::new(self.head.swap(ptr::null_mut(), Ordering::Acquire))?;
+        self.len.fetch_sub(1, Ordering::Relaxed);
+
+        // Safety: We own every allocation in the list we took.
+        unsafe {
+            let rest = first.as_ptr().cast::<*mut u8>().read();
+
+            // Return the remaining allocations. The list is usually still empty, in which case
+            // they can be returned without finding the end of the list.
+            if !rest.is_null()
+                && self
+                    .head
+                    .compare_exchange(ptr::null_mut(), rest, Ordering::Release, Ordering::Relaxed)
+                    .is_err()
+            {
+                let mut last = rest;
+                loop {
+                    let next = last.cast::<*mut u8>().read();
+                    if next.is_null() {
+                        break;
+                    }
+                    last = next;
+                }
+
+                self.push_chain(rest, last);
+            }
+        }
+
+        Some(first)
+    }
+
+    // Pushes a chain of allocations linked from `first` to `last` onto the list.
+    //
+    // # Safety
+    //
+    // We must own every allocation in the chain.
+    #[inline]
+    unsafe fn push_chain(&self, first: *mut u8, last: *mut u8) {
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            unsafe { last.cast::<*mut u8>().write(head) };
+
+            match self
+                .head
+                .compare_exchange_weak(head, first, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(found) => head = found,
+            }
+        }
+    }
+
+    // Removes every allocation from the list.
+    #[inline]
+    fn drain(&mut self) -> impl Iterator<Item = NonNull<u8>> {
+        let mut next = mem::replace(self.head.get_mut(), ptr::null_mut());
+        *self.len.get_mut() = 0;
+
+        std::iter::from_fn(move || {
+            let ptr = NonNull::new(next)?;
+
+            // Safety: We have unique access to the list.
+            next = unsafe { ptr.as_ptr().cast::<*mut u8>().read() };
+            Some(ptr)
+        })
+    }
+}
+
+impl<A: Allocator> Pool<A> {
+    // Create a pool for allocations of the given layout.
+    //
+    // Pooled allocations must be large enough to hold a pointer, which links them into
+    // the free list. The allocator must remain valid until the pool is dropped.
+    pub fn new(layout: Layout, limit: usize, alloc: &A) -> Pool<A> {
+        assert!(
+            layout.size() >= mem::size_of::<*mut u8>()
+                && layout.align() >= mem::align_of::<*mut u8>()
+        );
+
+        let shards = (0..utils::shards()).map(|_| Default::default()).collect();
+
+        Pool {
+            shards,
+            limit,
+            layout,
+            alloc,
+            retired: Counter::default(),
+        }
+    }
+
+    // Records an allocation that was retired, and will be deallocated once it is reclaimed.
+    #[inline]
+    pub fn retire(&self) {
+        let retired = self.retired.get(utils::thread_shard());
+        retired.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Records that a retired allocation was reclaimed.
+    #[inline]
+    pub fn reclaimed(&self) {
+        let retired = self.retired.get(utils::thread_shard());
+        retired.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    // Returns the number of allocations that were retired, but not yet reclaimed.
+    #[inline]
+    pub fn retired(&self) -> usize {
+        self.retired.sum()
+    }
+
+    // Returns the free list for the current thread.
+    #[inline]
+    fn free_list(&self) -> &FreeList {
+        &self.shards[utils::thread_shard() & (self.shards.len() - 1)]
+    }
+
+    // Returns the underlying allocator.
+    #[inline]
+    fn alloc(&self) -> &A {
+        // Safety: The allocator is valid as long as the pool is alive.
+        unsafe { &*self.alloc }
+    }
+}
+
+unsafe impl<A: Allocator> Allocator for Pool<A> {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout == self.layout && self.limit != 0 {
+            if let Some(ptr) = self.free_list().pop() {
+                return Ok(NonNull::slice_from_raw_parts(ptr, layout.size()));
+            }
+        }
+
+        self.alloc().allocate(layout)
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout == self.layout && self.limit != 0 && self.free_list().push(ptr, self.limit) {
+            return;
+        }
+
+        unsafe { self.alloc().deallocate(ptr, layout) }
+    }
+}
+
+impl<A: Allocator> Drop for Pool<A> {
+    fn drop(&mut self) {
+        // Return any cached allocations to the underlying allocator.
+        for shard in self.shards.iter_mut() {
+            for ptr in shard.drain() {
+                unsafe { (*self.alloc).deallocate(ptr, self.layout) }
+            }
+        }
+    }
+}
+
+// A metadata byte in the table.
+//
+// Groups of metadata are loaded with word-sized atomics, so individual bytes must be accessed
+// through the word containing them, as mixing atomic accesses of different sizes to the same
+// memory is not allowed. Writes are compare-exchange loops on the word, which only contend
+// with writers to neighboring entries.
+pub struct Meta<'a> {
+    // The word containing the byte.
+    word: &'a AtomicUsize,
+    // The index of the byte in the word, in memory order.
+    byte: usize,
+}
+
+impl<'a> Meta<'a> {
+    // The number of metadata bytes in a word.
+    pub const BYTES: usize = mem::size_of::<usize>();
+
+    // Creates a reference to the byte at the given index in memory order.
+    #[inline]
+    fn new(word: &'a AtomicUsize, byte: usize) -> Meta<'a> {
+        Meta { word, byte }
+    }
+
+    // Returns the offset of the byte in the value of the word.
+    #[inline]
+    fn shift(&self) -> usize {
+        if cfg!(target_endian = "little") {
+            self.byte * 8
+        } else {
+            (Meta::BYTES - 1 - self.byte) * 8
+        }
+    }
+
+    // Returns the byte in the given word value.
+    #[inline]
+    fn get(&self, word: usize) -> u8 {
+        (word >> self.shift()) as u8
+    }
+
+    // Returns the given word value with the byte replaced.
+    #[inline]
+    fn set(&self, word: usize, byte: u8) -> usize {
+        (word & !(0xff << self.shift())) | ((byte as usize) << self.shift())
+    }
+
+    // Loads the metadata byte.
+    #[inline]
+    pub fn load(&self, ordering: Ordering) -> u8 {
+        self.get(self.word.load(ordering))
+    }
+
+    // Stores the metadata byte.
+    #[inline]
+    pub fn store(&self, byte: u8, ordering: Ordering) {
+        let _ = self.word.fetch_update(ordering, Ordering::Relaxed, |word| {
+            Some(self.set(word, byte))
+        });
+    }
+
+    // Stores the metadata byte if it is equal to `current`, returning the previous value.
+    #[inline]
+    pub fn compare_exchange(
+        &self,
+        current: u8,
+        new: u8,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<u8, u8> {
+        let mut word = self.word.load(failure);
+
+        loop {
+            let found = self.get(word);
+            if found != current {
+                return Err(found);
+            }
+
+            match self
+                .word
+                .compare_exchange_weak(word, self.set(word, new), success, failure)
+            {
+                Ok(_) => return Ok(found),
+                Err(next) => word = next,
+            }
+        }
+    }
+
+    // Returns a pointer to the metadata byte.
+    //
+    // Writing through the pointer requires unique access to the table.
+    #[inline]
+    pub fn as_ptr(&self) -> *mut u8 {
+        unsafe { self.word.as_ptr().cast::<u8>().add(self.byte) }
+    }
+}
+
 // A hash-table laid out in a single allocation.
 #[repr(transparent)]
 pub struct RawTable(u8);
@@ -22,7 +407,7 @@ struct TableLayout {
     limit: usize,
     capacity: usize,
     state: State,
-    meta: [AtomicU8; 0],
+    meta: [AtomicUsize; 0],
     entries: [AtomicPtr<()>; 0],
 }
 
@@ -49,24 +434,31 @@ impl<T> Clone for Table<T> {
 }
 
 impl<T> Table<T> {
-    // Allocate a table with the provided length.
-    pub fn alloc(len: usize, collector: &Collector) -> Table<T> {
+    // Allocate a table with the provided length, using the probe limit of the
+    // given configuration.
+    //
+    // Tables hold at least a single group of entries. The allocator must remain valid
+    // until the table is deallocated.
+    pub fn alloc<A: Allocator>(
+        len: usize,
+        probe: &probe::Config,
+        collector: &Collector,
+        alloc: &A,
+    ) -> Table<T> {
         assert!(len.is_power_of_two());
+        let len = len.max(Group::WIDTH);
         assert!(mem::align_of::<seize::Link>() % mem::align_of::<*mut T>() == 0);
 
         // Pad the meta table to fulfill the alignment requirement of an entry.
         let capacity = (len + mem::align_of::<*mut T>() - 1) & !(mem::align_of::<*mut T>() - 1);
         let mask = len - 1;
-        let limit = probe::limit(len);
+        let limit = probe.limit(len);
 
         unsafe {
             let layout = Self::layout(capacity);
 
             // Allocate the table, zeroing the entries.
-            let ptr = alloc::alloc_zeroed(layout);
-            if ptr.is_null() {
-                alloc::handle_alloc_error(layout);
-            }
+            let ptr = allocate(alloc, layout, true).as_ptr();
 
             // Write the table state.
             ptr.cast::<TableLayout>().write(TableLayout {
@@ -76,6 +468,7 @@ impl<T> Table<T> {
                 capacity,
                 state: State {
                     collector,
+                    alloc: (alloc as *const A).cast(),
                     ..State::default()
                 },
                 meta: [],
@@ -123,13 +516,32 @@ impl<T> Table<T> {
 
     // Returns the metadata entry at the given index.
     #[inline]
-    pub unsafe fn meta(&self, i: usize) -> &AtomicU8 {
+    pub unsafe fn meta(&self, i: usize) -> Meta<'_> {
         debug_assert!(i < self.capacity);
-        &*self
-            .raw
-            .add(mem::size_of::<TableLayout>())
-            .add(i * mem::size_of::<u8>())
-            .cast::<AtomicU8>()
+        let word = unsafe { &*self.meta_word(i - i % Meta::BYTES) };
+        Meta::new(word, i % Meta::BYTES)
+    }
+
+    // Returns the group of metadata entries starting at the given index.
+    #[inline]
+    pub unsafe fn group(&self, i: usize) -> Group {
+        debug_assert!(i & (Group::WIDTH - 1) == 0 && i + Group::WIDTH <= self.capacity);
+        unsafe { Group::load(self.meta_word(i)) }
+    }
+
+    // Returns a pointer to the metadata word starting at the given index.
+    //
+    // The index must be a multiple of the word size.
+    #[inline]
+    unsafe fn meta_word(&self, i: usize) -> *const AtomicUsize {
+        // Note that the metadata table starts at the end of the aligned `TableLayout`, and its
+        // length is a multiple of the group width, so it is made up of whole words.
+        unsafe {
+            self.raw
+                .add(mem::size_of::<TableLayout>())
+                .add(i * mem::size_of::<u8>())
+                .cast::<AtomicUsize>()
+        }
     }
 
     // Returns the entry at the given index.
@@ -149,6 +561,12 @@ impl<T> Table<T> {
         self.mask + 1
     }
 
+    // Returns the size of the table allocation in bytes.
+    #[inline]
+    pub fn size(&self) -> usize {
+        Self::layout(self.capacity).size()
+    }
+
     // Returns a reference to the table state.
     #[inline]
     pub fn state(&self) -> &State {
@@ -162,10 +580,16 @@ impl<T> Table<T> {
     }
 
     // Deallocate the table.
-    pub unsafe fn dealloc(table: Table<T>) {
+    //
+    // The table must have been allocated with an allocator of type `A`.
+    pub unsafe fn dealloc<A: Allocator>(table: Table<T>) {
         let layout = Self::layout(table.capacity);
+
+        // Safety: The allocator is valid until the table is deallocated.
+        let alloc = unsafe { &*table.state().alloc.cast::<A>() };
+
         ptr::drop_in_place(table.raw.cast::<TableLayout>());
-        unsafe { alloc::dealloc(table.raw.cast::<u8>(), layout) }
+        unsafe { alloc.deallocate(NonNull::new_unchecked(table.raw.cast::<u8>()), layout) }
     }
 
     // The table layout used for allocation.
@@ -181,12 +605,17 @@ impl<T> Table<T> {
 fn layout() {
     unsafe {
         let collector = seize::Collector::new();
-        let table: Table<u8> = Table::alloc(4, &collector);
+        let table: Table<u8> = Table::alloc(32, &probe::Config::default(), &collector, &Global);
         let table: Table<u8> = Table::from_raw(table.raw);
-        assert_eq!(table.mask, 3);
-        assert_eq!(table.len(), 4);
-        // The capacity is padded for pointer alignment.
-        assert_eq!(table.capacity, 8);
-        Table::dealloc(table);
+        assert_eq!(table.mask, 31);
+        assert_eq!(table.len(), 32);
+        assert_eq!(table.capacity, 32);
+        Table::dealloc::<Global>(table);
+
+        // Tables hold at least a single group.
+        let table: Table<u8> = Table::alloc(4, &probe::Config::default(), &collector, &Global);
+        assert_eq!(table.len(), Group::WIDTH);
+        assert_eq!(table.capacity, Group::WIDTH);
+        Table::dealloc::<Global>(table);
     }
 }
@@ -0,0 +1,248 @@
+// A group of metadata bytes, scanned in parallel during probing.
+//
+// Groups are loaded from the metadata table with word-sized atomic loads, so writers can
+// continue to publish metadata bytes individually.
+use std::mem;
+use std::sync::atomic::{fence, AtomicUsize, Ordering};
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+use self::sse2 as imp;
+
+#[cfg(not(all(target_arch = "x86_64", target_feature = "sse2")))]
+use self::generic as imp;
+
+use super::meta;
+
+// A group of metadata bytes.
+#[derive(Clone, Copy)]
+pub struct Group(imp::Group);
+
+impl Group {
+    // The number of metadata bytes in a group.
+    pub const WIDTH: usize = 16;
+
+    // Load the group of metadata bytes starting at the given pointer.
+    //
+    // # Safety
+    //
+    // The pointer must be valid for reads of `Group::WIDTH` bytes.
+    #[inline]
+    pub unsafe fn load(meta: *const AtomicUsize) -> Group {
+        Group(imp::Group::from_words(unsafe { load_words(meta) }))
+    }
+
+    // Returns the bytes in the group that match the given byte.
+    //
+    // Matches may contain false positives, which must be verified by comparing
+    // against the full entry.
+    #[inline]
+    pub fn matches(&self, byte: u8) -> BitMask {
+        self.0.matches(byte)
+    }
+
+    // Returns the bytes in the group that are empty.
+    #[inline]
+    pub fn empty(&self) -> BitMask {
+        self.0.matches_exact(meta::EMPTY)
+    }
+
+    // Returns the bytes in the group that are tombstones.
+    #[inline]
+    pub fn tombstones(&self) -> BitMask {
+        self.0.matches_exact(meta::TOMBSTONE)
+    }
+}
+
+// Load a group of metadata bytes as little-endian words.
+//
+// Metadata bytes are written through the `usize` words containing them, so we read them with
+// loads of the same size. Note that the loaded words are not guaranteed to be a consistent
+// snapshot of the group, but every word is individually fresh.
+#[inline]
+unsafe fn load_words(meta: *const AtomicUsize) -> [u64; 2] {
+    const WORD: usize = mem::size_of::<usize>();
+
+    let mut bytes = [0; Group::WIDTH];
+    for (i, word) in bytes.chunks_exact_mut(WORD).enumerate() {
+        let value = unsafe { (*meta.add(i)).load(Ordering::Relaxed) };
+        word.copy_from_slice(&value.to_ne_bytes());
+    }
+
+    // Synchronize with the writers of any entries we found.
+    fence(Ordering::Acquire);
+
+    words_from(bytes)
+}
+
+// Convert a group of metadata bytes to little-endian words.
+#[inline]
+fn words_from(bytes: [u8; Group::WIDTH]) -> [u64; 2] {
+    let (lo, hi) = bytes.split_at(8);
+    [
+        u64::from_le_bytes(lo.try_into().unwrap()),
+        u64::from_le_bytes(hi.try_into().unwrap()),
+    ]
+}
+
+// A set of positions in a group, one bit per byte.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BitMask(pub u16);
+
+impl BitMask {
+    // Returns a mask of the first `len` positions in a group.
+    #[inline]
+    pub fn first(len: usize) -> BitMask {
+        match len {
+            len if len >= Group::WIDTH => BitMask(u16::MAX),
+            len => BitMask((1 << len) - 1),
+        }
+    }
+
+    // Returns the positions set in both masks.
+    #[inline]
+    pub fn and(self, other: BitMask) -> BitMask {
+        BitMask(self.0 & other.0)
+    }
+
+    // Returns the positions set in either mask.
+    #[inline]
+    pub fn or(self, other: BitMask) -> BitMask {
+        BitMask(self.0 | other.0)
+    }
+
+    // Returns `true` if the given position is set.
+    #[inline]
+    pub fn contains(self, i: usize) -> bool {
+        self.0 & (1 << i) != 0
+    }
+
+    // Returns `true` if any position is set.
+    #[inline]
+    pub fn any(self) -> bool {
+        self.0 != 0
+    }
+
+    // Returns the first position that is set.
+    #[inline]
+    pub fn lowest(self) -> Option<usize> {
+        self.any().then(|| self.0.trailing_zeros() as usize)
+    }
+}
+
+impl Iterator for BitMask {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        let bit = self.lowest()?;
+        self.0 &= self.0 - 1;
+        Some(bit)
+    }
+}
+
+// Groups scanned with SSE2 instructions.
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+mod sse2 {
+    use super::BitMask;
+
+    use std::arch::x86_64::{
+        __m128i, _mm_cmpeq_epi8, _mm_movemask_epi8, _mm_set1_epi8, _mm_set_epi64x,
+    };
+
+    #[derive(Clone, Copy)]
+    pub struct Group(__m128i);
+
+    impl Group {
+        #[inline]
+        pub fn from_words([lo, hi]: [u64; 2]) -> Group {
+            // Safety: SSE2 is enabled for this target.
+            Group(unsafe { _mm_set_epi64x(hi as i64, lo as i64) })
+        }
+
+        #[inline]
+        pub fn matches(&self, byte: u8) -> BitMask {
+            self.matches_exact(byte)
+        }
+
+        #[inline]
+        pub fn matches_exact(&self, byte: u8) -> BitMask {
+            // Safety: SSE2 is enabled for this target.
+            unsafe {
+                let cmp = _mm_cmpeq_epi8(self.0, _mm_set1_epi8(byte as i8));
+                BitMask(_mm_movemask_epi8(cmp) as u16)
+            }
+        }
+    }
+}
+
+// A portable fallback that scans groups a word at a time.
+#[cfg_attr(all(target_arch = "x86_64", target_feature = "sse2"), allow(dead_code))]
+mod generic {
+    use super::BitMask;
+
+    const LO: u64 = u64::from_ne_bytes([0x01; 8]);
+    const HI: u64 = u64::from_ne_bytes([0x80; 8]);
+
+    #[derive(Clone, Copy)]
+    pub struct Group([u64; 2]);
+
+    impl Group {
+        #[inline]
+        pub fn from_words(words: [u64; 2]) -> Group {
+            Group(words)
+        }
+
+        #[inline]
+        pub fn matches(&self, byte: u8) -> BitMask {
+            // Find zero bytes after XOR-ing with the repeated byte.
+            //
+            // This may produce false positives for bytes following a true match, but
+            // never misses a true match.
+            let find = |word: u64| {
+                let cmp = word ^ (LO * byte as u64);
+                cmp.wrapping_sub(LO) & !cmp & HI
+            };
+
+            BitMask(compress(find(self.0[0])) | (compress(find(self.0[1])) << 8))
+        }
+
+        #[inline]
+        pub fn matches_exact(&self, byte: u8) -> BitMask {
+            // Find zero bytes exactly, without carries across bytes.
+            let find = |word: u64| {
+                let cmp = word ^ (LO * byte as u64);
+                !(((cmp & !HI) + !HI) | cmp) & HI
+            };
+
+            BitMask(compress(find(self.0[0])) | (compress(find(self.0[1])) << 8))
+        }
+    }
+
+    // Compress the high bit of each byte in a word into the low bits of a mask.
+    #[inline]
+    fn compress(word: u64) -> u16 {
+        ((((word >> 7) & LO).wrapping_mul(0x0102_0408_1020_4080)) >> 56) as u16
+    }
+
+    #[test]
+    fn matches() {
+        let bytes = [0x80, 0x05, 0xff, 0x05, 0x00, 0x80, 0x7f, 0x06];
+        let word = u64::from_le_bytes(bytes);
+        let group = Group([word, word]);
+
+        let expected = |byte: u8| {
+            let mask = bytes
+                .iter()
+                .enumerate()
+                .filter(|(_, &b)| b == byte)
+                .fold(0u16, |mask, (i, _)| mask | (1 << i));
+            mask | (mask << 8)
+        };
+
+        for byte in [0x80, 0x05, 0xff, 0x00, 0x7f, 0x06, 0x42] {
+            assert_eq!(group.matches_exact(byte).0, expected(byte));
+            // Matches may contain false positives, but never miss a true match.
+            assert_eq!(group.matches(byte).0 & expected(byte), expected(byte));
+        }
+    }
+}
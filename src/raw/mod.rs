@@ -1,24 +1,44 @@
 mod alloc;
+mod group;
+mod pending;
 mod probe;
 mod utils;
+mod watch;
 
-use std::borrow::Borrow;
+#[cfg(test)]
+mod tests;
+
+use std::alloc::Layout;
 use std::hash::{BuildHasher, Hash};
+use std::hint;
 use std::marker::PhantomData;
 use std::mem::{self, MaybeUninit};
+#[cfg(feature = "rayon")]
+use std::ops::Range;
+use std::ptr::{self, NonNull};
 use std::sync::atomic::{fence, AtomicPtr, AtomicU32, AtomicUsize, Ordering};
-use std::sync::Mutex;
-use std::{hint, ptr};
-
-use self::alloc::RawTable;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+pub use self::alloc::{Allocator, Global};
+use self::alloc::{Pool, RawTable};
+use self::group::BitMask;
+use self::pending::Pending;
+pub use self::pending::{Initializer, Status};
+pub use self::probe::Config as ProbeConfig;
 use self::probe::Probe;
-use self::utils::{untagged, AtomicPtrFetchOps, Counter, Parker, Shared, StrictProvenance, Tagged};
-use crate::map::{Compute, Operation, ResizeMode};
+use self::utils::{
+    untagged, AtomicPtrFetchOps, Backoff, Counter, Parker, Shared, StrictProvenance, Tagged,
+};
+pub use self::watch::Registration;
+use self::watch::Watchers;
+use crate::equivalent::Equivalent;
+use crate::map::{CompactMode, Compute, EntryPool, MapStats, Operation, ResizeMode, ShrinkMode};
 
 use seize::{AsLink, Collector, Guard, Link};
 
 // A lock-free hash-table.
-pub struct HashMap<K, V, S> {
+pub struct HashMap<K, V, S, A: Allocator> {
     // A pointer to the root table.
     table: AtomicPtr<RawTable>,
     // Collector for memory reclamation.
@@ -28,10 +48,35 @@ pub struct HashMap<K, V, S> {
     collector: Shared<Collector>,
     // The resize mode, either blocking or incremental.
     resize: ResizeMode,
+    // The shrink mode, either manual or automatic.
+    shrink: ShrinkMode,
+    // The compaction mode, either manual or automatic.
+    compact: CompactMode,
+    // The load factor and probe limit used to size tables.
+    probe: ProbeConfig,
     // The number of keys in the table.
     count: Counter,
+    // The number of bytes held by tables that were retired by resizes, but not yet reclaimed.
+    //
+    // The counter is allocated as it's aliased by each table, as it needs to be accessed
+    // during reclamation.
+    retired: Shared<AtomicUsize>,
+    // Keys that are currently being initialized by `get_or_init_once`.
+    pending: Pending<K>,
+    // Threads and tasks watching for changes to keys.
+    watchers: Watchers,
     // Hasher for keys.
     pub hasher: S,
+    // The pool that entries are allocated from, caching entry allocations if enabled.
+    //
+    // The pool is allocated as it's aliased by each entry, and must be dropped
+    // before the allocator.
+    pool: Shared<Pool<A>>,
+    // Allocator for tables and entries.
+    //
+    // The allocator is allocated as it's aliased by each table and entry,
+    // as it needs to be accessed during reclamation.
+    alloc: Shared<A>,
     _kv: PhantomData<(K, V)>,
 }
 
@@ -57,6 +102,20 @@ pub struct State {
     pub deferred: seize::Deferred,
     // A pointer to the root collector, valid as long as the map is alive.
     pub collector: *const Collector,
+    // A pointer to the root allocator, valid as long as the map is alive.
+    pub alloc: *const (),
+    // A pointer to the root count of retired table bytes, valid as long as the map is alive.
+    pub retired: *const AtomicUsize,
+    // The number of deleted entries in the table.
+    pub tombstones: Counter,
+    // The number of tables allocated before this one by resizes.
+    //
+    // This identifies a table across guards, where the table pointer may be reused.
+    pub generation: usize,
+    // The number of resizes before this table that grew the table.
+    pub grown: usize,
+    // The number of resizes before this table that shrunk the table.
+    pub shrunk: usize,
 }
 
 impl Default for State {
@@ -70,6 +129,12 @@ impl Default for State {
             parker: Parker::default(),
             deferred: seize::Deferred::new(),
             collector: ptr::null(),
+            alloc: ptr::null(),
+            retired: ptr::null(),
+            tombstones: Counter::default(),
+            generation: 0,
+            grown: 0,
+            shrunk: 0,
         }
     }
 }
@@ -140,11 +205,81 @@ impl Entry<(), ()> {
     // In blocking mode this is unused.
     const BORROWED: usize = 0b100;
 
-    // Reclaims an entry.
+    // Reclaims a retired entry of a map with an allocator of type `A`.
     #[inline]
-    unsafe fn reclaim<K, V>(link: *mut Link) {
+    unsafe fn reclaim<K, V, A: Allocator>(link: *mut Link) {
         let entry: *mut Entry<K, V> = link.cast();
-        let _entry = unsafe { Box::from_raw(entry) };
+
+        // Safety: The entry was allocated by the map, whose pool outlives any retired entries.
+        unsafe { Entry::pool::<A>(entry).reclaimed() };
+        drop(unsafe { Entry::into_inner::<A>(entry) });
+    }
+}
+
+// An entry allocated by a map.
+//
+// Retired entries are reclaimed without access to the map, so a pointer to the
+// entry pool of the map is stored alongside the entry.
+#[repr(C)]
+struct EntryAlloc<K, V, A: Allocator> {
+    entry: Entry<K, V>,
+    pool: *const Pool<A>,
+}
+
+impl<K, V> Entry<K, V> {
+    // Allocates a new entry from the given pool.
+    //
+    // The pool must remain valid until the entry is deallocated.
+    #[inline]
+    fn alloc_in<A: Allocator>(entry: Entry<K, V>, pool: &Pool<A>) -> *mut Entry<K, V> {
+        let ptr = alloc::allocate(pool, Entry::<K, V>::layout::<A>(), false).as_ptr();
+        unsafe {
+            ptr.cast::<EntryAlloc<K, V, A>>()
+                .write(EntryAlloc { entry, pool })
+        };
+        ptr.cast()
+    }
+
+    // Returns the pool that an entry was allocated from.
+    //
+    // # Safety
+    //
+    // The entry must have been allocated by `Entry::alloc_in` with an allocator of type `A`,
+    // and the pool must still be valid.
+    #[inline]
+    unsafe fn pool<'a, A: Allocator>(entry: *mut Entry<K, V>) -> &'a Pool<A> {
+        unsafe { &*(*entry.cast::<EntryAlloc<K, V, A>>()).pool }
+    }
+
+    // Deallocates an entry of a map with an allocator of type `A`, returning its contents.
+    //
+    // # Safety
+    //
+    // The entry must have been allocated by the map, and must not be accessed again.
+    #[inline]
+    unsafe fn into_inner<A: Allocator>(entry: *mut Entry<K, V>) -> Entry<K, V> {
+        let pool = unsafe { Entry::pool::<A>(entry) };
+
+        let inner = unsafe { entry.read() };
+        let layout = Entry::<K, V>::layout::<A>();
+        unsafe { pool.deallocate(NonNull::new_unchecked(entry.cast()), layout) };
+        inner
+    }
+
+    // Drops and deallocates an entry of a map.
+    //
+    // # Safety
+    //
+    // See `Entry::into_inner` for details.
+    #[inline]
+    unsafe fn dealloc<A: Allocator>(entry: *mut Entry<K, V>) {
+        drop(unsafe { Entry::into_inner::<A>(entry) });
+    }
+
+    // Returns the layout of an entry of a map with an allocator of type `A`.
+    #[inline]
+    fn layout<A: Allocator>() -> Layout {
+        Layout::new::<EntryAlloc<K, V, A>>()
     }
 }
 
@@ -160,6 +295,23 @@ impl<K, V> Entry<K, V> {
     // Note that tombstone entries may still be marked as `COPYING`, so this state
     // cannot be used for direct equality.
     const TOMBSTONE: *mut Entry<K, V> = Entry::COPIED as _;
+
+    // A sentinel pointer for an entry reserved by an insert that must confirm its key is not
+    // present elsewhere in the probe sequence before publishing it.
+    //
+    // Reserved entries are never empty again, so other operations can probe past them once
+    // their metadata is initialized. They are either published by the insert or aborted into
+    // a tombstone, by the insert itself or by any concurrent operation that may conflict
+    // with it. Aborted entries are never reserved again, so a reservation cannot be mistaken
+    // for a later reservation of the same entry.
+    const RESERVED: *mut Entry<K, V> = Entry::BORROWED as _;
+
+    // A sentinel pointer for an empty entry that was sealed by an insert validating its
+    // reservation.
+    //
+    // An insert may have probed past the reserved entry, so sealed entries must be reserved
+    // and validated against any concurrent reservations before they can be claimed.
+    const SEALED: *mut Entry<K, V> = (Entry::COPIED | Entry::BORROWED) as _;
 }
 
 /// The status of an entry.
@@ -193,54 +345,317 @@ enum UpdateStatus<K, V> {
     Found(EntryStatus<K, V>),
 }
 
+/// The location of an entry that was observed by a lookup.
+pub struct Slot<K, V> {
+    // The observed entry, including any tag bits.
+    entry: Tagged<Entry<K, V>>,
+    // The table the entry was found in.
+    table: Table<K, V>,
+    // The index of the entry in the table.
+    i: usize,
+}
+
+impl<K, V> Clone for Slot<K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for Slot<K, V> {}
+
+// Safety: A slot only provides shared access to the entry, and tables are shared between threads.
+unsafe impl<K: Sync, V: Sync> Send for Slot<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for Slot<K, V> {}
+
 /// The state of an entry we attempted to insert into.
 enum InsertStatus<K, V> {
     // Successfully inserted the value.
     Inserted,
     // A new entry was written before we could update.
     Found(EntryStatus<K, V>),
+    // The reservation of a concurrent insert conflicted with ours, the probe must be restarted.
+    Retry,
+    // The entry was sealed by a concurrent insert, and must be reserved before it is claimed.
+    Sealed,
+}
+
+/// The result of validating a reservation.
+enum Validation {
+    // The key is not present in the probe sequence.
+    Valid,
+    // The key was inserted concurrently.
+    Found,
+    // The table is being resized, and the key may be inserted into the next table.
+    Copying,
+}
+
+// An entry reserved by an insert.
+//
+// The reservation is aborted when dropped, even if comparing keys panics while it is held.
+//
+// # Tombstone reuse
+//
+// An insert that probed past a tombstone reuses it in three steps, see `insert_reuse`:
+//
+// 1. The tombstone, or the empty entry if there is none, is reserved with `Entry::RESERVED`,
+//    which other operations treat as a deleted entry.
+// 2. The probe sequence is validated: reservations that may be for the same key are aborted,
+//    the key is compared against any entries that may match, and the first empty entry is
+//    sealed with `Entry::SEALED`.
+// 3. The reservation is published with a CAS from `Entry::RESERVED`.
+//
+// This relies on the following invariants:
+//
+// - Entries are never empty again once they are claimed, reserved, or sealed, and aborted
+//   entries are never reserved again. A stale insert can therefore never publish into a
+//   later reservation of the same entry.
+// - An insert that claims an empty entry without a reservation stops at the first empty
+//   entry. If it claims the entry before it is sealed, validation sees the key. Otherwise, the
+//   claim fails and the insert must go through a reservation of its own. Inserts that claim a
+//   sealed entry without a reservation move the seal forward first, so later inserts still
+//   reach a seal.
+// - Reservations, validation, and publishing are `SeqCst`. Of two inserts of the same key that
+//   both reserved an entry, the one that validates last sees the other's reservation, or its
+//   published entry, so at most one of them can publish.
+// - Entries are only reserved while the table is not being resized, and validation fails if it
+//   sees an entry being copied or a next table. Inserts that continue in the next table abort
+//   any reservations they probed past after allocating it, as do inserts that would otherwise
+//   reserve an entry while the table is being resized. An entry is therefore never published
+//   after an insert of the same key continued in the next table or claimed an entry without a
+//   reservation, and the copy of a published entry remains the only copy of its key.
+//
+// A reservation is never waited on, so a stalled insert cannot block others. However,
+// concurrent inserts of keys with the same metadata may abort each other's reservations, in
+// which case they back off before retrying. Aborted entries remain tombstones that count
+// towards compaction, and are reclaimed once the table is compacted or resized.
+struct Reservation<K, V> {
+    table: Table<K, V>,
+    i: usize,
+    meta: u8,
+    thread_id: usize,
+}
+
+impl<K, V> Reservation<K, V> {
+    // Reserves the tombstone at the given index, returning `None` if it is not a tombstone.
+    #[inline]
+    fn tombstone(table: Table<K, V>, i: usize, h2: u8, thread_id: usize) -> Option<Self> {
+        let meta = unsafe { table.meta(i) };
+
+        // Claim the metadata of the tombstone before the entry.
+        //
+        // This fails if the thread that deleted the entry has not yet marked it as a tombstone,
+        // ensuring that any later removals of the entry update the metadata after us.
+        meta.compare_exchange(meta::TOMBSTONE, h2, Ordering::SeqCst, Ordering::Relaxed)
+            .ok()?;
+
+        // Reserve the entry.
+        //
+        // This fails if the tombstone was copied to a new table while we claimed it, in which
+        // case it was treated as an empty entry.
+        let entry = unsafe { table.entry(i) };
+        if entry
+            .compare_exchange(
+                Entry::TOMBSTONE,
+                Entry::RESERVED,
+                Ordering::SeqCst,
+                Ordering::Relaxed,
+            )
+            .is_err()
+        {
+            // Release the tombstone.
+            meta.store(meta::TOMBSTONE, Ordering::Release);
+            return None;
+        }
+
+        // The tombstone is no longer part of this table.
+        let tombstones = table.state().tombstones.get(thread_id);
+        tombstones.fetch_sub(1, Ordering::Relaxed);
+
+        Some(Reservation {
+            table,
+            i,
+            meta: h2,
+            thread_id,
+        })
+    }
+
+    // Reserves the empty entry at the given index, returning the current entry if it is not
+    // empty.
+    #[inline]
+    fn empty(
+        table: Table<K, V>,
+        i: usize,
+        h2: u8,
+        thread_id: usize,
+    ) -> Result<Self, *mut Entry<K, V>> {
+        let entry = unsafe { table.entry(i) };
+
+        let mut current = ptr::null_mut();
+        loop {
+            match entry.compare_exchange(
+                current,
+                Entry::RESERVED,
+                Ordering::SeqCst,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    return Ok(Reservation {
+                        table,
+                        i,
+                        meta: h2,
+                        thread_id,
+                    })
+                }
+
+                // The entry was sealed, which is still considered empty.
+                Err(found) if found == Entry::SEALED && current.is_null() => current = found,
+
+                Err(found) => return Err(found),
+            }
+        }
+    }
+
+    // Publishes the new entry into the reserved entry.
+    //
+    // Returns `false` if the reservation was aborted by a concurrent insert, or the entry
+    // was null copied.
+    #[inline]
+    fn publish(self, new_entry: *mut Entry<K, V>) -> bool {
+        let entry = unsafe { self.table.entry(self.i) };
+
+        // Note that this must be `SeqCst` to synchronize with any watchers, as well as any
+        // concurrent reservations.
+        let published = entry
+            .compare_exchange(
+                Entry::RESERVED,
+                new_entry,
+                Ordering::SeqCst,
+                Ordering::Relaxed,
+            )
+            .is_ok();
+
+        // Otherwise there is nothing left to abort.
+        mem::forget(self);
+        published
+    }
+
+    // Aborts the reservation of the entry at the given index, turning it into a tombstone.
+    //
+    // The insert that reserved the entry may still try to publish it, so the tombstone must
+    // never be reserved again. Its metadata is not marked as a tombstone, and is initialized
+    // with the given metadata if the entry was reserved while empty, keeping the probe chain
+    // alive for readers.
+    //
+    // Returns `false` if the entry was not reserved.
+    #[inline]
+    fn abort(table: Table<K, V>, i: usize, meta: u8, thread_id: usize) -> bool {
+        let entry = unsafe { table.entry(i) };
+
+        if entry
+            .compare_exchange(
+                Entry::RESERVED,
+                Entry::TOMBSTONE,
+                Ordering::SeqCst,
+                Ordering::Relaxed,
+            )
+            .is_err()
+        {
+            return false;
+        }
+
+        // Note that the metadata of an entry is initialized exactly once, so this cannot
+        // overwrite the metadata of the reservation.
+        let _ = unsafe { table.meta(i) }.compare_exchange(
+            meta::EMPTY,
+            meta,
+            Ordering::Release,
+            Ordering::Relaxed,
+        );
+
+        let tombstones = table.state().tombstones.get(thread_id);
+        tombstones.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+}
+
+impl<K, V> Drop for Reservation<K, V> {
+    fn drop(&mut self) {
+        Reservation::abort(self.table, self.i, self.meta, self.thread_id);
+    }
 }
 
-impl<K, V, S> HashMap<K, V, S> {
+impl<K, V, S, A: Allocator> HashMap<K, V, S, A> {
     // Creates new hash-table with the given options.
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         capacity: usize,
         hasher: S,
+        alloc: A,
         collector: Collector,
         resize: ResizeMode,
-    ) -> HashMap<K, V, S> {
+        shrink: ShrinkMode,
+        compact: CompactMode,
+        probe: ProbeConfig,
+        pool: EntryPool,
+    ) -> HashMap<K, V, S, A> {
         let collector = Shared::from(collector);
+        let alloc = Shared::from(alloc);
+        let retired = Shared::from(AtomicUsize::new(0));
+        let limit = match pool {
+            EntryPool::Disabled => 0,
+            EntryPool::PerThread(limit) => limit,
+        };
+        let pool = Shared::from(Pool::new(Entry::<K, V>::layout::<A>(), limit, &*alloc));
 
         // The table is lazily allocated.
         if capacity == 0 {
             return HashMap {
                 collector,
                 resize,
+                shrink,
+                compact,
+                probe,
                 hasher,
+                pool,
+                alloc,
                 table: AtomicPtr::new(ptr::null_mut()),
                 count: Counter::default(),
+                retired,
+                pending: Pending::default(),
+                watchers: Watchers::default(),
                 _kv: PhantomData,
             };
         }
 
         // Initialize the table and mark it as the root.
-        let mut table = Table::<K, V>::alloc(probe::entries_for(capacity), &collector);
+        let mut table =
+            Table::<K, V>::alloc(probe.entries_for(capacity), &probe, &collector, &*alloc);
         *table.state_mut().status.get_mut() = State::PROMOTED;
+        table.state_mut().retired = &*retired;
 
         HashMap {
             hasher,
+            pool,
+            alloc,
             resize,
+            shrink,
+            compact,
+            probe,
             collector,
             table: AtomicPtr::new(table.raw),
             count: Counter::default(),
+            retired,
+            pending: Pending::default(),
+            watchers: Watchers::default(),
             _kv: PhantomData,
         }
     }
 
     // Returns a reference to the root hash-table.
     #[inline]
-    pub fn root<'g>(&self, guard: &'g impl Guard) -> HashMapRef<'g, K, V, S> {
+    pub fn root<'g>(&self, guard: &'g impl Guard) -> HashMapRef<'g, K, V, S, A> {
         assert!(
             guard.belongs_to(&self.collector),
             "accessed map with incorrect guard"
@@ -253,7 +668,9 @@ impl<K, V, S> HashMap<K, V, S> {
         // Safety: We verified above that the guard belongs to our collector, so
         // &'g Guard implies &'g self. This makes bounds a little nicer for users.
         unsafe {
-            mem::transmute::<HashMapRef<'_, K, V, S>, HashMapRef<'g, K, V, S>>(self.as_ref(table))
+            mem::transmute::<HashMapRef<'_, K, V, S, A>, HashMapRef<'g, K, V, S, A>>(
+                self.as_ref(table),
+            )
         }
     }
 
@@ -263,6 +680,18 @@ impl<K, V, S> HashMap<K, V, S> {
         &self.collector
     }
 
+    // Returns a reference to the allocator.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    // Allocates an entry from the entry pool.
+    #[inline]
+    fn alloc_entry<T>(&self, entry: Entry<K, T>) -> *mut Entry<K, T> {
+        Entry::alloc_in(entry, &*self.pool)
+    }
+
     // Returns the number of entries in the table.
     #[inline]
     pub fn len(&self) -> usize {
@@ -277,427 +706,1227 @@ impl<K, V, S> HashMap<K, V, S> {
 
     // Returns a reference to the given table.
     #[inline]
-    fn as_ref(&self, table: Table<K, V>) -> HashMapRef<'_, K, V, S> {
+    fn as_ref(&self, table: Table<K, V>) -> HashMapRef<'_, K, V, S, A> {
         HashMapRef { table, root: self }
     }
 }
 
-// A reference to the root table, or an arbitrarily nested table migration.
-pub struct HashMapRef<'a, K, V, S> {
-    table: Table<K, V>,
-    root: &'a HashMap<K, V, S>,
-}
-
-// Hash-table operations.
-impl<'root, K, V, S> HashMapRef<'root, K, V, S>
+// Exclusive-access operations.
+//
+// Having unique access to the map means there are no active guards, so these operations
+// can read and write the table directly, without any synchronization or deferred reclamation.
+impl<K, V, S, A: Allocator> HashMap<K, V, S, A>
 where
     K: Hash + Eq,
     S: BuildHasher,
 {
-    // Returns a reference to the entry corresponding to the key.
+    // Returns a mutable reference to the value corresponding to the key.
     #[inline]
-    pub fn get<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> Option<(&'g K, &'g V)>
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + Equivalent<K> + ?Sized,
     {
+        let table = self.linearize_mut();
+
         // The table has not yet been allocated.
-        if self.table.raw.is_null() {
+        if table.raw.is_null() {
             return None;
         }
 
         // Initialize the probe state.
-        let (h1, h2) = self.hash(key);
-        let mut probe = Probe::start(h1, self.table.mask);
-
-        // Probe until we reach the limit.
-        while probe.len <= self.table.limit {
-            // Load the entry metadata first for cheap searches.
-            let meta = unsafe { self.table.meta(probe.i) }.load(Ordering::Acquire);
+        let hash = self.hasher.hash_one(key);
+        let (h1, h2) = (meta::h1(hash), meta::h2(hash));
+        let mut probe = Probe::start(h1, table.mask);
+
+        // Probe a group at a time until we reach the limit.
+        while probe.len <= table.limit {
+            // Load the group metadata first for cheap searches.
+            let group = unsafe { table.group(probe.i) };
+
+            // Ignore any entries past the probe limit.
+            let probed = BitMask::first(table.limit - probe.len + 1);
+
+            // Check for potential matches.
+            for offset in group.matches(h2).and(probed) {
+                // Safety: We have unique access to the table.
+                let entry = unsafe { (*table.entry(probe.i + offset).as_ptr()).unpack() };
+
+                // Check for a full match.
+                if !entry.ptr.is_null() && key.equivalent(unsafe { &(*entry.ptr).key }) {
+                    return Some(unsafe { &mut (*entry.ptr).value });
+                }
+            }
 
             // The key is not in the table.
-            //
-            // It also cannot be in the next table because we have not went over the probe limit.
-            if meta == meta::EMPTY {
+            if group.empty().and(probed).any() {
                 return None;
             }
 
-            // Check for a potential match.
-            if meta != h2 {
-                probe.next(self.table.mask);
-                continue;
-            }
-
-            // Load the full entry.
-            let entry =
-                unsafe { guard.protect(self.table.entry(probe.i), Ordering::Acquire) }.unpack();
-
-            // The entry was deleted, keep probing.
-            if entry.ptr.is_null() {
-                probe.next(self.table.mask);
-                continue;
-            }
-
-            // Check for a full match.
-            if unsafe { (*entry.ptr).key.borrow() } == key {
-                // The entry was copied to the new table.
-                //
-                // In blocking resize mode we do not need to perform this check as all writes block
-                // until any resizes are complete, making the root table the source of truth for readers.
-                if entry.tag() & Entry::COPIED != 0 {
-                    break;
-                }
+            probe.next_group(table.mask);
+        }
 
-                // Found the correct entry, return the key and value.
-                return unsafe { Some((&(*entry.ptr).key, &(*entry.ptr).value)) };
-            }
+        // The table is linearized, so the key cannot be in a next table.
+        None
+    }
 
-            probe.next(self.table.mask);
+    // Returns an iterator over mutable references to the values of this table.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            i: 0,
+            table: self.linearize_mut(),
+            _map: PhantomData,
         }
+    }
 
-        // In incremental resize mode, we have to check the next table if we found
-        // a copied entry or went over the probe limit.
-        if self.root.is_incremental() {
-            if let Some(next) = self.next_table_ref() {
-                // Retry in the new table.
-                return next.get(key, guard);
-            }
+    // Returns an iterator that removes all entries from this table.
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, K, V, A> {
+        Drain {
+            i: 0,
+            table: self.linearize_mut(),
+            count: &mut self.count,
+            _alloc: PhantomData,
         }
+    }
 
-        // Otherwise, the key is not in the table.
-        None
+    // Returns an iterator that moves all entries out of this table.
+    #[inline]
+    pub fn into_iter(mut self) -> IntoIter<K, V, S, A> {
+        IntoIter {
+            i: 0,
+            table: self.linearize_mut(),
+            _map: self,
+        }
     }
 
-    // Inserts a key-value pair into the table.
+    // Completes any pending resizes, returning the only remaining table.
     #[inline]
-    pub fn insert<'g>(
-        &mut self,
-        key: K,
-        value: V,
-        replace: bool,
-        guard: &'g impl Guard,
-    ) -> InsertResult<'g, V> {
-        // Allocate the entry to be inserted.
-        let entry = Box::into_raw(Box::new(Entry {
-            key,
-            value,
-            link: self.root.collector.link(),
-        }));
+    fn linearize_mut(&mut self) -> Table<K, V> {
+        let table = unsafe { Table::<K, V>::from_raw(*self.table.get_mut()) };
 
-        // Perform the insert.
-        //
-        // Safety: We just allocated the entry above.
-        let result = unsafe { self.insert_with(untagged(entry), replace, true, guard) };
-        let result = match result {
-            RawInsertResult::Inserted(value) => InsertResult::Inserted(value),
-            RawInsertResult::Replaced(value) => InsertResult::Replaced(value),
-            RawInsertResult::Error {
-                current,
-                not_inserted,
-            } => {
-                let current = unsafe { &(*current.ptr).value };
+        // There are no pending resizes.
+        if table.raw.is_null() || table.state().next.load(Ordering::Acquire).is_null() {
+            return table;
+        }
 
-                // Safety: We allocated this box above and it was not inserted into the table.
-                let not_inserted = unsafe { Box::from_raw(not_inserted) };
+        // Safety: We have unique access to the map, so there are no active guards and
+        // any tables or entries that are retired can be reclaimed immediately.
+        let guard = unsafe { seize::unprotected() };
 
-                InsertResult::Error {
-                    current,
-                    not_inserted: not_inserted.value,
+        let mut root = self.root(&guard);
+        root.linearize(&guard);
+        root.table
+    }
+}
+
+// Single-flight initialization.
+impl<K, V, S, A: Allocator> HashMap<K, V, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    // Returns the value for the given key, or inserts the value computed by `f`.
+    //
+    // Unlike `compute`, `f` is only called once across all threads racing to insert the same key.
+    #[inline]
+    pub fn get_or_init_once<'g, F>(&self, mut key: K, f: F, guard: &'g impl Guard) -> &'g V
+    where
+        F: FnOnce() -> V,
+        K: 'g, // TODO: this bound is necessary because `HashMap::get` returns the full entry.
+    {
+        let initializer = loop {
+            match self.claim(key, guard) {
+                Claim::Present(value) => return value,
+                Claim::Claimed(initializer) => break initializer,
+
+                // Another thread is initializing the value, wait for it to complete.
+                //
+                // If the initializer was abandoned, the value will not be present and we
+                // can retry the initialization.
+                Claim::Pending(returned, status) => {
+                    self.pending.wait(&status);
+                    key = returned;
                 }
             }
         };
 
-        // Increment the length if we inserted a new entry.
-        if matches!(result, InsertResult::Inserted(_)) {
-            self.root
-                .count
-                .get(guard.thread_id())
-                .fetch_add(1, Ordering::Relaxed);
-        }
+        // If `f` panics, the initializer is dropped and the placeholder is removed,
+        // allowing a waiting thread to retry.
+        let value = f();
 
-        result
+        self.complete(initializer, value, guard)
     }
 
-    // Inserts an entry into the map.
-    //
-    // # Safety
-    //
-    // The new entry must be a valid pointer.
+    // Claims the initialization of a key.
     #[inline]
-    unsafe fn insert_with<'g>(
-        &mut self,
-        new_entry: Tagged<Entry<K, V>>,
-        should_replace: bool,
-        help_copy: bool,
-        guard: &'g impl Guard,
-    ) -> RawInsertResult<'g, K, V> {
-        // Allocate the table if it has not been initialized yet.
-        if self.table.raw.is_null() {
-            self.init(None);
+    pub fn claim<'g>(&self, key: K, guard: &'g impl Guard) -> Claim<'_, 'g, K, V>
+    where
+        K: 'g,
+    {
+        // Check for an existing value.
+        //
+        // Note that we always load the root table, as the value may have been inserted into
+        // a newer table than the one we started with.
+        if let Some((_, value)) = self.root(guard).get(&key, guard) {
+            return Claim::Present(value);
         }
 
-        // Safety: The new entry is guaranteed to be valid by the caller.
-        let new_ref = unsafe { &*(new_entry).ptr };
-
-        // Initialize the probe state.
-        let (h1, h2) = self.hash(&new_ref.key);
-        let mut probe = Probe::start(h1, self.table.mask);
+        let hash = self.hasher.hash_one(&key);
+        let placeholders = self.pending.lock(hash);
 
-        // Probe until we reach the limit.
-        let copying = 'probe: loop {
-            if probe.len > self.table.limit {
-                break None;
-            }
+        // The value may have been inserted before we acquired the lock.
+        //
+        // Note that initializers insert the value before removing their placeholder, so
+        // either we observe the value here, or we find the placeholder and wait for it.
+        if let Some((_, value)) = self.root(guard).get(&key, guard) {
+            return Claim::Present(value);
+        }
 
-            // Load the entry metadata first for cheap searches.
-            let meta = unsafe { self.table.meta(probe.i) }.load(Ordering::Acquire);
+        match placeholders.find(hash, &key) {
+            // Another thread is initializing the value.
+            Some(status) => Claim::Pending(key, status),
 
-            // The entry is empty, try to insert.
-            let mut entry = if meta == meta::EMPTY {
-                match self.insert_at(probe.i, h2, new_entry.raw, guard) {
-                    // Successfully inserted.
-                    InsertStatus::Inserted => return RawInsertResult::Inserted(&new_ref.value),
+            // Install a placeholder and initialize the value ourselves.
+            None => Claim::Claimed(placeholders.insert(hash, key)),
+        }
+    }
 
-                    // Lost to a concurrent insert.
-                    //
-                    // If the key matches, we might be able to update the value.
-                    InsertStatus::Found(EntryStatus::Value(found))
-                    | InsertStatus::Found(EntryStatus::Copied(found)) => found,
+    // Inserts an initialized value, removing its placeholder.
+    #[inline]
+    pub fn complete<'g>(
+        &self,
+        initializer: Initializer<'_, K>,
+        value: V,
+        guard: &'g impl Guard,
+    ) -> &'g V {
+        initializer.complete(
+            |key| match self.root(guard).insert(key, value, false, guard) {
+                InsertResult::Inserted(value) => value,
+                // The key was inserted by an operation other than an initializer.
+                InsertResult::Error { current, .. } => current,
+                InsertResult::Replaced(_) => unreachable!(),
+            },
+        )
+    }
+}
 
-                    // Otherwise, continue probing.
-                    InsertStatus::Found(EntryStatus::Null) => {
-                        probe.next(self.table.mask);
-                        continue 'probe;
+// Watch operations.
+impl<K, V, S, A: Allocator> HashMap<K, V, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    // Blocks until the given key is present in the map, or the deadline is reached.
+    #[inline]
+    pub fn wait_for<'g, Q>(
+        &self,
+        key: &Q,
+        deadline: Option<Instant>,
+        guard: &'g impl Guard,
+    ) -> Option<&'g V>
+    where
+        K: 'g,
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        // Fast-path, the key is already present.
+        if let Some((_, value)) = self.root(guard).get(key, guard) {
+            return Some(value);
+        }
+
+        let mut registration = self.watch(key);
+
+        loop {
+            // Check for the key after registering, as it may have been inserted concurrently.
+            //
+            // Note that we always load the root table, as the value may have been inserted into
+            // a newer table than the one we started with.
+            if let Some((_, value)) = self.root(guard).get(key, guard) {
+                return Some(value);
+            }
+
+            // Wait for the key to change.
+            if !registration.park(deadline) {
+                return None;
+            }
+        }
+    }
+
+    // Registers a watcher for changes to the given key.
+    #[inline]
+    pub fn watch<Q>(&self, key: &Q) -> Registration<'_>
+    where
+        Q: Hash + ?Sized,
+    {
+        self.watchers.register(self.hasher.hash_one(key))
+    }
+
+    // Notifies any watchers of a change to the given key.
+    #[inline]
+    fn notify<Q>(&self, key: &Q)
+    where
+        Q: Hash + ?Sized,
+    {
+        self.watchers.notify(|| self.hasher.hash_one(key));
+    }
+}
+
+// The result of claiming the initialization of a key.
+pub enum Claim<'a, 'g, K, V> {
+    // The key is already present in the map.
+    Present(&'g V),
+    // Another thread is initializing the key.
+    Pending(K, Arc<Status>),
+    // We installed a placeholder, and are responsible for initializing the key.
+    Claimed(Initializer<'a, K>),
+}
+
+// The length of the initial table, and the minimum length of a shrunk table.
+const MIN_CAPACITY: usize = 32;
+
+// A reference to the root table, or an arbitrarily nested table migration.
+pub struct HashMapRef<'a, K, V, S, A: Allocator> {
+    table: Table<K, V>,
+    root: &'a HashMap<K, V, S, A>,
+}
+
+// Hash-table operations.
+impl<'root, K, V, S, A: Allocator> HashMapRef<'root, K, V, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    // Returns a reference to the entry corresponding to the key.
+    #[inline]
+    pub fn get<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> Option<(&'g K, &'g V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        // The table has not yet been allocated.
+        if self.table.raw.is_null() {
+            return None;
+        }
+
+        let hash = self.root.hasher.hash_one(key);
+        self.get_hashed(hash, &mut |k| key.equivalent(k), guard)
+    }
+
+    // Returns a reference to the entry with the given hash that matches the predicate.
+    #[inline]
+    pub fn get_hashed<'g, F>(
+        &self,
+        hash: u64,
+        eq: &mut F,
+        guard: &'g impl Guard,
+    ) -> Option<(&'g K, &'g V)>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        let slot = self.find_hashed(hash, eq, guard)?;
+
+        // Safety: The entry is protected by the guard.
+        unsafe { Some((&(*slot.entry.ptr).key, &(*slot.entry.ptr).value)) }
+    }
+
+    // Returns a reference to the entry for a key, along with its location in the table.
+    #[inline]
+    pub fn get_slot<'g, Q>(
+        &self,
+        key: &Q,
+        guard: &'g impl Guard,
+    ) -> Option<(&'g K, &'g V, Slot<K, V>)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let hash = self.root.hasher.hash_one(key);
+        let slot = self.find_hashed(hash, &mut |k| key.equivalent(k), guard)?;
+
+        // Safety: The entry is protected by the guard.
+        unsafe { Some((&(*slot.entry.ptr).key, &(*slot.entry.ptr).value, slot)) }
+    }
+
+    // Returns the location of the entry with the given hash that matches the predicate.
+    //
+    // The returned entry is protected by the guard.
+    #[inline]
+    fn find_hashed<F>(&self, hash: u64, eq: &mut F, guard: &impl Guard) -> Option<Slot<K, V>>
+    where
+        F: FnMut(&K) -> bool,
+    {
+        // The table has not yet been allocated.
+        if self.table.raw.is_null() {
+            return None;
+        }
+
+        // Initialize the probe state.
+        let (h1, h2) = (meta::h1(hash), meta::h2(hash));
+        let mut probe = Probe::start(h1, self.table.mask);
+
+        // Probe a group at a time until we reach the limit.
+        'probe: while probe.len <= self.table.limit {
+            // Load the group metadata first for cheap searches.
+            let group = unsafe { self.table.group(probe.i) };
+
+            // Ignore any entries past the probe limit.
+            let probed = BitMask::first(self.table.limit - probe.len + 1);
+
+            // Check for potential matches.
+            for offset in group.matches(h2).and(probed) {
+                let i = probe.i + offset;
+
+                // Load the full entry.
+                let entry =
+                    unsafe { guard.protect(self.table.entry(i), Ordering::Acquire) }.unpack();
+
+                // The entry was deleted, keep probing.
+                if entry.ptr.is_null() {
+                    continue;
+                }
+
+                // Check for a full match.
+                if eq(unsafe { &(*entry.ptr).key }) {
+                    // The entry was copied to the new table.
+                    //
+                    // In blocking resize mode we do not need to perform this check as all writes block
+                    // until any resizes are complete, making the root table the source of truth for readers.
+                    if entry.tag() & Entry::COPIED != 0 {
+                        break 'probe;
                     }
+
+                    // Found the correct entry.
+                    return Some(Slot {
+                        entry,
+                        table: self.table,
+                        i,
+                    });
                 }
             }
-            // Found a potential match.
-            else if meta == h2 {
+
+            // The key is not in the table.
+            //
+            // It also cannot be in the next table because we have not went over the probe limit.
+            if group.empty().and(probed).any() {
+                return None;
+            }
+
+            probe.next_group(self.table.mask);
+        }
+
+        // In incremental resize mode, we have to check the next table if we found
+        // a copied entry or went over the probe limit.
+        if self.root.is_incremental() {
+            if let Some(next) = self.next_table_ref() {
+                // Retry in the new table.
+                return next.find_hashed(hash, eq, guard);
+            }
+        }
+
+        // Otherwise, the key is not in the table.
+        None
+    }
+
+    // Inserts a key-value pair into the table.
+    #[inline]
+    pub fn insert<'g>(
+        &mut self,
+        key: K,
+        value: V,
+        replace: bool,
+        guard: &'g impl Guard,
+    ) -> InsertResult<'g, V> {
+        let hash = self.root.hasher.hash_one(&key);
+        self.insert_hashed(hash, key, value, replace, guard)
+    }
+
+    // Inserts a key-value pair with the given hash into the table.
+    //
+    // The hash must match the hash of the key.
+    #[inline]
+    pub fn insert_hashed<'g>(
+        &mut self,
+        hash: u64,
+        key: K,
+        value: V,
+        replace: bool,
+        guard: &'g impl Guard,
+    ) -> InsertResult<'g, V> {
+        // Allocate the entry to be inserted.
+        let entry = self.root.alloc_entry(Entry {
+            key,
+            value,
+            link: self.root.collector.link(),
+        });
+
+        // Perform the insert.
+        //
+        // Safety: We just allocated the entry above.
+        let result = unsafe { self.insert_with(untagged(entry), hash, replace, true, guard) };
+        let result = match result {
+            RawInsertResult::Inserted(value) => InsertResult::Inserted(value),
+            RawInsertResult::Replaced(value) => InsertResult::Replaced(value),
+            RawInsertResult::Error {
+                current,
+                not_inserted,
+            } => {
+                let current = unsafe { &(*current.ptr).value };
+
+                // Safety: We allocated this entry above and it was not inserted into the table.
+                let not_inserted = unsafe { Entry::into_inner::<A>(not_inserted) };
+
+                InsertResult::Error {
+                    current,
+                    not_inserted: not_inserted.value,
+                }
+            }
+        };
+
+        // Increment the length if we inserted a new entry.
+        if matches!(result, InsertResult::Inserted(_)) {
+            self.root
+                .count
+                .get(guard.thread_id())
+                .fetch_add(1, Ordering::Relaxed);
+        }
+
+        // Notify any watchers if we inserted or replaced the value.
+        if !matches!(result, InsertResult::Error { .. }) {
+            self.root.watchers.notify(|| hash);
+        }
+
+        result
+    }
+
+    // Inserts an entry with the given hash into the map.
+    //
+    // # Safety
+    //
+    // The new entry must be a valid pointer.
+    #[inline]
+    unsafe fn insert_with<'g>(
+        &mut self,
+        new_entry: Tagged<Entry<K, V>>,
+        hash: u64,
+        should_replace: bool,
+        help_copy: bool,
+        guard: &'g impl Guard,
+    ) -> RawInsertResult<'g, K, V> {
+        // Allocate the table if it has not been initialized yet.
+        if self.table.raw.is_null() {
+            self.init(None);
+        }
+
+        // Safety: The new entry is guaranteed to be valid by the caller.
+        let new_ref = unsafe { &*(new_entry).ptr };
+
+        // Initialize the probe state.
+        let (h1, h2) = (meta::h1(hash), meta::h2(hash));
+        let mut probe = Probe::start(h1, self.table.mask);
+
+        // Whether we must reserve an entry before claiming it, either to reuse a tombstone
+        // we probed past or because an empty entry we probed past may have been sealed.
+        let mut reuse = false;
+        let mut backoff = Backoff::default();
+
+        // Probe a group at a time until we reach the limit.
+        let copying = 'probe: loop {
+            if probe.len > self.table.limit {
+                break None;
+            }
+
+            // Load the group metadata first for cheap searches.
+            let group = unsafe { self.table.group(probe.i) };
+
+            // Ignore any entries past the probe limit.
+            let probed = BitMask::first(self.table.limit - probe.len + 1);
+            let empty = group.empty().and(probed);
+            let tombstones = group.tombstones().and(probed);
+
+            // Check for potential matches and empty entries, in probe order.
+            'group: for offset in group.matches(h2).or(empty).and(probed) {
+                let i = probe.i + offset;
+
+                // The entry is empty, try to insert.
+                let mut entry = if empty.contains(offset) {
+                    // Reuse any tombstones we probed past.
+                    reuse |= tombstones.and(BitMask::first(offset)).any();
+
+                    loop {
+                        let status = if reuse {
+                            self.insert_reuse(i, probe.len + offset, h1, h2, new_entry.raw, guard)
+                        } else {
+                            self.insert_at(i, h2, new_entry.raw, guard)
+                        };
+
+                        // The entry may have been sealed and claimed after we loaded its
+                        // metadata, so any later inserts must go through a reservation.
+                        if let InsertStatus::Found(_) = status {
+                            reuse = true;
+                        }
+
+                        match status {
+                            // Successfully inserted.
+                            InsertStatus::Inserted => {
+                                return RawInsertResult::Inserted(&new_ref.value)
+                            }
+
+                            // Lost to a concurrent insert.
+                            //
+                            // If the key matches, we might be able to update the value.
+                            InsertStatus::Found(EntryStatus::Value(found))
+                            | InsertStatus::Found(EntryStatus::Copied(found)) => break found,
+
+                            // Otherwise, continue probing.
+                            InsertStatus::Found(EntryStatus::Null) => continue 'group,
+
+                            // Our reservation conflicted with a concurrent insert, restart the
+                            // probe.
+                            InsertStatus::Retry => {
+                                backoff.spin();
+                                probe = Probe::start(h1, self.table.mask);
+                                reuse = false;
+                                continue 'probe;
+                            }
+
+                            // The entry must be reserved before we can claim it.
+                            InsertStatus::Sealed => reuse = true,
+                        }
+                    }
+                }
+                // Found a potential match.
+                else {
+                    // Load the full entry.
+                    let found = guard
+                        .protect(self.table.entry(i), Ordering::Acquire)
+                        .unpack();
+
+                    // The entry was deleted, keep probing.
+                    if found.ptr.is_null() {
+                        continue 'group;
+                    }
+
+                    // If the key matches, we might be able to update the value.
+                    found
+                };
+
+                // Check for a full match.
+                if unsafe { (*entry.ptr).key != new_ref.key } {
+                    continue 'group;
+                }
+
+                // The entry is being copied to the new table.
+                if entry.tag() & Entry::COPYING != 0 {
+                    break 'probe Some(i);
+                }
+
+                // Return an error for calls to `try_insert`.
+                if !should_replace {
+                    return RawInsertResult::Error {
+                        current: entry,
+                        not_inserted: new_entry.ptr,
+                    };
+                }
+
+                loop {
+                    // Try to update the value.
+                    match self.update_at(i, entry, new_entry.raw, guard) {
+                        // Successfully updated.
+                        UpdateStatus::Replaced(entry) => {
+                            return RawInsertResult::Replaced(&(*entry.ptr).value)
+                        }
+
+                        // The entry is being copied.
+                        UpdateStatus::Found(EntryStatus::Copied(_)) => break 'probe Some(i),
+
+                        // The entry was deleted before we could update it, continue probing.
+                        UpdateStatus::Found(EntryStatus::Null) => continue 'group,
+
+                        // Someone else beat us to the update, retry.
+                        UpdateStatus::Found(EntryStatus::Value(found)) => entry = found,
+                    }
+                }
+            }
+
+            reuse |= tombstones.any();
+            probe.next_group(self.table.mask);
+        };
+
+        // If went over the probe limit or found a copied entry, trigger a resize.
+        let mut next_table = self.get_or_alloc_next(None);
+
+        let next_table = match self.root.resize {
+            ResizeMode::Blocking => {
+                // In blocking mode we must complete the resize before proceeding.
+                self.help_copy(guard, false)
+            }
+
+            ResizeMode::Incremental(_) => {
+                // Help out with the copy.
+                if help_copy {
+                    next_table = self.help_copy(guard, false);
+                }
+
+                // The entry we want to update is being copied.
+                if let Some(i) = copying {
+                    // Wait for the entry to be copied.
+                    //
+                    // We could race with the copy to insert into the table. However,
+                    // this entire code path is very rare and likely to complete quickly,
+                    // so blocking allows us to make copies faster.
+                    self.wait_copied(i);
+                }
+
+                // Abort any reservations we probed past before inserting into the next table.
+                if !self.abort_reservations(h1, h2, guard) {
+                    return self.insert_with(new_entry, hash, should_replace, false, guard);
+                }
+
+                next_table
+            }
+        };
+
+        // Insert into the next table.
+        self.as_ref(next_table)
+            .insert_with(new_entry, hash, should_replace, false, guard)
+    }
+
+    // Removes a key from the map, returning the entry for the key if the key was previously in the map.
+    #[inline]
+    pub fn remove<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> Option<(&'g K, &'g V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        let hash = self.root.hasher.hash_one(key);
+        self.remove_hashed(hash, &mut |k| key.equivalent(k), guard)
+    }
+
+    // Removes the entry with the given hash that matches the predicate from the map, returning
+    // the entry if it was previously in the map.
+    #[inline]
+    pub fn remove_hashed<'g, E>(
+        &self,
+        hash: u64,
+        eq: &mut E,
+        guard: &'g impl Guard,
+    ) -> Option<(&'g K, &'g V)>
+    where
+        E: FnMut(&K) -> bool,
+    {
+        match self.remove_inner(hash, eq, &mut |_| true, true, guard) {
+            Ok(entry) => {
+                if entry.is_some() {
+                    self.root.watchers.notify(|| hash);
+                }
+
+                entry
+            }
+            Err(_) => unreachable!(),
+        }
+    }
+
+    // Removes a key from the map if its value satisfies the given predicate, returning the entry
+    // for the key if it was removed, or the current entry if the predicate was not satisfied.
+    #[inline]
+    pub fn remove_if<'g, Q, F>(
+        &self,
+        key: &Q,
+        mut should_remove: F,
+        guard: &'g impl Guard,
+    ) -> Result<Option<(&'g K, &'g V)>, (&'g K, &'g V)>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        F: FnMut(&V) -> bool,
+    {
+        let hash = self.root.hasher.hash_one(key);
+        let result = self.remove_inner(
+            hash,
+            &mut |k| key.equivalent(k),
+            &mut should_remove,
+            true,
+            guard,
+        );
+
+        if let Ok(Some(_)) = result {
+            self.root.watchers.notify(|| hash);
+        }
+
+        result
+    }
+
+    // Removes a key from the map if its value satisfies the given predicate, returning the entry
+    // for the key if the key was previously in the map.
+    //
+    // This is a recursive helper for `remove` and `remove_if`.
+    #[inline]
+    fn remove_inner<'g, E, F>(
+        &self,
+        hash: u64,
+        eq: &mut E,
+        should_remove: &mut F,
+        help_copy: bool,
+        guard: &'g impl Guard,
+    ) -> Result<Option<(&'g K, &'g V)>, (&'g K, &'g V)>
+    where
+        E: FnMut(&K) -> bool,
+        F: FnMut(&V) -> bool,
+    {
+        if self.table.raw.is_null() {
+            return Ok(None);
+        }
+
+        // Initialize the probe state.
+        let (h1, h2) = (meta::h1(hash), meta::h2(hash));
+        let mut probe = Probe::start(h1, self.table.mask);
+
+        // Probe a group at a time until we reach the limit.
+        let copying = 'probe: loop {
+            if probe.len > self.table.limit {
+                break None;
+            }
+
+            // Load the group metadata first for cheap searches.
+            let group = unsafe { self.table.group(probe.i) };
+
+            // Ignore any entries past the probe limit.
+            let probed = BitMask::first(self.table.limit - probe.len + 1);
+
+            // Check for potential matches.
+            for offset in group.matches(h2).and(probed) {
+                let i = probe.i + offset;
+
                 // Load the full entry.
-                let found = guard
-                    .protect(self.table.entry(probe.i), Ordering::Acquire)
-                    .unpack();
+                let mut entry =
+                    unsafe { guard.protect(self.table.entry(i), Ordering::Acquire) }.unpack();
 
                 // The entry was deleted, keep probing.
-                if found.ptr.is_null() {
-                    probe.next(self.table.mask);
-                    continue 'probe;
+                if entry.ptr.is_null() {
+                    continue;
                 }
 
-                // If the key matches, we might be able to update the value.
-                found
+                // Check for a full match.
+                if !eq(unsafe { &(*entry.ptr).key }) {
+                    continue;
+                }
+
+                // The entry is being copied to the new table, we have to complete the copy
+                // before we can remove it.
+                if entry.tag() & Entry::COPYING != 0 {
+                    break 'probe Some(i);
+                }
+
+                loop {
+                    // The current value does not satisfy the predicate.
+                    let entry_ref = unsafe { &(*entry.ptr) };
+                    if !should_remove(&entry_ref.value) {
+                        return Err((&entry_ref.key, &entry_ref.value));
+                    }
+
+                    match unsafe { self.update_at(i, entry, Entry::TOMBSTONE, guard) } {
+                        // Successfully removed the entry.
+                        UpdateStatus::Replaced(entry) => {
+                            self.removed_at(i, guard);
+
+                            let entry = unsafe { &(*entry.ptr) };
+                            return Ok(Some((&entry.key, &entry.value)));
+                        }
+
+                        // The entry is being copied to the new table, we have to complete the
+                        // copy before we can remove.
+                        UpdateStatus::Found(EntryStatus::Copied(_)) => break 'probe Some(i),
+
+                        // The entry was deleted.
+                        //
+                        // We know that at some point during our execution the key was not in
+                        // the map.
+                        UpdateStatus::Found(EntryStatus::Null) => return Ok(None),
+
+                        // Lost to a concurrent update, retry.
+                        UpdateStatus::Found(EntryStatus::Value(found)) => entry = found,
+                    }
+                }
             }
-            // Otherwise, continue probing.
-            else {
-                probe.next(self.table.mask);
-                continue 'probe;
-            };
 
-            // Check for a full match.
-            if unsafe { (*entry.ptr).key != new_ref.key } {
-                probe.next(self.table.mask);
-                continue 'probe;
+            // The key is not in the table.
+            //
+            // It also cannot be in the next table because we have not went over the probe limit.
+            if group.empty().and(probed).any() {
+                return Ok(None);
             }
 
-            // The entry is being copied to the new table.
-            if entry.tag() & Entry::COPYING != 0 {
-                break 'probe Some(probe.i);
+            probe.next_group(self.table.mask);
+        };
+
+        match self.root.resize {
+            ResizeMode::Blocking => match copying {
+                // The entry we want to remove is being copied.
+                Some(_) => {
+                    // In blocking mode we must complete the resize before proceeding.
+                    let next_table = self.help_copy(guard, false);
+
+                    // Continue in the new table.
+                    return self.as_ref(next_table).remove_inner(
+                        hash,
+                        eq,
+                        should_remove,
+                        help_copy,
+                        guard,
+                    );
+                }
+                // If we went over the probe limit, the key is not in this table.
+                None => Ok(None),
+            },
+
+            ResizeMode::Incremental(_) => {
+                // The entry we want to remove is being copied.
+                if let Some(i) = copying {
+                    let next_table = self.next_table_ref().unwrap();
+
+                    // Help out with the copy.
+                    if help_copy {
+                        self.help_copy(guard, false);
+                    }
+
+                    // Wait for the entry to be copied.
+                    self.wait_copied(i);
+
+                    // Continue in the new table.
+                    return next_table.remove_inner(hash, eq, should_remove, false, guard);
+                }
+
+                // In incremental resize mode, we have to check the next table if we found
+                // a copied entry or went over the probe limit.
+                if let Some(next_table) = self.next_table_ref() {
+                    // Help out with the copy.
+                    if help_copy {
+                        self.help_copy(guard, false);
+                    }
+
+                    // Continue in the new table.
+                    return next_table.remove_inner(hash, eq, should_remove, false, guard);
+                }
+
+                // Otherwise, the key is not in the table.
+                Ok(None)
             }
+        }
+    }
 
-            // Return an error for calls to `try_insert`.
-            if !should_replace {
-                return RawInsertResult::Error {
-                    current: entry,
-                    not_inserted: new_entry.ptr,
-                };
+    // Completes the removal of the entry at the given index, after it was replaced with a
+    // tombstone.
+    #[inline]
+    fn removed_at(&self, i: usize, guard: &impl Guard) {
+        // Mark the entry as a tombstone.
+        //
+        // Note that metadata is only initialized if the entry is still marked as empty, so this
+        // cannot be overwritten by a lagging insertion.
+        unsafe { self.table.meta(i).store(meta::TOMBSTONE, Ordering::Release) };
+        let tombstones = self.add_tombstone(guard);
+
+        // Decrement the table length.
+        let count = self.root.count.get(guard.thread_id());
+        count.fetch_sub(1, Ordering::Relaxed);
+
+        // Shrink or compact the table if we passed the configured thresholds.
+        self.maybe_shrink(tombstones, guard);
+        self.maybe_compact(tombstones, guard);
+    }
+
+    // Removes an entry observed by `get_slot` if it has not been modified since, returning
+    // `true` if it was removed.
+    #[inline]
+    pub fn remove_slot(&self, slot: Slot<K, V>, guard: &impl Guard) -> bool {
+        // Safety: The entry was observed by `get_slot` and is protected by the caller's guard.
+        let entry = unsafe { &*slot.entry.ptr };
+
+        // Try to remove the entry from the slot it was observed in.
+        let table = self.as_ref(slot.table);
+        let removed = match unsafe { table.update_slot(slot, Entry::TOMBSTONE, guard) } {
+            Some(removed) => {
+                if removed {
+                    table.removed_at(slot.i, guard);
+                }
+
+                removed
+            }
+
+            // The entry is being copied to the next table, so we have to find it there.
+            //
+            // Entries are compared by identity, so this cannot match a different entry.
+            None => {
+                let hash = self.root.hasher.hash_one(&entry.key);
+                let result = self.remove_inner(
+                    hash,
+                    &mut |k| ptr::eq(k, &entry.key),
+                    &mut |v| ptr::eq(v, &entry.value),
+                    true,
+                    guard,
+                );
+
+                matches!(result, Ok(Some(_)))
             }
+        };
+
+        if removed {
+            self.root.notify(&entry.key);
+        }
+
+        removed
+    }
 
-            loop {
-                // Try to update the value.
-                match self.update_at(probe.i, entry, new_entry.raw, guard) {
-                    // Successfully updated.
-                    UpdateStatus::Replaced(entry) => {
-                        return RawInsertResult::Replaced(&(*entry.ptr).value)
-                    }
+    // Replaces an entry observed by `get_slot` if it has not been modified since, returning the
+    // new value on success, or the value that was not inserted on failure.
+    //
+    // The key must be equal to the key of the observed entry.
+    #[inline]
+    pub fn replace_slot<'g>(
+        &self,
+        slot: Slot<K, V>,
+        key: K,
+        value: V,
+        guard: &'g impl Guard,
+    ) -> Result<&'g V, V> {
+        // Safety: The entry was observed by `get_slot` and is protected by the caller's guard.
+        let entry = unsafe { &*slot.entry.ptr };
+        assert!(
+            entry.key == key,
+            "the key must be equal to the key of the entry being replaced"
+        );
 
-                    // The entry is being copied.
-                    UpdateStatus::Found(EntryStatus::Copied(_)) => break 'probe Some(probe.i),
+        // Allocate the entry to be inserted.
+        let new_entry = self.root.alloc_entry(Entry {
+            key,
+            value,
+            link: self.root.collector.link(),
+        });
 
-                    // The entry was deleted before we could update it, continue probing.
-                    UpdateStatus::Found(EntryStatus::Null) => {
-                        probe.next(self.table.mask);
-                        continue 'probe;
-                    }
+        // Try to replace the entry in the slot it was observed in.
+        let table = self.as_ref(slot.table);
+        let replaced = match unsafe { table.update_slot(slot, new_entry, guard) } {
+            Some(replaced) => replaced,
 
-                    // Someone else beat us to the update, retry.
-                    UpdateStatus::Found(EntryStatus::Value(found)) => entry = found,
-                }
-            }
+            // The entry is being copied to the next table, so we have to find it there.
+            //
+            // Entries are compared by identity, so this cannot match a different entry.
+            None => unsafe {
+                self.replace_if_with(new_entry, &mut |v| ptr::eq(v, &entry.value), true, guard)
+                    .is_ok()
+            },
         };
 
-        // If went over the probe limit or found a copied entry, trigger a resize.
-        let mut next_table = self.get_or_alloc_next(None);
+        if replaced {
+            // Safety: The entry was inserted into the table and is protected by the guard.
+            let new_entry = unsafe { &*new_entry };
+            self.root.notify(&new_entry.key);
+            Ok(&new_entry.value)
+        } else {
+            // Safety: We allocated this entry above and it was not inserted into the table.
+            let not_inserted = unsafe { Entry::into_inner::<A>(new_entry) };
 
-        let next_table = match self.root.resize {
-            ResizeMode::Blocking => {
-                // In blocking mode we must complete the resize before proceeding.
-                self.help_copy(guard, false)
-            }
+            Err(not_inserted.value)
+        }
+    }
 
-            ResizeMode::Incremental(_) => {
-                // Help out with the copy.
-                if help_copy {
-                    next_table = self.help_copy(guard, false);
+    // Attempts to replace an entry in the slot it was observed in.
+    //
+    // Returns `Some(true)` if the entry was replaced, `Some(false)` if it was modified since it
+    // was observed, or `None` if it is being copied to the next table.
+    //
+    // # Safety
+    //
+    // The new entry must be a valid pointer or a tombstone.
+    #[inline]
+    unsafe fn update_slot(
+        &self,
+        slot: Slot<K, V>,
+        new_entry: *mut Entry<K, V>,
+        guard: &impl Guard,
+    ) -> Option<bool> {
+        let mut current = slot.entry;
+
+        loop {
+            match unsafe { self.update_at(slot.i, current, new_entry, guard) } {
+                // Successfully replaced the entry.
+                UpdateStatus::Replaced(_) => return Some(true),
+
+                // The compare-exchange failed spuriously, or the tag bits of the entry changed.
+                UpdateStatus::Found(EntryStatus::Value(found)) if found.ptr == current.ptr => {
+                    current = found;
                 }
 
-                // The entry we want to update is being copied.
-                if let Some(i) = copying {
-                    // Wait for the entry to be copied.
-                    //
-                    // We could race with the copy to insert into the table. However,
-                    // this entire code path is very rare and likely to complete quickly,
-                    // so blocking allows us to make copies faster.
-                    self.wait_copied(i);
+                // The entry is being copied to the next table.
+                UpdateStatus::Found(EntryStatus::Copied(found)) if found.ptr == current.ptr => {
+                    return None
                 }
 
-                next_table
+                // The entry was replaced or removed.
+                UpdateStatus::Found(_) => return Some(false),
             }
-        };
-
-        // Insert into the next table.
-        self.as_ref(next_table)
-            .insert_with(new_entry, should_replace, false, guard)
+        }
     }
 
-    // Removes a key from the map, returning the entry for the key if the key was previously in the map.
+    // Replaces the value of a key if its current value satisfies the given predicate, returning the
+    // new value on success, or the current value and the value that was not inserted on failure.
     #[inline]
-    pub fn remove<'g, Q: ?Sized>(&self, key: &Q, guard: &'g impl Guard) -> Option<(&'g K, &'g V)>
+    pub fn replace_if<'g, F>(
+        &self,
+        key: K,
+        new: V,
+        mut should_replace: F,
+        guard: &'g impl Guard,
+    ) -> Result<&'g V, (Option<&'g V>, V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq,
+        F: FnMut(&V) -> bool,
     {
-        self.remove_inner(key, true, guard)
+        // Allocate the entry to be inserted.
+        let new_entry = self.root.alloc_entry(Entry {
+            key,
+            value: new,
+            link: self.root.collector.link(),
+        });
+
+        // Safety: We just allocated the entry above.
+        match unsafe { self.replace_if_with(new_entry, &mut should_replace, true, guard) } {
+            Ok(()) => {
+                // Safety: The entry was inserted into the table and is protected by the guard.
+                let new_entry = unsafe { &*new_entry };
+                self.root.notify(&new_entry.key);
+
+                Ok(&new_entry.value)
+            }
+            Err(found) => {
+                // Safety: We allocated this entry above and it was not inserted into the table.
+                let not_inserted = unsafe { Entry::into_inner::<A>(new_entry) };
+
+                Err((found, not_inserted.value))
+            }
+        }
     }
 
-    // Removes a key from the map, returning the entry for the key if the key was previously in the map.
+    // Replaces the value of a key if its current value satisfies the given predicate, returning the
+    // current value on failure.
+    //
+    // This is a recursive helper for `replace_if`.
+    //
+    // # Safety
     //
-    // This is a recursive helper for `remove_entry`.
+    // The new entry must be a valid pointer.
     #[inline]
-    pub fn remove_inner<'g, Q: ?Sized>(
+    unsafe fn replace_if_with<'g, F>(
         &self,
-        key: &Q,
+        new_entry: *mut Entry<K, V>,
+        should_replace: &mut F,
         help_copy: bool,
         guard: &'g impl Guard,
-    ) -> Option<(&'g K, &'g V)>
+    ) -> Result<(), Option<&'g V>>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq,
+        F: FnMut(&V) -> bool,
     {
         if self.table.raw.is_null() {
-            return None;
+            return Err(None);
         }
 
+        // Safety: The new entry is guaranteed to be valid by the caller.
+        let key = unsafe { &(*new_entry).key };
+
         // Initialize the probe state.
         let (h1, h2) = self.hash(key);
         let mut probe = Probe::start(h1, self.table.mask);
 
-        // Probe until we reach the limit.
+        // Probe a group at a time until we reach the limit.
         let copying = 'probe: loop {
             if probe.len > self.table.limit {
                 break None;
             }
 
-            // Load the entry metadata first for cheap searches.
-            let meta = unsafe { self.table.meta(probe.i).load(Ordering::Acquire) };
-
-            // The key is not in the table.
-            // It also cannot be in the next table because we have not went over the probe limit.
-            if meta == meta::EMPTY {
-                return None;
-            }
-
-            // Check for a potential match.
-            if meta != h2 {
-                probe.next(self.table.mask);
-                continue 'probe;
-            }
+            // Load the group metadata first for cheap searches.
+            let group = unsafe { self.table.group(probe.i) };
 
-            // Load the full entry.
-            let mut entry =
-                unsafe { guard.protect(self.table.entry(probe.i), Ordering::Acquire) }.unpack();
+            // Ignore any entries past the probe limit.
+            let probed = BitMask::first(self.table.limit - probe.len + 1);
 
-            // The entry was deleted, keep probing.
-            if entry.ptr.is_null() {
-                probe.next(self.table.mask);
-                continue 'probe;
-            }
+            // Check for potential matches.
+            for offset in group.matches(h2).and(probed) {
+                let i = probe.i + offset;
 
-            // Check for a full match.
-            if unsafe { (*entry.ptr).key.borrow() != key } {
-                probe.next(self.table.mask);
-                continue 'probe;
-            }
+                // Load the full entry.
+                let mut entry =
+                    unsafe { guard.protect(self.table.entry(i), Ordering::Acquire) }.unpack();
 
-            // The entry is being copied to the new table, we have to complete the copy before
-            // we can remove it.
-            if entry.tag() & Entry::COPYING != 0 {
-                break 'probe Some(probe.i);
-            }
+                // The entry was deleted, keep probing.
+                if entry.ptr.is_null() {
+                    continue;
+                }
 
-            loop {
-                match unsafe { self.update_at(probe.i, entry, Entry::TOMBSTONE, guard) } {
-                    // Successfully removed the entry.
-                    UpdateStatus::Replaced(entry) => {
-                        // Mark the entry as a tombstone.
-                        //
-                        // Note that this might end up being overwritten by the metadata hash
-                        // if the initial insertion is lagging behind, but we avoid the RMW
-                        // and sacrifice reads in the extremely rare case.
-                        unsafe {
-                            self.table
-                                .meta(probe.i)
-                                .store(meta::TOMBSTONE, Ordering::Release)
-                        };
+                // Check for a full match.
+                if unsafe { (*entry.ptr).key != *key } {
+                    continue;
+                }
 
-                        // Decrement the table length.
-                        let count = self.root.count.get(guard.thread_id());
-                        count.fetch_sub(1, Ordering::Relaxed);
+                // The entry is being copied to the new table, we have to complete the copy
+                // before we can update it.
+                if entry.tag() & Entry::COPYING != 0 {
+                    break 'probe Some(i);
+                }
 
-                        let entry = unsafe { &(*entry.ptr) };
-                        return Some((&entry.key, &entry.value));
+                loop {
+                    // The current value does not satisfy the predicate.
+                    let value = unsafe { &(*entry.ptr).value };
+                    if !should_replace(value) {
+                        return Err(Some(value));
                     }
 
-                    // The entry is being copied to the new table, we have to complete the copy
-                    // before we can remove.
-                    UpdateStatus::Found(EntryStatus::Copied(_)) => break 'probe Some(probe.i),
+                    match unsafe { self.update_at(i, entry, new_entry, guard) } {
+                        // Successfully updated the entry.
+                        UpdateStatus::Replaced(_) => return Ok(()),
 
-                    // The entry was deleted.
-                    //
-                    // We know that at some point during our execution the key was not in the map.
-                    UpdateStatus::Found(EntryStatus::Null) => return None,
+                        // The entry is being copied to the new table, we have to complete the
+                        // copy before we can update.
+                        UpdateStatus::Found(EntryStatus::Copied(_)) => break 'probe Some(i),
 
-                    // Lost to a concurrent update, retry.
-                    UpdateStatus::Found(EntryStatus::Value(found)) => entry = found,
+                        // The entry was deleted.
+                        //
+                        // We know that at some point during our execution the key was not in
+                        // the map.
+                        UpdateStatus::Found(EntryStatus::Null) => return Err(None),
+
+                        // Lost to a concurrent update, retry.
+                        UpdateStatus::Found(EntryStatus::Value(found)) => entry = found,
+                    }
                 }
             }
+
+            // The key is not in the table.
+            //
+            // It also cannot be in the next table because we have not went over the probe limit.
+            if group.empty().and(probed).any() {
+                return Err(None);
+            }
+
+            probe.next_group(self.table.mask);
         };
 
         match self.root.resize {
             ResizeMode::Blocking => match copying {
-                // The entry we want to remove is being copied.
+                // The entry we want to update is being copied.
                 Some(_) => {
                     // In blocking mode we must complete the resize before proceeding.
                     let next_table = self.help_copy(guard, false);
 
                     // Continue in the new table.
-                    return self.as_ref(next_table).remove_inner(key, help_copy, guard);
+                    unsafe {
+                        self.as_ref(next_table).replace_if_with(
+                            new_entry,
+                            should_replace,
+                            help_copy,
+                            guard,
+                        )
+                    }
                 }
                 // If we went over the probe limit, the key is not in this table.
-                None => None,
+                None => Err(None),
             },
 
             ResizeMode::Incremental(_) => {
-                // The entry we want to remove is being copied.
+                // The entry we want to update is being copied.
                 if let Some(i) = copying {
                     let next_table = self.next_table_ref().unwrap();
 
@@ -706,90 +1935,412 @@ where
                         self.help_copy(guard, false);
                     }
 
-                    // Wait for the entry to be copied.
-                    self.wait_copied(i);
+                    // Wait for the entry to be copied.
+                    self.wait_copied(i);
+
+                    // Continue in the new table.
+                    return unsafe {
+                        next_table.replace_if_with(new_entry, should_replace, false, guard)
+                    };
+                }
+
+                // In incremental resize mode, we have to check the next table if we found
+                // a copied entry or went over the probe limit.
+                if let Some(next_table) = self.next_table_ref() {
+                    // Help out with the copy.
+                    if help_copy {
+                        self.help_copy(guard, false);
+                    }
+
+                    // Continue in the new table.
+                    return unsafe {
+                        next_table.replace_if_with(new_entry, should_replace, false, guard)
+                    };
+                }
+
+                // Otherwise, the key is not in the table.
+                Err(None)
+            }
+        }
+    }
+
+    // Attempts to insert an entry at the given index.
+    #[inline]
+    unsafe fn insert_at(
+        &self,
+        i: usize,
+        meta: u8,
+        new_entry: *mut Entry<K, V>,
+        guard: &impl Guard,
+    ) -> InsertStatus<K, V> {
+        let entry = unsafe { self.table.entry(i) };
+
+        // Try to claim the empty entry.
+        //
+        // Note that this must be `SeqCst` to synchronize with any watchers.
+        let found = match entry.compare_exchange(
+            ptr::null_mut(),
+            new_entry,
+            Ordering::SeqCst,
+            Ordering::Acquire,
+        ) {
+            // Successfully claimed the entry.
+            Ok(_) => {
+                // Update the metadata table.
+                unsafe { self.init_meta(i, meta) };
+
+                // Return the value we inserted.
+                return InsertStatus::Inserted;
+            }
+
+            // Lost to a concurrent update.
+            Err(found) => found.unpack(),
+        };
+
+        unsafe { self.insert_found(i, meta, found, guard) }
+    }
+
+    // Handles a failed attempt to claim the empty entry at the given index.
+    #[inline]
+    unsafe fn insert_found(
+        &self,
+        i: usize,
+        meta: u8,
+        found: Tagged<Entry<K, V>>,
+        guard: &impl Guard,
+    ) -> InsertStatus<K, V> {
+        // The entry was sealed, and may only be claimed by reserving it.
+        if found.raw == Entry::SEALED {
+            return InsertStatus::Sealed;
+        }
+
+        // The entry is reserved by a concurrent insert. Reserved entries are never empty again,
+        // so we can probe past it.
+        if found.raw == Entry::RESERVED {
+            if unsafe { self.skip_reserved(i, meta, guard) } {
+                return InsertStatus::Found(EntryStatus::Null);
+            }
+
+            // The reservation was published or aborted concurrently, check the entry again.
+            let found = unsafe { self.table.entry(i) }
+                .load(Ordering::Acquire)
+                .unpack();
+            return unsafe { self.insert_found(i, meta, found, guard) };
+        }
+
+        // Note that we use our own metadata for deleted entries, as only the thread that
+        // deleted the entry may mark it as a tombstone. Any non-empty metadata is enough
+        // to keep the probe chain alive.
+        let entry = unsafe { self.table.entry(i) };
+        let (meta, status) = match EntryStatus::from(found) {
+            EntryStatus::Value(_) | EntryStatus::Copied(_) => {
+                // Protect the entry before accessing it.
+                let found = guard.protect(entry, Ordering::Acquire).unpack();
+
+                // Re-check the entry status.
+                match EntryStatus::from(found) {
+                    EntryStatus::Value(found) | EntryStatus::Copied(found) => {
+                        // An entry was inserted, we have to hash it to get the metadata.
+                        let hash = self.root.hasher.hash_one(unsafe { &(*found.ptr).key });
+                        (meta::h2(hash), EntryStatus::Value(found))
+                    }
+
+                    // The entry was deleted or null copied.
+                    EntryStatus::Null => (meta, EntryStatus::Null),
+                }
+            }
+
+            // The entry was deleted or null copied.
+            EntryStatus::Null => (meta, EntryStatus::Null),
+        };
+
+        // Ensure the meta table is updated to keep the probe chain alive for readers.
+        unsafe { self.init_meta(i, meta) };
+
+        InsertStatus::Found(status)
+    }
+
+    // Ensures that the metadata of the reserved entry at the given index is initialized before
+    // we probe past it.
+    //
+    // The metadata of an entry reserved while empty is initialized by the insert after it is
+    // reserved. Readers stop probing at empty metadata, so if the insert has not done so yet,
+    // the reservation is aborted, initializing the metadata with our own. Returns `false` if
+    // the entry is no longer reserved and must be checked again.
+    #[inline]
+    unsafe fn skip_reserved(&self, i: usize, meta: u8, guard: &impl Guard) -> bool {
+        let found = unsafe { self.table.meta(i) }.load(Ordering::Acquire);
+        found != meta::EMPTY || Reservation::abort(self.table, i, meta, guard.thread_id())
+    }
+
+    // Attempts to insert an entry into a tombstone earlier in the probe sequence, falling back
+    // to the empty entry at the given index, found after probing `len` entries.
+    //
+    // The entry is reserved before the probe sequence is validated, confirming that the key
+    // is not present, and only then published. Concurrent inserts of the same key either see
+    // our reservation while validating their own, or publish an entry we see while validating
+    // ours. Conflicting reservations are aborted rather than waited on, so an insert that
+    // stalls while holding a reservation never blocks other operations.
+    //
+    // Inserts that may have probed past a tombstone that was reused, or an empty entry that was
+    // claimed after it was sealed, must also go through a reservation to confirm their key is
+    // not present, even if there is no tombstone to reuse.
+    #[inline]
+    unsafe fn insert_reuse(
+        &self,
+        i: usize,
+        len: usize,
+        h1: usize,
+        h2: u8,
+        new_entry: *mut Entry<K, V>,
+        guard: &impl Guard,
+    ) -> InsertStatus<K, V> {
+        // Entries are only reserved while the table is not being resized, as an insert of the
+        // same key may continue in the next table without seeing our reservation.
+        if !self.table.state().next.load(Ordering::SeqCst).is_null() {
+            // Any reservations we probed past were made before the resize started, and may
+            // still be published.
+            if !unsafe { self.abort_reservations(h1, h2, guard) } {
+                return InsertStatus::Retry;
+            }
+
+            // Otherwise, no entry for our key can be published in this table anymore, so we
+            // can claim the empty entry directly.
+            return unsafe { self.insert_unsealed(i, len, h1, h2, new_entry, guard) };
+        }
 
-                    // Continue in the new table.
-                    return next_table.remove_inner(key, false, guard);
+        // Try to reserve the first tombstone in the probe sequence.
+        let mut reservation = None;
+        let mut rescan = Probe::start(h1, self.table.mask);
+        while rescan.len < len {
+            let meta = unsafe { self.table.meta(rescan.i) }.load(Ordering::Acquire);
+
+            if meta == meta::TOMBSTONE {
+                reservation = Reservation::tombstone(self.table, rescan.i, h2, guard.thread_id());
+
+                if reservation.is_some() {
+                    break;
                 }
+            }
 
-                // In incremental resize mode, we have to check the next table if we found
-                // a copied entry or went over the probe limit.
-                if let Some(next_table) = self.next_table_ref() {
-                    // Help out with the copy.
-                    if help_copy {
-                        self.help_copy(guard, false);
-                    }
+            rescan.next(self.table.mask);
+        }
 
-                    // Continue in the new table.
-                    return next_table.remove_inner(key, false, guard);
+        // Otherwise, reserve the empty entry.
+        let reservation = match reservation {
+            Some(reservation) => reservation,
+            None => match Reservation::empty(self.table, i, h2, guard.thread_id()) {
+                Ok(reservation) => {
+                    // Update the metadata table, keeping the entry hidden from readers.
+                    unsafe { self.init_meta(i, h2) };
+                    reservation
                 }
 
-                // Otherwise, the key is not in the table.
-                None
-            }
+                // Lost to a concurrent update.
+                Err(found) => return unsafe { self.insert_found(i, h2, found.unpack(), guard) },
+            },
+        };
+
+        // Confirm that the key was not inserted concurrently.
+        let key = unsafe { &(*new_entry).key };
+        match unsafe { self.validate(h1, h2, reservation.i, key, guard) } {
+            Validation::Valid => {}
+
+            // Abort the reservation and restart the probe to find the key.
+            Validation::Found => return InsertStatus::Retry,
+
+            // Abort the reservation and restart the probe, as entries can no longer be
+            // reserved.
+            Validation::Copying => return InsertStatus::Retry,
+        }
+
+        if reservation.publish(new_entry) {
+            return InsertStatus::Inserted;
         }
+
+        // The reservation was aborted by a concurrent insert or null copied, so we have
+        // to restart the probe.
+        InsertStatus::Retry
     }
 
-    // Attempts to insert an entry at the given index.
+    // Attempts to insert an entry at the given index, found after probing `len` entries,
+    // without a reservation.
+    //
+    // If the entry is sealed, the seal is moved to the next empty entry before it is claimed,
+    // so concurrent inserts of the key the seal was validated for still reach a seal.
     #[inline]
-    unsafe fn insert_at(
+    unsafe fn insert_unsealed(
         &self,
         i: usize,
-        meta: u8,
+        len: usize,
+        h1: usize,
+        h2: u8,
         new_entry: *mut Entry<K, V>,
         guard: &impl Guard,
     ) -> InsertStatus<K, V> {
         let entry = unsafe { self.table.entry(i) };
 
-        // Try to claim the empty entry.
-        let found = match entry.compare_exchange(
-            ptr::null_mut(),
-            new_entry,
-            Ordering::Release,
-            Ordering::Acquire,
-        ) {
-            // Successfully claimed the entry.
-            Ok(_) => {
-                // Update the metadata table.
-                unsafe { self.table.meta(i).store(meta, Ordering::Release) };
+        let mut current = ptr::null_mut();
+        loop {
+            // Note that this must be `SeqCst` to synchronize with any watchers.
+            match entry.compare_exchange(current, new_entry, Ordering::SeqCst, Ordering::Acquire) {
+                // Successfully claimed the entry.
+                Ok(_) => {
+                    // Update the metadata table.
+                    unsafe { self.init_meta(i, h2) };
+                    return InsertStatus::Inserted;
+                }
 
-                // Return the value we inserted.
-                return InsertStatus::Inserted;
+                // The entry was sealed.
+                Err(found) if found == Entry::SEALED && current.is_null() => {
+                    self.forward_seal(h1, len);
+                    current = found;
+                }
+
+                // Lost to a concurrent update.
+                Err(found) => return unsafe { self.insert_found(i, h2, found.unpack(), guard) },
             }
+        }
+    }
 
-            // Lost to a concurrent update.
-            Err(found) => found.unpack(),
-        };
+    // Confirms that the given key is not present in its probe sequence, ignoring the entry
+    // we reserved.
+    //
+    // Any reservations that may be for the same key are aborted, and the first empty entry in
+    // the probe sequence is sealed, forcing later inserts of the key to validate against our
+    // reservation. If any entry is being copied or a next table was allocated, the key may
+    // already have been inserted into the next table, so the reservation cannot be published.
+    #[inline]
+    unsafe fn validate(
+        &self,
+        h1: usize,
+        h2: u8,
+        reserved: usize,
+        key: &K,
+        guard: &impl Guard,
+    ) -> Validation {
+        let mut copying = false;
+        let mut probe = Probe::start(h1, self.table.mask);
+        while probe.len <= self.table.limit {
+            if probe.i == reserved {
+                probe.next(self.table.mask);
+                continue;
+            }
 
-        let (meta, status) = match EntryStatus::from(found) {
-            EntryStatus::Value(_) | EntryStatus::Copied(_) => {
-                // Protect the entry before accessing it.
-                let found = guard.protect(entry, Ordering::Acquire).unpack();
+            // Note that these loads must be `SeqCst` to synchronize with concurrent reservations.
+            let entry = unsafe { self.table.entry(probe.i) };
+            let found = entry.load(Ordering::SeqCst);
 
-                // Re-check the entry status.
-                match EntryStatus::from(found) {
-                    EntryStatus::Value(found) | EntryStatus::Copied(found) => {
-                        // An entry was inserted, we have to hash it to get the metadata.
-                        let hash = self.root.hasher.hash_one(&(*found.ptr).key);
-                        (meta::h2(hash), EntryStatus::Value(found))
-                    }
+            // The table is being copied. Note that we still have to check the rest of the probe
+            // sequence, as the key may not have been copied yet.
+            copying |= found.addr() & Entry::COPYING != 0;
 
-                    // The entry was deleted or null copied.
-                    EntryStatus::Null => (meta::TOMBSTONE, EntryStatus::Null),
+            // Reached the end of the probe sequence.
+            if found == Entry::SEALED {
+                break;
+            }
+
+            // Seal the empty entry.
+            if found.is_null() {
+                match entry.compare_exchange(
+                    ptr::null_mut(),
+                    Entry::SEALED,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => break,
+
+                    // Lost to a concurrent update, check the entry again.
+                    Err(_) => continue,
                 }
             }
 
-            // The entry was deleted or null copied.
-            EntryStatus::Null => (meta::TOMBSTONE, EntryStatus::Null),
-        };
+            // Ignore any entries with different metadata. Note that the metadata may not yet be
+            // initialized for entries that were claimed while empty.
+            let meta = unsafe { self.table.meta(probe.i) }.load(Ordering::Acquire);
+            if meta != h2 && meta != meta::EMPTY {
+                probe.next(self.table.mask);
+                continue;
+            }
 
-        // Ensure the meta table is updated to keep the probe chain alive for readers.
-        if self.table.meta(i).load(Ordering::Relaxed) == meta::EMPTY {
-            self.table.meta(i).store(meta, Ordering::Release);
+            // A concurrent insert may be reserving the entry for the same key.
+            if found == Entry::RESERVED {
+                Reservation::abort(self.table, probe.i, h2, guard.thread_id());
+                probe.next(self.table.mask);
+                continue;
+            }
+
+            // Check for a full match.
+            let found = guard.protect(entry, Ordering::SeqCst).unpack();
+            if !found.ptr.is_null() && unsafe { (*found.ptr).key == *key } {
+                return Validation::Found;
+            }
+
+            probe.next(self.table.mask);
         }
 
-        InsertStatus::Found(status)
+        // The key cannot be present past the probe limit in this table, but an insert that
+        // probed past our reservation may have continued in the next table. Inserts abort any
+        // reservations they probed past after allocating the next table, so if it is not yet
+        // allocated, our reservation will be aborted by any such insert.
+        if copying || !self.table.state().next.load(Ordering::SeqCst).is_null() {
+            return Validation::Copying;
+        }
+
+        Validation::Valid
+    }
+
+    // Aborts any reservations in the probe sequence that may be for the same key, before
+    // continuing in the next table.
+    //
+    // A concurrent insert of the key may have reserved an entry that we probed past, and would
+    // not see our entry in the next table while validating. Returns `false` if a reservation
+    // was published instead, in which case the probe must be restarted.
+    //
+    // Note that this must be called after the next table is allocated.
+    #[inline]
+    unsafe fn abort_reservations(&self, h1: usize, h2: u8, guard: &impl Guard) -> bool {
+        let mut probe = Probe::start(h1, self.table.mask);
+        while probe.len <= self.table.limit {
+            // Note that this load must be `SeqCst` to synchronize with concurrent reservations.
+            let entry = unsafe { self.table.entry(probe.i) };
+            let found = entry.load(Ordering::SeqCst);
+
+            // Entries are never empty again, so we could not have probed further.
+            if found.is_null() {
+                break;
+            }
+
+            if found == Entry::RESERVED {
+                // Ignore any reservations with different metadata.
+                let meta = unsafe { self.table.meta(probe.i) }.load(Ordering::Acquire);
+
+                if (meta == h2 || meta == meta::EMPTY)
+                    && !Reservation::abort(self.table, probe.i, h2, guard.thread_id())
+                    && !entry.load(Ordering::Acquire).unpack().ptr.is_null()
+                {
+                    return false;
+                }
+            }
+
+            probe.next(self.table.mask);
+        }
+
+        true
+    }
+
+    // Initializes the metadata for an entry that was claimed while empty.
+    //
+    // The metadata of an entry is initialized exactly once, so lagging writers cannot
+    // overwrite the metadata of a tombstone that was reused.
+    #[inline]
+    unsafe fn init_meta(&self, i: usize, meta: u8) {
+        let _ = unsafe { self.table.meta(i) }.compare_exchange(
+            meta::EMPTY,
+            meta,
+            Ordering::Release,
+            Ordering::Relaxed,
+        );
     }
 
     // Attempts to replace the value of an existing entry at the given index.
@@ -804,10 +2355,12 @@ where
         let entry = unsafe { self.table.entry(i) };
 
         // Try to perform the update.
+        //
+        // Note that this must be `SeqCst` to synchronize with any watchers.
         let found = match entry.compare_exchange_weak(
             current.raw,
             new_entry,
-            Ordering::Release,
+            Ordering::SeqCst,
             Ordering::Acquire,
         ) {
             // Successfully updated.
@@ -827,7 +2380,16 @@ where
                 let found = guard.protect(entry, Ordering::Acquire).unpack();
 
                 // Re-check the entry status.
-                EntryStatus::from(found)
+                match EntryStatus::from(found) {
+                    // The entry was deleted and its tombstone was reused for a different key.
+                    EntryStatus::Value(found)
+                        if unsafe { (*found.ptr).key != (*current.ptr).key } =>
+                    {
+                        EntryStatus::Null
+                    }
+
+                    status => status,
+                }
             }
 
             // The entry was copied.
@@ -847,13 +2409,15 @@ where
     #[inline]
     pub fn reserve(&mut self, additional: usize, guard: &impl Guard) {
         // The table has not yet been allocated, try to initialize it.
-        if self.table.raw.is_null() && self.init(Some(probe::entries_for(additional))) {
+        if self.table.raw.is_null() && self.init(Some(self.root.probe.entries_for(additional))) {
             return;
         }
 
         loop {
-            let capacity =
-                probe::entries_for(self.root.count.sum().checked_add(additional).unwrap());
+            let capacity = self
+                .root
+                .probe
+                .entries_for(self.root.count.sum().checked_add(additional).unwrap());
 
             // We have enough capacity.
             if self.table.len() >= capacity {
@@ -900,11 +2464,13 @@ where
                 }
 
                 // Try to delete the entry.
+                //
+                // Note that this must be `SeqCst` to synchronize with any watchers.
                 let result = unsafe {
                     self.table.entry(i).compare_exchange(
                         entry.raw,
                         Entry::TOMBSTONE,
-                        Ordering::Release,
+                        Ordering::SeqCst,
                         Ordering::Acquire,
                     )
                 };
@@ -914,11 +2480,15 @@ where
                     Ok(_) => unsafe {
                         // Update the metadata table.
                         self.table.meta(i).store(meta::TOMBSTONE, Ordering::Release);
+                        self.add_tombstone(guard);
 
                         // Decrement the table length.
                         let count = self.root.count.get(guard.thread_id());
                         count.fetch_sub(1, Ordering::Relaxed);
 
+                        // Notify any watchers of the removal.
+                        self.root.notify(&(*entry.ptr).key);
+
                         // Safety: We just removed the old value from this table.
                         self.defer_retire(entry, guard);
 
@@ -938,6 +2508,123 @@ where
             let next_table = self.help_copy(guard, true);
             return self.as_ref(next_table).clear(guard);
         }
+
+        // The table is likely empty now, shrink it if necessary.
+        self.check_shrink(guard);
+    }
+
+    // Returns an iterator that removes and yields all entries matching the predicate.
+    #[inline]
+    pub fn extract_if<'g, F, G>(
+        &mut self,
+        f: F,
+        guard: &'g G,
+    ) -> ExtractIf<'root, 'g, K, V, S, A, F, G>
+    where
+        F: FnMut(&K, &V) -> bool,
+        G: Guard,
+    {
+        if !self.table.raw.is_null() {
+            // Get a clean copy of the table to delete from.
+            self.linearize(guard);
+        }
+
+        ExtractIf {
+            i: 0,
+            end: None,
+            copying: false,
+            map: self.clone(),
+            f,
+            guard,
+        }
+    }
+
+    // Returns an iterator that removes and yields the entries in the given slot range of
+    // this table that match the predicate.
+    //
+    // Unlike `extract_if`, entries that are being copied to a new table are skipped rather
+    // than followed, which can be detected with `ExtractIf::is_complete`.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn extract_if_range<'g, F, G>(
+        &self,
+        range: Range<usize>,
+        f: F,
+        guard: &'g G,
+    ) -> ExtractIf<'root, 'g, K, V, S, A, F, G>
+    where
+        F: FnMut(&K, &V) -> bool,
+        G: Guard,
+    {
+        ExtractIf {
+            i: range.start,
+            end: Some(range.end.min(self.table.len())),
+            copying: false,
+            map: self.clone(),
+            f,
+            guard,
+        }
+    }
+
+    // Returns the number of slots in the table, completing any pending resizes first.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn linearized_len(&mut self, guard: &impl Guard) -> usize {
+        // The table has not yet been allocated.
+        if self.table.raw.is_null() {
+            return 0;
+        }
+
+        self.linearize(guard);
+        self.table.len()
+    }
+
+    // Shrink the table to fit at least `min_capacity` elements, or the current
+    // number of elements if it is larger.
+    #[inline]
+    pub fn shrink_to(&mut self, min_capacity: usize, guard: &impl Guard) {
+        // The table has not yet been allocated, there is nothing to shrink.
+        if self.table.raw.is_null() {
+            return;
+        }
+
+        let capacity = self
+            .root
+            .probe
+            .entries_for(self.root.count.sum().max(min_capacity));
+        let capacity = capacity.max(MIN_CAPACITY);
+
+        // The table is already small enough.
+        if capacity >= self.table.len() {
+            return;
+        }
+
+        // Race to allocate the new table.
+        //
+        // Note that if a resize is already in-progress we will help it complete
+        // instead, so the resulting table may be larger than requested.
+        self.get_or_alloc_next(Some(capacity));
+
+        // Force the copy to complete.
+        self.table = self.help_copy(guard, true);
+    }
+
+    // Migrate to a table of the same capacity, leaving behind any tombstones.
+    #[inline]
+    pub fn compact(&mut self, guard: &impl Guard) {
+        // The table has not yet been allocated, or there is nothing to compact.
+        if self.table.raw.is_null() || self.table.state().tombstones.sum() == 0 {
+            return;
+        }
+
+        // Race to allocate the new table.
+        //
+        // Note that if a resize is already in-progress we will help it complete
+        // instead, so the resulting table may have a different capacity.
+        self.get_or_alloc_next(Some(self.table.len()));
+
+        // Force the copy to complete.
+        self.table = self.help_copy(guard, true);
     }
 
     // Returns an iterator over the keys and values of this table.
@@ -949,19 +2636,192 @@ where
         if self.table.raw.is_null() {
             return Iter {
                 i: 0,
+                end: 0,
                 guard,
                 table: self.table,
             };
         }
 
-        // Get a clean copy of the table to iterate over.
-        self.linearize(guard);
+        // Get a clean copy of the table to iterate over.
+        self.linearize(guard);
+
+        Iter {
+            i: 0,
+            end: self.table.len(),
+            guard,
+            table: self.table,
+        }
+    }
+
+    // Returns an iterator over the keys and values of this table, resuming from the given cursor.
+    //
+    // If the table was resized since the cursor was created, iteration restarts from the
+    // beginning of the new table.
+    #[inline]
+    pub fn iter_from<'g, G>(&mut self, cursor: Cursor, guard: &'g G) -> Iter<'g, K, V, G>
+    where
+        G: Guard,
+    {
+        let mut iter = self.iter(guard);
+
+        if iter.generation() == cursor.generation {
+            iter.i = cursor.index.min(iter.end);
+        }
+
+        iter
+    }
+
+    // Returns an iterator over the keys and values of this table and any tables it is
+    // being resized to, without completing any in-progress resizes.
+    #[inline]
+    pub fn iter_incremental<'g, G>(&self, guard: &'g G) -> IncrementalIter<'g, K, V, S, A, G>
+    where
+        'root: 'g,
+        G: Guard,
+    {
+        // Any table after the first is allocated after every table before the first was
+        // copied, unless it already exists.
+        let skip_copies = self.table.raw.is_null() || self.next_table_ref().is_none();
+
+        IncrementalIter {
+            i: 0,
+            first: self.table,
+            skip_copies,
+            map: self.as_ref(self.table),
+            guard,
+        }
+    }
+
+    // Returns `true` if the key has an entry in this table, including entries that
+    // were copied to the next table.
+    #[inline]
+    fn contains_entry(&self, key: &K, guard: &impl Guard) -> bool {
+        // The table has not yet been allocated.
+        if self.table.raw.is_null() {
+            return false;
+        }
+
+        // Initialize the probe state.
+        let (h1, h2) = self.hash(key);
+        let mut probe = Probe::start(h1, self.table.mask);
+
+        // Probe a group at a time until we reach the limit.
+        while probe.len <= self.table.limit {
+            // Load the group metadata first for cheap searches.
+            let group = unsafe { self.table.group(probe.i) };
+
+            // Ignore any entries past the probe limit.
+            let probed = BitMask::first(self.table.limit - probe.len + 1);
+
+            // Check for potential matches.
+            for offset in group.matches(h2).and(probed) {
+                let entry =
+                    unsafe { guard.protect(self.table.entry(probe.i + offset), Ordering::Acquire) }
+                        .unpack();
+
+                // Note that copied entries are never removed from the old table.
+                if !entry.ptr.is_null() && unsafe { (*entry.ptr).key == *key } {
+                    return true;
+                }
+            }
+
+            // The key is not in the table.
+            if group.empty().and(probed).any() {
+                return false;
+            }
+
+            probe.next_group(self.table.mask);
+        }
+
+        false
+    }
+
+    // Returns statistics about the state of this table and any in-progress resizes.
+    //
+    // This reads a fixed set of counters, and estimates the probe length histogram from a
+    // bounded sample of the table.
+    pub fn stats(&self, guard: &impl Guard) -> MapStats {
+        let entry_bytes = self.root.pool.retired() * Entry::<K, V>::layout::<A>().size();
+        let mut stats = MapStats {
+            len: self.root.len(),
+            retired_bytes: self.root.retired.load(Ordering::Relaxed) + entry_bytes,
+            ..MapStats::default()
+        };
+
+        // The table has not yet been allocated.
+        if self.table.raw.is_null() {
+            return stats;
+        }
+
+        let state = self.table.state();
+        stats.capacity = self.table.len();
+        stats.probe_limit = self.table.limit;
+        stats.probe_lengths = self.sample_probe_lengths(guard);
+        stats.resizes = state.grown;
+        stats.shrinks = state.shrunk;
+        stats.compactions = state.generation - state.grown - state.shrunk;
+        stats.tombstones = state.tombstones.sum().min(stats.capacity);
+        stats.load_factor = stats.len.min(stats.capacity) as f64 / stats.capacity as f64;
+
+        // Check for any in-progress resizes.
+        if let Some(next) = self.next_table_ref() {
+            let state = next.table.state();
+            stats.copied = state.copied.load(Ordering::Relaxed).min(stats.capacity);
+            stats.claimed = state.claim.load(Ordering::Relaxed).min(stats.capacity);
+
+            let mut next = Some(next);
+            while let Some(table) = next {
+                stats.pending_tables += 1;
+                next = table.next_table_ref();
+            }
+        }
+
+        stats
+    }
+
+    // Returns a histogram of probe lengths for the entries in this table.
+    //
+    // At most `PROBE_SAMPLE` slots, one in each evenly sized stride of the table, are visited,
+    // and the counts are scaled to the capacity of the table. This makes the histogram exact
+    // for small tables, and an estimate for larger ones.
+    fn sample_probe_lengths(&self, guard: &impl Guard) -> Vec<usize> {
+        const PROBE_SAMPLE: usize = 1024;
+
+        // Table lengths are powers of two, so the strides divide the table evenly.
+        let step = (self.table.len() / PROBE_SAMPLE).max(1);
+
+        let mut lengths = vec![0; self.table.limit + 1];
+        for stride in 0..self.table.len() / step {
+            // Groups are filled from the front, so the sampled slot is staggered across strides
+            // to sample every position within a group equally.
+            let i = stride * step + stride % step;
+            let meta = unsafe { self.table.meta(i) }.load(Ordering::Acquire);
+
+            if meta == meta::EMPTY || meta == meta::TOMBSTONE {
+                continue;
+            }
+
+            let entry = unsafe { guard.protect(self.table.entry(i), Ordering::Acquire) }.unpack();
+
+            // The entry was deleted, but the metadata has not yet been updated.
+            if entry.ptr.is_null() {
+                continue;
+            }
+
+            // Find the position of the entry in its probe sequence.
+            let (h1, _) = self.hash(unsafe { &(*entry.ptr).key });
+            let mut probe = Probe::start(h1, self.table.mask);
+            while probe.len <= self.table.limit {
+                if probe.i == i {
+                    lengths[probe.len] += step;
+                    break;
+                }
 
-        Iter {
-            i: 0,
-            guard,
-            table: self.table,
+                probe.next(self.table.mask);
+            }
         }
+
+        lengths
     }
 
     // Returns the h1 and h2 hash for the given key.
@@ -1035,7 +2895,7 @@ where
 }
 
 // Update operations.
-impl<'root, K, V, S> HashMapRef<'root, K, V, S>
+impl<'root, K, V, S, A: Allocator> HashMapRef<'root, K, V, S, A>
 where
     K: Hash + Eq,
     S: BuildHasher,
@@ -1072,25 +2932,84 @@ where
         compute: F,
         guard: &'g impl Guard,
     ) -> Compute<'g, K, V, T>
+    where
+        F: FnMut(Option<(&'g K, &'g V)>) -> Operation<V, T>,
+    {
+        self.compute_entry(key, ComputeState::new(compute), guard)
+    }
+
+    // Update an entry with a CAS function, looking up the key by reference.
+    //
+    // The key is only converted to an owned key if a new entry must be written to the map,
+    // meaning the operation was not aborted.
+    #[inline]
+    pub fn compute_ref<'g, Q, C, F, T>(
+        &mut self,
+        key: &Q,
+        to_owned: C,
+        compute: F,
+        guard: &'g impl Guard,
+    ) -> Compute<'g, K, V, T>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+        C: FnOnce(&Q) -> K,
+        F: FnMut(Option<(&'g K, &'g V)>) -> Operation<V, T>,
+    {
+        let mut state = ComputeState::new(compute);
+
+        // Compute the operation for the current entry.
+        let hash = self.root.hasher.hash_one(key);
+        let entry = self
+            .find_hashed(hash, &mut |k| key.equivalent(k), guard)
+            .map(|slot| slot.entry.ptr);
+
+        match state.next(entry) {
+            // The operation was aborted, we don't need to convert the key.
+            Operation::Abort(value) => return Compute::Aborted(value),
+            Operation::Remove if entry.is_none() => panic!("Cannot remove `None` entry."),
+
+            // Save the operation, it will be reused if the entry is unchanged.
+            op => state.restore(entry, op),
+        }
+
+        self.compute_entry(to_owned(key), state, guard)
+    }
+
+    // Update an entry with the given compute state.
+    #[inline]
+    fn compute_entry<'g, F, T>(
+        &mut self,
+        key: K,
+        state: ComputeState<F, K, V, T>,
+        guard: &'g impl Guard,
+    ) -> Compute<'g, K, V, T>
     where
         F: FnMut(Option<(&'g K, &'g V)>) -> Operation<V, T>,
     {
         // Initialize the entry we will be inserting.
-        let entry = Box::into_raw(Box::new(Entry {
+        let entry = self.root.alloc_entry(Entry {
             key,
             link: self.root.collector.link(),
             value: MaybeUninit::uninit(),
-        }));
+        });
 
         // Perform the update.
         //
         // Safety: We just allocated the entry above.
-        let result = unsafe { self.compute_with(entry, ComputeState::new(compute), true, guard) };
+        let result = unsafe { self.compute_with(entry, state, true, guard) };
+
+        // Notify any watchers if the entry was changed.
+        match result {
+            Compute::Inserted(key, _)
+            | Compute::Updated { new: (key, _), .. }
+            | Compute::Removed(key, _) => self.root.notify(key),
+            Compute::Aborted(_) => {}
+        }
 
         // Deallocate the entry if it was not inserted.
         if matches!(result, Compute::Removed(..) | Compute::Aborted(_)) {
-            // Safety: We allocated this box above and it was not inserted into the map.
-            let _ = unsafe { Box::from_raw(entry) };
+            // Safety: We allocated this entry above and it was not inserted into the map.
+            unsafe { Entry::dealloc::<A>(entry) };
         }
 
         result
@@ -1132,6 +3051,11 @@ where
         let (h1, h2) = self.hash(key);
         let mut probe = Probe::start(h1, self.table.mask);
 
+        // Whether we must reserve an entry before claiming it, either to reuse a tombstone
+        // we probed past or because an empty entry we probed past may have been sealed.
+        let mut reuse = false;
+        let mut backoff = Backoff::default();
+
         // Probe until we reach the limit.
         let copying = 'probe: loop {
             if probe.len > self.table.limit {
@@ -1153,7 +3077,19 @@ where
                 unsafe { (*new_entry).value = MaybeUninit::new(value) }
 
                 // Attempt to insert.
-                match self.insert_at(probe.i, h2, new_entry.cast(), guard) {
+                let status = if reuse {
+                    self.insert_reuse(probe.i, probe.len, h1, h2, new_entry.cast(), guard)
+                } else {
+                    self.insert_at(probe.i, h2, new_entry.cast(), guard)
+                };
+
+                // The entry may have been sealed and claimed after we loaded its metadata,
+                // so any later inserts must go through a reservation.
+                if let InsertStatus::Found(_) = status {
+                    reuse = true;
+                }
+
+                match status {
                     // Successfully inserted.
                     InsertStatus::Inserted => {
                         // Increment the table length.
@@ -1186,6 +3122,29 @@ where
                         probe.next(self.table.mask);
                         continue 'probe;
                     }
+
+                    // Our reservation conflicted with a concurrent insert.
+                    InsertStatus::Retry => {
+                        // Save the previous value.
+                        let value = unsafe { (*new_entry).value.assume_init_read() };
+                        state.restore(None, Operation::Insert(value));
+
+                        // Restart the probe.
+                        backoff.spin();
+                        probe = Probe::start(h1, self.table.mask);
+                        reuse = false;
+                        continue 'probe;
+                    }
+
+                    // The entry must be reserved before we can claim it.
+                    InsertStatus::Sealed => {
+                        // Save the previous value.
+                        let value = unsafe { (*new_entry).value.assume_init_read() };
+                        state.restore(None, Operation::Insert(value));
+
+                        reuse = true;
+                        continue 'probe;
+                    }
                 }
             }
             // Found a potential match.
@@ -1206,6 +3165,7 @@ where
             }
             // Otherwise, continue probing.
             else {
+                reuse |= meta == meta::TOMBSTONE;
                 probe.next(self.table.mask);
                 continue 'probe;
             };
@@ -1258,20 +3218,7 @@ where
                         match unsafe { self.update_at(probe.i, entry, Entry::TOMBSTONE, guard) } {
                             // Successfully removed the entry.
                             UpdateStatus::Replaced(entry) => {
-                                // Mark the entry as a tombstone.
-                                //
-                                // Note that this might end up being overwritten by the metadata hash
-                                // if the initial insertion is lagging behind, but we avoid the RMW
-                                // and sacrifice reads in the extremely rare case.
-                                unsafe {
-                                    self.table
-                                        .meta(probe.i)
-                                        .store(meta::TOMBSTONE, Ordering::Release)
-                                };
-
-                                // Decrement the table length.
-                                let count = self.root.count.get(guard.thread_id());
-                                count.fetch_sub(1, Ordering::Relaxed);
+                                self.removed_at(probe.i, guard);
 
                                 let entry = unsafe { &(*entry.ptr) };
                                 return Compute::Removed(&entry.key, &entry.value);
@@ -1353,6 +3300,11 @@ where
                     // so blocking allows us to make copies faster.
                     self.wait_copied(i);
 
+                    // Abort any reservations we probed past before continuing in the new table.
+                    if !self.abort_reservations(h1, h2, guard) {
+                        return self.compute_with(new_entry, state, false, guard);
+                    }
+
                     // Continue in the new table.
                     return next_table.compute_with(new_entry, state, false, guard);
                 }
@@ -1365,6 +3317,11 @@ where
                         self.help_copy(guard, false);
                     }
 
+                    // Abort any reservations we probed past before continuing in the new table.
+                    if !self.abort_reservations(h1, h2, guard) {
+                        return self.compute_with(new_entry, state, false, guard);
+                    }
+
                     // Continue in the new table.
                     return next_table.compute_with(new_entry, state, false, guard);
                 }
@@ -1377,11 +3334,16 @@ where
             op @ Operation::Insert(_) => {
                 // Trigger a resize.
                 self.get_or_alloc_next(None);
+                state.restore(None, op);
+
+                // Abort any reservations we probed past before continuing in the new table.
+                if self.root.is_incremental() && !self.abort_reservations(h1, h2, guard) {
+                    return self.compute_with(new_entry, state, false, guard);
+                }
 
                 // Help out with the resize.
                 let next_table = self.help_copy(guard, false);
 
-                state.restore(None, op);
                 return self
                     .as_ref(next_table)
                     .compute_with(new_entry, state, false, guard);
@@ -1393,14 +3355,14 @@ where
 }
 
 // Resize operations.
-impl<'root, K, V, S> HashMapRef<'root, K, V, S>
+impl<'root, K, V, S, A: Allocator> HashMapRef<'root, K, V, S, A>
 where
     K: Hash + Eq,
     S: BuildHasher,
 {
     // Returns a reference to the given table.
     #[inline]
-    fn as_ref(&self, table: Table<K, V>) -> HashMapRef<'root, K, V, S> {
+    fn as_ref(&self, table: Table<K, V>) -> HashMapRef<'root, K, V, S, A> {
         HashMapRef {
             table,
             root: self.root,
@@ -1409,7 +3371,7 @@ where
 
     // Returns a reference to the next table, if it has already been created.
     #[inline]
-    fn next_table_ref(&self) -> Option<HashMapRef<'root, K, V, S>> {
+    fn next_table_ref(&self) -> Option<HashMapRef<'root, K, V, S, A>> {
         let state = self.table.state();
         let next = state.next.load(Ordering::Acquire);
 
@@ -1423,11 +3385,15 @@ where
     // Allocate the initial table.
     #[cold]
     fn init(&mut self, capacity: Option<usize>) -> bool {
-        const CAPACITY: usize = 32;
-
         // Allocate the table and mark it as the root.
-        let mut table = Table::<K, V>::alloc(capacity.unwrap_or(CAPACITY), &self.root.collector);
+        let mut table = Table::<K, V>::alloc(
+            capacity.unwrap_or(MIN_CAPACITY),
+            &self.root.probe,
+            &self.root.collector,
+            &*self.root.alloc,
+        );
         *table.state_mut().status.get_mut() = State::PROMOTED;
+        table.state_mut().retired = &*self.root.retired;
 
         // Race to write the initial table.
         match self.root.table.compare_exchange(
@@ -1444,7 +3410,7 @@ where
 
             // Someone beat us, deallocate our table and use the table that was written.
             Err(found) => {
-                unsafe { Table::dealloc(table) }
+                unsafe { Table::dealloc::<A>(table) }
                 self.table = unsafe { Table::from_raw(found) };
                 false
             }
@@ -1502,17 +3468,29 @@ where
         let next_capacity = match cfg!(papaya_stress) {
             // Never grow the table to stress the incremental resizing algorithm.
             true => self.table.len(),
-            // Double the table capacity if we are at least 50% full.
+            // Double the table capacity if we are close enough to the load factor, by
+            // default at least 50% full.
             //
-            // Loading the length here is quite expensive, we may want to consider
-            // a probabilistic counter to detect high-deletion workloads.
-            false if self.root.len() >= (self.table.len() >> 1) => self.table.len() << 1,
-            // Otherwise keep the capacity the same.
+            // Loading the length here is quite expensive, but high-deletion workloads
+            // can avoid reaching the probe limit through `CompactMode`.
+            false if self.root.len() >= self.root.probe.grow_threshold(self.table.len()) => {
+                self.table.len() << 1
+            }
+            // Otherwise keep the capacity the same with the default configuration.
             //
             // This can occur due to poor hash distribution or frequent cycling of
             // insertions and deletions, in which case we want to avoid continuously
             // growing the table.
-            false => self.table.len(),
+            false if self.root.probe.is_default() => self.table.len(),
+            // Keep the capacity the same if there are tombstones to clear.
+            //
+            // This can occur due to frequent cycling of insertions and deletions, in which
+            // case we want to avoid continuously growing the table.
+            false if self.table.state().tombstones.sum() > 0 => self.table.len(),
+            // Otherwise, the probe limit was reached due to a configured load factor or
+            // maximum probe length, and a table of the same capacity would not fit the
+            // entries either.
+            false => self.table.len() << 1,
         };
 
         let next_capacity = capacity.unwrap_or(next_capacity);
@@ -1522,13 +3500,148 @@ where
         );
 
         // Allocate the new table while holding the lock.
-        let next = Table::alloc(next_capacity, &self.root.collector);
+        let mut next = Table::alloc(
+            next_capacity,
+            &self.root.probe,
+            &self.root.collector,
+            &*self.root.alloc,
+        );
+        next.state_mut().generation = state.generation.wrapping_add(1);
+        next.state_mut().grown = state.grown + usize::from(next_capacity > self.table.len());
+        next.state_mut().shrunk = state.shrunk + usize::from(next_capacity < self.table.len());
+        next.state_mut().retired = state.retired;
         state.next.store(next.raw, Ordering::Release);
         drop(_allocating);
 
         next
     }
 
+    // Shrink the root table if the number of entries was sampled below the shrink threshold.
+    //
+    // Loading the length is expensive, so we only check it once every `SHRINK_SAMPLE`
+    // removals from a given counter shard, using the shard's previous number of tombstones.
+    // The shard's share of the length is not used for sampling, as entries may be spread
+    // unevenly across shards. The decision itself is made on the summed length.
+    #[inline]
+    fn maybe_shrink(&self, tombstones: isize, guard: &impl Guard) {
+        const SHRINK_SAMPLE: isize = 64;
+
+        if tombstones % SHRINK_SAMPLE == 0 {
+            self.check_shrink(guard);
+        }
+    }
+
+    // Record a deleted entry in this table, returning the previous count of the counter shard.
+    #[inline]
+    fn add_tombstone(&self, guard: &impl Guard) -> isize {
+        let tombstones = self.table.state().tombstones.get(guard.thread_id());
+        tombstones.fetch_add(1, Ordering::Relaxed)
+    }
+
+    // Compact the root table if the number of tombstones was sampled above the compaction
+    // threshold.
+    //
+    // Loading the number of tombstones is expensive, so we only check it once every
+    // `COMPACT_SAMPLE` removals from a given counter shard, using the shard's previous count.
+    #[inline]
+    fn maybe_compact(&self, tombstones: isize, guard: &impl Guard) {
+        const COMPACT_SAMPLE: isize = 64;
+
+        if tombstones % COMPACT_SAMPLE == 0 {
+            self.check_compact(guard);
+        }
+    }
+
+    // Compact the root table if it has risen above the compaction threshold.
+    #[inline]
+    pub fn check_compact(&self, guard: &impl Guard) {
+        if let CompactMode::Threshold(threshold) = self.root.compact {
+            self.root.root(guard).compact_above(threshold, guard);
+        }
+    }
+
+    // Migrate to a table of the same capacity if the ratio of tombstones in the table is above
+    // the given threshold.
+    //
+    // Unlike `compact`, this respects the resize mode and only copies a single chunk
+    // in incremental resize mode.
+    #[cold]
+    fn compact_above(&self, threshold: f64, guard: &impl Guard) {
+        // The table has not yet been allocated.
+        if self.table.raw.is_null() {
+            return;
+        }
+
+        // A resize is already in-progress, help it along.
+        if self.next_table_ref().is_some() {
+            self.help_copy(guard, false);
+            return;
+        }
+
+        let tombstones = self.table.state().tombstones.sum();
+
+        // We are below the threshold.
+        if (tombstones as f64) < self.table.len() as f64 * threshold {
+            return;
+        }
+
+        // Race to allocate the new table.
+        self.get_or_alloc_next(Some(self.table.len()));
+
+        // Help out with the copy.
+        //
+        // In blocking mode this completes the resize, while in incremental mode the
+        // copy will be completed by future writers.
+        self.help_copy(guard, false);
+    }
+
+    // Shrink the root table if it has fallen below the shrink threshold.
+    #[inline]
+    pub fn check_shrink(&self, guard: &impl Guard) {
+        if let ShrinkMode::Threshold(threshold) = self.root.shrink {
+            self.root.root(guard).shrink_below(threshold, guard);
+        }
+    }
+
+    // Migrate to a smaller table if the load factor of the table is below the given threshold.
+    //
+    // Unlike `shrink_to`, this respects the resize mode and only copies a single chunk
+    // in incremental resize mode.
+    #[cold]
+    fn shrink_below(&self, threshold: f64, guard: &impl Guard) {
+        // The table has not yet been allocated, or cannot be shrunk any further.
+        if self.table.raw.is_null() || self.table.len() <= MIN_CAPACITY {
+            return;
+        }
+
+        // A resize is already in-progress, help it along.
+        if self.next_table_ref().is_some() {
+            self.help_copy(guard, false);
+            return;
+        }
+
+        let len = self.root.len();
+
+        // We are above the threshold.
+        if len as f64 >= self.table.len() as f64 * threshold {
+            return;
+        }
+
+        let capacity = self.root.probe.entries_for(len).max(MIN_CAPACITY);
+        if capacity >= self.table.len() {
+            return;
+        }
+
+        // Race to allocate the new table.
+        self.get_or_alloc_next(Some(capacity));
+
+        // Help out with the copy.
+        //
+        // In blocking mode this completes the resize, while in incremental mode the
+        // copy will be completed by future writers.
+        self.help_copy(guard, false);
+    }
+
     // Help along with an existing resize operation, returning the new root table.
     //
     // If `copy_all` is `false` in incremental resize mode, this returns the current reference's next table,
@@ -1851,29 +3964,57 @@ where
                 let entry = unsafe { self.table.entry(probe.i) };
 
                 // Try to claim the entry.
-                match entry.compare_exchange(
-                    ptr::null_mut(),
-                    new_entry.raw,
-                    Ordering::Release,
-                    Ordering::Acquire,
-                ) {
+                let mut current = ptr::null_mut();
+                let result = loop {
+                    match entry.compare_exchange(
+                        current,
+                        new_entry.raw,
+                        Ordering::Release,
+                        Ordering::Acquire,
+                    ) {
+                        // The entry was sealed by an insert. We are the only writer inserting
+                        // this key, so there is no need to reserve it. However, inserts of the
+                        // key the seal was validated for could probe past our entry, so the
+                        // seal is moved to the next empty entry first.
+                        Err(found) if found == Entry::SEALED && current.is_null() => {
+                            self.forward_seal(h1, probe.len);
+                            current = found
+                        }
+
+                        result => break result,
+                    }
+                };
+
+                match result {
                     // Successfully inserted.
                     Ok(_) => {
                         // Update the metadata table.
-                        unsafe { self.table.meta(probe.i).store(h2, Ordering::Release) };
+                        unsafe { self.init_meta(probe.i, h2) };
                         return Some((self.table, probe.i));
                     }
+
+                    // The entry is reserved by an insert.
+                    Err(found) if found == Entry::RESERVED => {
+                        // The reservation was published or aborted, check the entry again.
+                        if !self.skip_reserved(probe.i, h2, guard) {
+                            continue;
+                        }
+                    }
+
                     Err(found) => {
                         // The entry was deleted or copied.
+                        //
+                        // Note that only the thread that deleted an entry may mark it as a
+                        // tombstone, so we use our own metadata instead.
                         let meta = if found.unpack().ptr.is_null() {
-                            meta::TOMBSTONE
+                            h2
                         } else {
                             // Protect the entry before accessing it.
                             let found = guard.protect(entry, Ordering::Acquire).unpack();
 
                             // Recheck the pointer.
                             if found.ptr.is_null() {
-                                meta::TOMBSTONE
+                                h2
                             } else {
                                 // Ensure the meta table is updated to avoid breaking the probe chain.
                                 let hash = self.root.hasher.hash_one(&(*found.ptr).key);
@@ -1881,9 +4022,7 @@ where
                             }
                         };
 
-                        if self.table.meta(probe.i).load(Ordering::Relaxed) == meta::EMPTY {
-                            self.table.meta(probe.i).store(meta, Ordering::Release);
-                        }
+                        self.init_meta(probe.i, meta);
                     }
                 }
             }
@@ -1902,6 +4041,36 @@ where
             .insert_copy(new_entry, resize, guard)
     }
 
+    // Seals the first empty entry in the probe sequence after the sealed entry found after
+    // probing `len` entries, before it is claimed.
+    #[inline]
+    fn forward_seal(&self, h1: usize, len: usize) {
+        let mut probe = Probe::start(h1, self.table.mask);
+        while probe.len <= self.table.limit {
+            if probe.len > len {
+                let entry = unsafe { self.table.entry(probe.i) };
+
+                match entry.compare_exchange(
+                    ptr::null_mut(),
+                    Entry::SEALED,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    // Sealed the empty entry.
+                    Ok(_) => return,
+
+                    // The entry is already sealed.
+                    Err(found) if found == Entry::SEALED => return,
+
+                    // The entry is not empty, keep probing.
+                    Err(_) => {}
+                }
+            }
+
+            probe.next(self.table.mask);
+        }
+    }
+
     // Update the copy state and attempt to promote a table to the root.
     //
     // Returns `true` if the table was promoted.
@@ -1945,10 +4114,14 @@ where
                         //
                         // Note that we do not drop entries because they have been copied to the
                         // new root.
+                        self.root
+                            .retired
+                            .fetch_add(self.table.size(), Ordering::Relaxed);
                         guard.defer_retire(self.table.raw, |link| {
                             let raw: *mut RawTable = link.cast();
                             let table = Table::<K, V>::from_raw(raw);
-                            drop_table::<K, V>(table);
+                            (*table.state().retired).fetch_sub(table.size(), Ordering::Relaxed);
+                            drop_table::<K, V, A>(table);
                         });
                     }
                 }
@@ -2018,18 +4191,23 @@ where
     /// The entry must be unreachable from the current table.
     #[inline]
     unsafe fn defer_retire(&self, entry: Tagged<Entry<K, V>>, guard: &impl Guard) {
+        let reclaim = Entry::reclaim::<K, V, A>;
+
+        // The entry is reclaimed once it is inaccessible from any table.
+        self.root.pool.retire();
+
         match self.root.resize {
             // Safety: In blocking resize mode, we only ever write to the root table, so the entry
             // is inaccessible from all tables.
             ResizeMode::Blocking => unsafe {
-                guard.defer_retire(entry.ptr, Entry::reclaim::<K, V>);
+                guard.defer_retire(entry.ptr, reclaim);
             },
             // In incremental resize mode, the entry may be accessible in previous tables.
             ResizeMode::Incremental(_) => {
                 if entry.tag() & Entry::BORROWED == 0 {
                     // Safety: If the entry is not borrowed, meaning it is not in any previous tables,
                     // it is inaccessible even if we are not the root. Thus we can safely retire.
-                    unsafe { guard.defer_retire(entry.ptr, Entry::reclaim::<K, V>) };
+                    unsafe { guard.defer_retire(entry.ptr, reclaim) };
                     return;
                 }
 
@@ -2041,29 +4219,169 @@ where
                     if map.table.raw == root {
                         // Safety: The root table is our table or a table that succeeds ours.
                         // Thus any previous tables are unreachable and we can safely retire.
-                        unsafe { guard.defer_retire(entry.ptr, Entry::reclaim::<K, V>) };
+                        unsafe { guard.defer_retire(entry.ptr, reclaim) };
+                        return;
+                    }
+
+                    next = map.next_table_ref();
+                }
+
+                // Otherwise, we have to wait for the table we are copying from to be reclaimed.
+                //
+                // Find the table we are copying from, searching from the root.
+                fence(Ordering::Acquire);
+                let mut prev = self.as_ref(unsafe { Table::<K, V>::from_raw(root) });
+
+                loop {
+                    let next = prev.next_table_ref().unwrap();
+
+                    // Defer the entry to be retired by the table we are copying from.
+                    if next.table.raw == self.table.raw {
+                        prev.table.state().deferred.defer(entry.ptr);
                         return;
                     }
 
-                    next = map.next_table_ref();
-                }
+                    prev = next;
+                }
+            }
+        }
+    }
+}
+
+// An iterator that removes entries matching a predicate from this table.
+pub struct ExtractIf<'root, 'g, K, V, S, A: Allocator, F, G> {
+    i: usize,
+    // The end of the slot range to visit, or `None` to visit every entry, following
+    // any resizes.
+    end: Option<usize>,
+    // Whether we skipped any entries that were being copied.
+    copying: bool,
+    map: HashMapRef<'root, K, V, S, A>,
+    f: F,
+    guard: &'g G,
+}
+
+impl<K, V, S, A: Allocator, F, G> ExtractIf<'_, '_, K, V, S, A, F, G> {
+    // Returns `true` if no entries were skipped due to a concurrent resize.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        !self.copying
+    }
+}
+
+impl<'g, K: 'g, V: 'g, S, A: Allocator, F, G> Iterator for ExtractIf<'_, 'g, K, V, S, A, F, G>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    F: FnMut(&K, &V) -> bool,
+    G: Guard,
+{
+    type Item = (&'g K, &'g V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // The table has not yet been allocated.
+        if self.map.table.raw.is_null() {
+            return None;
+        }
+
+        'probe: loop {
+            // Visited every entry in the given range.
+            if let Some(end) = self.end {
+                if self.i >= end {
+                    return None;
+                }
+            }
+            // Visited every entry in the table.
+            else if self.i >= self.map.table.len() {
+                // A resize prevented us from visiting all the entries in this table.
+                //
+                // Complete the resize and continue in the new table.
+                if self.copying {
+                    self.map.table = self.map.help_copy(self.guard, true);
+                    self.copying = false;
+                    self.i = 0;
+                    continue 'probe;
+                }
+
+                // The table may be mostly empty now, shrink it if necessary.
+                self.map.check_shrink(self.guard);
+                return None;
+            }
+
+            let i = self.i;
+            self.i += 1;
+
+            // Load the entry to test.
+            let mut entry = unsafe {
+                self.guard
+                    .protect(self.map.table.entry(i), Ordering::Acquire)
+                    .unpack()
+            };
+
+            loop {
+                // Found a non-empty entry being copied.
+                if entry.tag() & Entry::COPYING != 0 && !entry.ptr.is_null() {
+                    // Visit every entry in this table that we can, then deal with the copy.
+                    self.copying = true;
+                    continue 'probe;
+                }
+
+                // The entry is empty or already deleted.
+                if entry.ptr.is_null() {
+                    continue 'probe;
+                }
+
+                // The entry does not match the predicate.
+                let entry_ref = unsafe { &*entry.ptr };
+                if !(self.f)(&entry_ref.key, &entry_ref.value) {
+                    continue 'probe;
+                }
+
+                // Try to delete the entry, only if it has not changed since we tested it.
+                //
+                // Note that this must be `SeqCst` to synchronize with any watchers.
+                let result = unsafe {
+                    self.map.table.entry(i).compare_exchange(
+                        entry.raw,
+                        Entry::TOMBSTONE,
+                        Ordering::SeqCst,
+                        Ordering::Acquire,
+                    )
+                };
+
+                match result {
+                    // Successfully deleted the entry.
+                    Ok(_) => unsafe {
+                        // Update the metadata table.
+                        self.map
+                            .table
+                            .meta(i)
+                            .store(meta::TOMBSTONE, Ordering::Release);
+                        self.map.add_tombstone(self.guard);
 
-                // Otherwise, we have to wait for the table we are copying from to be reclaimed.
-                //
-                // Find the table we are copying from, searching from the root.
-                fence(Ordering::Acquire);
-                let mut prev = self.as_ref(unsafe { Table::<K, V>::from_raw(root) });
+                        // Decrement the table length.
+                        let count = self.map.root.count.get(self.guard.thread_id());
+                        count.fetch_sub(1, Ordering::Relaxed);
 
-                loop {
-                    let next = prev.next_table_ref().unwrap();
+                        // Notify any watchers of the removal.
+                        self.map.root.notify(&(*entry.ptr).key);
 
-                    // Defer the entry to be retired by the table we are copying from.
-                    if next.table.raw == self.table.raw {
-                        prev.table.state().deferred.defer(entry.ptr);
-                        return;
-                    }
+                        // Safety: We just removed the old value from this table.
+                        self.map.defer_retire(entry, self.guard);
 
-                    prev = next;
+                        return Some((&(*entry.ptr).key, &(*entry.ptr).value));
+                    },
+
+                    // Lost to a concurrent update, retry with the new entry.
+                    Err(_) => {
+                        entry = unsafe {
+                            self.guard
+                                .protect(self.map.table.entry(i), Ordering::Acquire)
+                                .unpack()
+                        };
+                    }
                 }
             }
         }
@@ -2073,10 +4391,84 @@ where
 // An iterator over the keys and values of this table.
 pub struct Iter<'g, K, V, G> {
     i: usize,
+    // The end of the slot range to iterate over.
+    end: usize,
     table: Table<K, V>,
     guard: &'g G,
 }
 
+// A position in the slots of a given table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Cursor {
+    // The generation of the table.
+    generation: usize,
+    // The index of the next slot to visit.
+    index: usize,
+}
+
+impl<K, V, G> Iter<'_, K, V, G> {
+    // The minimum number of slots in a split iterator.
+    const MIN_SPLIT: usize = 64;
+
+    // Returns the position of the next slot this iterator will visit.
+    #[inline]
+    pub fn cursor(&self) -> Cursor {
+        Cursor {
+            generation: self.generation(),
+            index: self.i,
+        }
+    }
+
+    // Returns the generation of the table being iterated over.
+    #[inline]
+    fn generation(&self) -> usize {
+        if self.table.raw.is_null() {
+            return 0;
+        }
+
+        self.table.state().generation
+    }
+
+    // Returns an iterator over the given shard of the remaining slot range of this iterator.
+    //
+    // The range is split into `count` contiguous shards, as evenly as possible.
+    #[inline]
+    pub fn shard(&self, index: usize, count: usize) -> Self {
+        let len = self.end.saturating_sub(self.i);
+        let start = self.i + len / count * index + (len % count).min(index);
+
+        Iter {
+            i: start,
+            end: start + len / count + usize::from(index < len % count),
+            table: self.table,
+            guard: self.guard,
+        }
+    }
+
+    // Splits the remaining slot range of this iterator in half, returning an iterator
+    // over the second half.
+    //
+    // Returns `None` if the remaining range is too small to split.
+    #[inline]
+    pub fn split(&mut self) -> Option<Self> {
+        let remaining = self.end.saturating_sub(self.i);
+        if remaining < Self::MIN_SPLIT * 2 {
+            return None;
+        }
+
+        let mid = self.i + remaining / 2;
+        let split = Iter {
+            i: mid,
+            end: self.end,
+            table: self.table,
+            guard: self.guard,
+        };
+
+        self.end = mid;
+        Some(split)
+    }
+}
+
 impl<'g, K: 'g, V: 'g, G> Iterator for Iter<'g, K, V, G>
 where
     G: Guard,
@@ -2092,7 +4484,7 @@ where
 
         loop {
             // Iterated over every entry in the table, we're done.
-            if self.i >= self.table.len() {
+            if self.i >= self.end {
                 return None;
             }
 
@@ -2146,18 +4538,355 @@ where
 {
 }
 
+// An iterator over the keys and values of a table and any tables it is being resized to.
+//
+// Tables are iterated over in order. Entries are only returned from the first table that
+// they appear in, as copied entries are never removed from the old table. If an entry has
+// been copied, the latest value is read from the next table. Copies are recognized by their
+// `BORROWED` tag, so only entries written directly to a newer table have to be checked against
+// the previous tables.
+//
+// Note that a copy skips over a table that is too full to hold it. If the first table was
+// already being resized when the iterator was created, copies from tables before it may be
+// in newer tables without appearing in the first table, so every entry is checked instead.
+pub struct IncrementalIter<'g, K, V, S, A: Allocator, G> {
+    i: usize,
+    // The table the iterator started from.
+    first: Table<K, V>,
+    // Whether copies in newer tables are guaranteed to appear in a table before them.
+    skip_copies: bool,
+    // The table currently being iterated over.
+    map: HashMapRef<'g, K, V, S, A>,
+    guard: &'g G,
+}
+
+impl<'g, K, V, S, A: Allocator, G> IncrementalIter<'g, K, V, S, A, G>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: Guard,
+{
+    // Returns `true` if the key has an entry in any of the tables preceding the current table.
+    #[inline]
+    fn in_previous_table(&self, key: &K) -> bool {
+        let mut table = self.map.as_ref(self.first);
+
+        while table.table.raw != self.map.table.raw {
+            if table.contains_entry(key, self.guard) {
+                return true;
+            }
+
+            table = table.next_table_ref().unwrap();
+        }
+
+        false
+    }
+}
+
+impl<'g, K: 'g, V: 'g, S, A: Allocator, G> Iterator for IncrementalIter<'g, K, V, S, A, G>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: Guard,
+{
+    type Item = (&'g K, &'g V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // The table has not yet been allocated.
+        if self.map.table.raw.is_null() {
+            return None;
+        }
+
+        // In blocking mode, the root table is the source of truth for readers.
+        let incremental = self.map.root.is_incremental();
+
+        loop {
+            // Iterated over every entry in the table.
+            if self.i >= self.map.table.len() {
+                if !incremental {
+                    return None;
+                }
+
+                // Continue to the next table, if there is one.
+                self.map = self.map.next_table_ref()?;
+                self.i = 0;
+                continue;
+            }
+
+            let i = self.i;
+            self.i += 1;
+
+            // Load the entry metadata first to ensure consistency with calls to `get`.
+            let meta = unsafe { self.map.table.meta(i) }.load(Ordering::Acquire);
+
+            // The entry is empty or deleted.
+            if matches!(meta, meta::EMPTY | meta::TOMBSTONE) {
+                continue;
+            }
+
+            // Load the entry.
+            let entry = unsafe {
+                self.guard
+                    .protect(self.map.table.entry(i), Ordering::Acquire)
+                    .unpack()
+            };
+
+            // The entry was deleted.
+            if entry.ptr.is_null() {
+                continue;
+            }
+
+            let (key, value) = unsafe { (&(*entry.ptr).key, &(*entry.ptr).value) };
+
+            if !incremental {
+                return Some((key, value));
+            }
+
+            if self.map.table.raw != self.first.raw {
+                // The entry was copied from a previous table, which still holds it, so it was
+                // already returned from there.
+                if self.skip_copies && entry.tag() & Entry::BORROWED != 0 {
+                    continue;
+                }
+
+                // The entry may have replaced an entry that was copied, and already returned,
+                // from a previous table.
+                if self.in_previous_table(key) {
+                    continue;
+                }
+            }
+
+            // The entry is being copied to the next table, which holds the latest value.
+            if entry.tag() & Entry::COPYING != 0 {
+                let next = self.map.next_table_ref().unwrap();
+                if let Some(entry) = next.get(key, self.guard) {
+                    return Some(entry);
+                }
+
+                // The entry was removed after it was copied.
+                if entry.tag() & Entry::COPIED != 0 {
+                    continue;
+                }
+            }
+
+            return Some((key, value));
+        }
+    }
+}
+
+// Safety: An iterator holds a shared reference to the HashMap
+// and Guard, and outputs shared references to keys and values.
+// Thus everything must be Sync for the iterator to be Send/Sync.
+unsafe impl<K, V, S, A: Allocator, G> Send for IncrementalIter<'_, K, V, S, A, G>
+where
+    K: Sync,
+    V: Sync,
+    S: Sync,
+    G: Sync,
+{
+}
+
+unsafe impl<K, V, S, A: Allocator, G> Sync for IncrementalIter<'_, K, V, S, A, G>
+where
+    K: Sync,
+    V: Sync,
+    S: Sync,
+    G: Sync,
+{
+}
+
+// An iterator over mutable references to the values of a table.
+pub struct IterMut<'a, K, V> {
+    i: usize,
+    table: Table<K, V>,
+    _map: PhantomData<&'a mut (K, V)>,
+}
+
+impl<'a, K: 'a, V: 'a> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // The table has not yet been allocated.
+        if self.table.raw.is_null() {
+            return None;
+        }
+
+        while self.i < self.table.len() {
+            // Safety: We have unique access to the table.
+            let entry = unsafe { (*self.table.entry(self.i).as_ptr()).unpack() };
+            self.i += 1;
+
+            // The entry is empty or deleted.
+            if entry.ptr.is_null() {
+                continue;
+            }
+
+            // Safety: Every entry is visited at most once.
+            return Some(unsafe { (&(*entry.ptr).key, &mut (*entry.ptr).value) });
+        }
+
+        // Iterated over every entry in the table, we're done.
+        None
+    }
+}
+
+// Safety: The iterator holds a unique reference to the HashMap, and outputs
+// shared references to keys and unique references to values.
+unsafe impl<K, V> Send for IterMut<'_, K, V>
+where
+    K: Sync,
+    V: Send,
+{
+}
+
+unsafe impl<K, V> Sync for IterMut<'_, K, V>
+where
+    K: Sync,
+    V: Sync,
+{
+}
+
+// An iterator that removes all entries from a table.
+pub struct Drain<'a, K, V, A: Allocator> {
+    i: usize,
+    table: Table<K, V>,
+    count: &'a mut Counter,
+    _alloc: PhantomData<A>,
+}
+
+impl<K, V, A: Allocator> Iterator for Drain<'_, K, V, A> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // The table has not yet been allocated.
+        if self.table.raw.is_null() {
+            return None;
+        }
+
+        while self.i < self.table.len() {
+            let i = self.i;
+            self.i += 1;
+
+            // Safety: We have unique access to the table, and `i` is in bounds.
+            if let Some(entry) = unsafe { take_entry::<K, V, A>(self.table, i) } {
+                return Some(entry);
+            }
+        }
+
+        // Drained every entry in the table, we're done.
+        None
+    }
+}
+
+impl<K, V, A: Allocator> Drop for Drain<'_, K, V, A> {
+    fn drop(&mut self) {
+        // Remove any entries that were not yielded.
+        self.for_each(drop);
+
+        if self.table.raw.is_null() {
+            return;
+        }
+
+        // The table is now empty, so it can be reset without leaving tombstones behind.
+        for i in 0..self.table.len() {
+            // Safety: We have unique access to the table.
+            unsafe {
+                *self.table.entry(i).as_ptr() = ptr::null_mut();
+                *self.table.meta(i).as_ptr() = meta::EMPTY;
+            }
+        }
+
+        self.table.state_mut().tombstones.reset();
+        self.count.reset();
+    }
+}
+
+// Safety: The iterator holds a unique reference to the HashMap and outputs owned
+// keys and values.
+unsafe impl<K: Send, V: Send, A: Allocator> Send for Drain<'_, K, V, A> {}
+unsafe impl<K: Sync, V: Sync, A: Allocator> Sync for Drain<'_, K, V, A> {}
+
+// An iterator that moves all entries out of a table.
+pub struct IntoIter<K, V, S, A: Allocator> {
+    i: usize,
+    table: Table<K, V>,
+    // The owned map, dropping any entries that were not yielded.
+    _map: HashMap<K, V, S, A>,
+}
+
+impl<K, V, S, A: Allocator> Iterator for IntoIter<K, V, S, A> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // The table has not yet been allocated.
+        if self.table.raw.is_null() {
+            return None;
+        }
+
+        while self.i < self.table.len() {
+            let i = self.i;
+            self.i += 1;
+
+            // Safety: We own the table, and `i` is in bounds.
+            if let Some(entry) = unsafe { take_entry::<K, V, A>(self.table, i) } {
+                return Some(entry);
+            }
+        }
+
+        // Moved every entry out of the table, we're done.
+        None
+    }
+}
+
+// Safety: The iterator owns the HashMap and outputs owned keys and values.
+unsafe impl<K: Send, V: Send, S: Send, A: Allocator + Send> Send for IntoIter<K, V, S, A> {}
+unsafe impl<K: Sync, V: Sync, S: Sync, A: Allocator + Sync> Sync for IntoIter<K, V, S, A> {}
+
+// Moves the entry at the given index out of a table, leaving a tombstone behind.
+//
+// # Safety
+//
+// The caller must have unique access to the table, the table must not have any pending
+// resizes, and `i` must be in bounds.
+unsafe fn take_entry<K, V, A: Allocator>(table: Table<K, V>, i: usize) -> Option<(K, V)> {
+    let slot = unsafe { &mut *table.entry(i).as_ptr() };
+    let entry = slot.unpack();
+
+    // The entry is empty or deleted.
+    if entry.ptr.is_null() {
+        return None;
+    }
+
+    *slot = Entry::TOMBSTONE;
+    unsafe { *table.meta(i).as_ptr() = meta::TOMBSTONE };
+    table
+        .state()
+        .tombstones
+        .get(0)
+        .fetch_add(1, Ordering::Relaxed);
+
+    // Safety: The entry was allocated by the map and is now unreachable from the table.
+    let entry = unsafe { Entry::into_inner::<A>(entry.ptr) };
+    Some((entry.key, entry.value))
+}
+
 impl<K, V, G> Clone for Iter<'_, K, V, G> {
     #[inline]
     fn clone(&self) -> Self {
         Iter {
             i: self.i,
+            end: self.end,
             table: self.table,
             guard: self.guard,
         }
     }
 }
 
-impl<K, V, S> Clone for HashMapRef<'_, K, V, S> {
+impl<K, V, S, A: Allocator> Clone for HashMapRef<'_, K, V, S, A> {
     #[inline]
     fn clone(&self) -> Self {
         HashMapRef {
@@ -2167,7 +4896,7 @@ impl<K, V, S> Clone for HashMapRef<'_, K, V, S> {
     }
 }
 
-impl<K, V, S> Drop for HashMap<K, V, S> {
+impl<K, V, S, A: Allocator> Drop for HashMap<K, V, S, A> {
     fn drop(&mut self) {
         let mut raw = *self.table.get_mut();
 
@@ -2181,15 +4910,15 @@ impl<K, V, S> Drop for HashMap<K, V, S> {
         while !raw.is_null() {
             let mut table = unsafe { Table::<K, V>::from_raw(raw) };
             let next = *table.state_mut().next.get_mut();
-            unsafe { drop_entries::<K, V>(table) };
-            unsafe { drop_table::<K, V>(table) };
+            unsafe { drop_entries::<K, V, A>(table) };
+            unsafe { drop_table::<K, V, A>(table) };
             raw = next;
         }
     }
 }
 
 // Drop all entries in this table.
-unsafe fn drop_entries<K, V>(table: Table<K, V>) {
+unsafe fn drop_entries<K, V, A: Allocator>(table: Table<K, V>) {
     for i in 0..table.len() {
         let entry = unsafe { (*table.entry(i).as_ptr()).unpack() };
 
@@ -2199,12 +4928,12 @@ unsafe fn drop_entries<K, V>(table: Table<K, V>) {
         }
 
         // Drop the entry.
-        unsafe { Entry::reclaim::<K, V>(entry.ptr.cast()) }
+        unsafe { Entry::dealloc::<A>(entry.ptr) };
     }
 }
 
 // Drop the table allocation.
-unsafe fn drop_table<K, V>(mut table: Table<K, V>) {
+unsafe fn drop_table<K, V, A: Allocator>(mut table: Table<K, V>) {
     // Safety: `drop_table` is being called from `reclaim_all` in `Drop` or
     // a table is being reclaimed by our thread. In both cases, the collector
     // is still alive and safe to access through the state pointer.
@@ -2217,15 +4946,11 @@ unsafe fn drop_table<K, V>(mut table: Table<K, V>) {
     // for this entry to be deferred, our table must have been retired *after* the
     // entry was made accessible in the next table. Now that our table is being reclaimed,
     // the entry has thus been totally removed from the map, and can be safely retired.
-    unsafe {
-        table
-            .state_mut()
-            .deferred
-            .retire_all(collector, Entry::reclaim::<K, V>)
-    }
+    let reclaim = Entry::reclaim::<K, V, A>;
+    unsafe { table.state_mut().deferred.retire_all(collector, reclaim) }
 
     // Deallocate the table.
-    unsafe { Table::dealloc(table) };
+    unsafe { Table::dealloc::<A>(table) };
 }
 
 // Entry metadata, inspired by `hashbrown`.
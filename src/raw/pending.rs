@@ -0,0 +1,289 @@
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+use super::utils::{self, Parker};
+
+// Keys that are currently being initialized by `get_or_init_once` or `get_or_insert_async`.
+pub struct Pending<K> {
+    // A placeholder for every key that is being initialized, sharded by hash.
+    //
+    // Initializers are expected to be rare and expensive, so a simple list for each
+    // shard is sufficient.
+    shards: Box<[Mutex<Vec<Placeholder>>]>,
+    // A thread parker for waiting on initializers.
+    parker: Parker,
+    _key: PhantomData<K>,
+}
+
+impl<K> Default for Pending<K> {
+    fn default() -> Pending<K> {
+        let shards = (0..utils::shards()).map(|_| Default::default()).collect();
+
+        Pending {
+            shards,
+            parker: Parker::default(),
+            _key: PhantomData,
+        }
+    }
+}
+
+// Safety: The placeholder list owns boxed keys, and keys are only accessed while
+// the lock is held.
+unsafe impl<K: Send> Send for Pending<K> {}
+unsafe impl<K: Send> Sync for Pending<K> {}
+
+impl<K> Drop for Pending<K> {
+    fn drop(&mut self) {
+        // Drop the keys of any initializers that were leaked.
+        for shard in self.shards.iter_mut() {
+            for mut placeholder in shard.get_mut().unwrap().drain(..) {
+                // Safety: The key was allocated as a `Box<K>` in `Placeholders::insert`.
+                let _key = unsafe { placeholder.take_key::<K>() };
+            }
+        }
+    }
+}
+
+// A placeholder for a key that is being initialized.
+struct Placeholder {
+    hash: u64,
+    // The boxed key, or `None` if the key was taken by the initializer to insert the value.
+    //
+    // The key is type-erased to avoid making the map invariant over `K`.
+    key: Option<NonNull<()>>,
+    status: Arc<Status>,
+}
+
+impl Placeholder {
+    // Returns `true` if this placeholder may be for the given key.
+    //
+    // Once the key is taken by the initializer it can no longer be compared, so any key
+    // with the same hash is considered a match until the placeholder is removed.
+    //
+    // # Safety
+    //
+    // The key must be of type `K`.
+    #[inline]
+    unsafe fn matches<K: Eq>(&self, hash: u64, key: &K) -> bool {
+        self.hash == hash
+            && match self.key {
+                Some(ours) => unsafe { ours.cast::<K>().as_ref() == key },
+                None => true,
+            }
+    }
+
+    // Takes ownership of the key, if it has not already been taken.
+    //
+    // # Safety
+    //
+    // The key must be of type `K`.
+    #[inline]
+    unsafe fn take_key<K>(&mut self) -> Option<K> {
+        let key = self.key.take()?;
+        unsafe { Some(*Box::from_raw(key.cast::<K>().as_ptr())) }
+    }
+}
+
+// The status of an initializer, shared with any waiting threads or tasks.
+pub struct Status {
+    state: AtomicPtr<()>,
+    // Tasks waiting for the initializer to complete.
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Status {
+    // The initializer is still running.
+    const RUNNING: *mut () = ptr::null_mut();
+
+    // The placeholder was removed, either because the value was inserted
+    // or because the initializer panicked.
+    const DONE: *mut () = 1 as _;
+
+    // Returns the key used to park on this status.
+    #[inline]
+    fn addr(&self) -> usize {
+        self as *const Status as usize
+    }
+
+    // Returns `true` if the initializer is no longer running.
+    #[inline]
+    fn is_done(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == Status::DONE
+    }
+
+    // Returns a future that resolves once the initializer completes or is abandoned.
+    #[inline]
+    pub fn wait_async(self: Arc<Status>) -> Wait {
+        Wait { status: self }
+    }
+}
+
+// A future that resolves once an initializer completes or is abandoned.
+pub struct Wait {
+    status: Arc<Status>,
+}
+
+impl Future for Wait {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.status.is_done() {
+            return Poll::Ready(());
+        }
+
+        // Register our waker.
+        {
+            let mut wakers = self.status.wakers.lock().unwrap();
+            if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+        }
+
+        // Check the status again in case the initializer completed before we registered.
+        //
+        // The initializer marks itself as done before waking any tasks, so we
+        // either observe the new state here, or will be woken.
+        if self.status.is_done() {
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<K> Pending<K> {
+    // Acquires the lock for the placeholders with the given hash.
+    #[inline]
+    pub fn lock(&self, hash: u64) -> Placeholders<'_, K> {
+        Placeholders {
+            pending: self,
+            placeholders: self.shards[(hash as usize) & (self.shards.len() - 1)]
+                .lock()
+                .unwrap(),
+        }
+    }
+
+    // Blocks until the given initializer completes or is abandoned.
+    #[inline]
+    pub fn wait(&self, status: &Status) {
+        self.parker.park(status.addr(), &status.state, |state| {
+            state == Status::RUNNING
+        });
+    }
+}
+
+// A locked shard of placeholders.
+pub struct Placeholders<'a, K> {
+    pending: &'a Pending<K>,
+    placeholders: MutexGuard<'a, Vec<Placeholder>>,
+}
+
+impl<'a, K: Eq> Placeholders<'a, K> {
+    // Returns the status of the initializer for the given key, if there is one.
+    #[inline]
+    pub fn find(&self, hash: u64, key: &K) -> Option<Arc<Status>> {
+        self.placeholders
+            .iter()
+            // Safety: Every placeholder in the list holds a key of type `K`.
+            .find(|placeholder| unsafe { placeholder.matches(hash, key) })
+            .map(|placeholder| placeholder.status.clone())
+    }
+
+    // Installs a placeholder for the given key, returning the initializer that owns it.
+    #[inline]
+    pub fn insert(mut self, hash: u64, key: K) -> Initializer<'a, K> {
+        let status = Arc::new(Status {
+            state: AtomicPtr::new(Status::RUNNING),
+            wakers: Mutex::new(Vec::new()),
+        });
+
+        self.placeholders.push(Placeholder {
+            hash,
+            key: Some(NonNull::from(Box::leak(Box::new(key))).cast()),
+            status: status.clone(),
+        });
+
+        Initializer {
+            pending: self.pending,
+            hash,
+            status,
+        }
+    }
+}
+
+// The owner of a placeholder.
+//
+// If the initializer is dropped before completing, for example if the initialization
+// function panics, fails, or its future is cancelled, the placeholder is removed and any
+// waiting threads or tasks are woken to retry the initialization.
+pub struct Initializer<'a, K> {
+    pending: &'a Pending<K>,
+    hash: u64,
+    status: Arc<Status>,
+}
+
+impl<K> Initializer<'_, K> {
+    // Takes the key out of the placeholder and passes it to `f`, then removes the placeholder
+    // and wakes any waiting threads.
+    //
+    // The placeholder lock is not held while `f` runs, but the placeholder continues to block
+    // other initializers for any key with the same hash. `f` should make the initialized value
+    // visible before it returns, so that it is observed by threads that acquire the lock after
+    // the placeholder is removed.
+    #[inline]
+    pub fn complete<T>(self, f: impl FnOnce(K) -> T) -> T {
+        let key = {
+            let mut placeholders = self.pending.lock(self.hash);
+            let i = self.position(&placeholders);
+
+            // Safety: Every placeholder in the list holds a key of type `K`, and only
+            // the initializer takes it.
+            unsafe { placeholders.placeholders[i].take_key::<K>().unwrap() }
+        };
+
+        // Note that if `f` panics, the placeholder is still removed when we are dropped.
+        f(key)
+    }
+
+    // Returns the position of our placeholder in the list.
+    #[inline]
+    fn position(&self, placeholders: &Placeholders<'_, K>) -> usize {
+        placeholders
+            .placeholders
+            .iter()
+            .position(|placeholder| Arc::ptr_eq(&placeholder.status, &self.status))
+            .unwrap()
+    }
+}
+
+impl<K> Drop for Initializer<'_, K> {
+    fn drop(&mut self) {
+        // Remove our placeholder, dropping the key if the initializer was abandoned.
+        {
+            let mut placeholders = self.pending.lock(self.hash);
+            let i = self.position(&placeholders);
+            let mut placeholder = placeholders.placeholders.swap_remove(i);
+            drop(placeholders);
+
+            // Safety: Every placeholder in the list holds a key of type `K`.
+            let _key = unsafe { placeholder.take_key::<K>() };
+        }
+
+        // Wake up any waiting threads.
+        //
+        // Note that this must be `SeqCst` to be visible to the parker.
+        self.status.state.store(Status::DONE, Ordering::SeqCst);
+        self.pending.parker.unpark(self.status.addr());
+
+        // Wake up any waiting tasks.
+        let wakers = std::mem::take(&mut *self.status.wakers.lock().unwrap());
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
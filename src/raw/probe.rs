@@ -1,4 +1,9 @@
-// A quadratic probe sequence.
+use super::group::Group;
+
+// A quadratic probe sequence over groups of entries.
+//
+// Entries within a group are probed linearly, allowing their metadata to be
+// scanned in parallel, while groups themselves are probed quadratically.
 #[derive(Default)]
 pub struct Probe {
     // The current index in the probe sequence.
@@ -11,10 +16,14 @@ pub struct Probe {
 
 impl Probe {
     // Initialize the probe sequence.
+    //
+    // The table length must be at least `Group::WIDTH`.
     #[inline]
     pub fn start(hash: usize, mask: usize) -> Probe {
+        debug_assert!(mask + 1 >= Group::WIDTH);
+
         Probe {
-            i: hash & mask,
+            i: hash & mask & !(Group::WIDTH - 1),
             len: 0,
             stride: 0,
         }
@@ -24,24 +33,96 @@ impl Probe {
     #[inline]
     pub fn next(&mut self, mask: usize) {
         self.len += 1;
-        self.stride += 1;
+
+        // Continue to the next entry in the group.
+        if self.len & (Group::WIDTH - 1) != 0 {
+            self.i += 1;
+            return;
+        }
+
+        // Otherwise, continue to the next group.
+        self.stride += Group::WIDTH;
+        self.i = ((self.i & !(Group::WIDTH - 1)) + self.stride) & mask;
+    }
+
+    // Increment the probe sequence to the start of the next group.
+    //
+    // The probe must currently be at the start of a group.
+    #[inline]
+    pub fn next_group(&mut self, mask: usize) {
+        debug_assert!(self.len & (Group::WIDTH - 1) == 0);
+
+        self.len += Group::WIDTH;
+        self.stride += Group::WIDTH;
         self.i = (self.i + self.stride) & mask;
     }
 }
 
-// The maximum probe length for table operations.
-//
-// Estimating a load factor for the hash-table based on probe lengths allows
-// the hash-table to avoid loading the length every insert, which is a source
-// of contention.
-pub fn limit(capacity: usize) -> usize {
-    // 5 * log2(capacity): Testing shows this gives us a ~85% load factor.
-    5 * ((usize::BITS as usize) - (capacity.leading_zeros() as usize) - 1)
+// The probe configuration of a map.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    // The target load factor.
+    load_factor: f64,
+    // The probe limit multiplier, per `log2(capacity)`.
+    scale: f64,
+    // The maximum probe limit.
+    max_probe: usize,
 }
 
-// Returns an estimate of the number of entries needed to hold `capacity` elements.
-pub fn entries_for(capacity: usize) -> usize {
-    // We should rarely resize before 75%.
-    let capacity = capacity.checked_mul(8).expect("capacity overflow") / 6;
-    capacity.next_power_of_two()
+impl Config {
+    // The default load factor.
+    pub const LOAD_FACTOR: f64 = 0.75;
+
+    // Create a probe configuration.
+    pub fn new(load_factor: f64, max_probe: Option<usize>) -> Config {
+        // 5 * log2(capacity) at the default load factor: Testing shows this gives us a ~85% load
+        // factor. The expected probe length grows with `-ln(1 - load_factor)`, so we scale the
+        // limit proportionally.
+        let scale = 5.0 * (1.0 - load_factor).ln() / (1.0 - Config::LOAD_FACTOR).ln();
+
+        Config {
+            load_factor,
+            scale,
+            max_probe: max_probe.unwrap_or(usize::MAX),
+        }
+    }
+
+    // Returns `true` if neither the load factor nor the maximum probe length were configured.
+    pub fn is_default(&self) -> bool {
+        self.load_factor == Config::LOAD_FACTOR && self.max_probe == usize::MAX
+    }
+
+    // The maximum probe length for table operations.
+    //
+    // Estimating a load factor for the hash-table based on probe lengths allows
+    // the hash-table to avoid loading the length every insert, which is a source
+    // of contention.
+    pub fn limit(&self, capacity: usize) -> usize {
+        let log2 = (usize::BITS as usize) - (capacity.leading_zeros() as usize) - 1;
+        ((self.scale * log2 as f64).round() as usize).min(self.max_probe)
+    }
+
+    // Returns an estimate of the number of entries needed to hold `capacity` elements.
+    pub fn entries_for(&self, capacity: usize) -> usize {
+        // We should rarely resize before reaching the load factor.
+        let entries = (capacity as f64 / self.load_factor).ceil();
+        assert!(entries < usize::MAX as f64, "capacity overflow");
+
+        (entries as usize)
+            .checked_next_power_of_two()
+            .expect("capacity overflow")
+    }
+
+    // Returns the number of elements at which a table of the given length should be
+    // grown, rather than rehashed at the same capacity, after reaching the probe limit.
+    pub fn grow_threshold(&self, len: usize) -> usize {
+        // At the default load factor, we grow tables that are at least 50% full.
+        (len as f64 * self.load_factor / 1.5) as usize
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config::new(Config::LOAD_FACTOR, None)
+    }
 }
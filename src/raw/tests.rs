@@ -0,0 +1,271 @@
+// Scripted interleavings of concurrent operations, driven step by step on a single thread.
+
+use super::*;
+
+use std::hash::Hasher;
+
+use seize::Collector;
+
+// A hasher that maps keys with the same low byte to the same hash, placing them in the same
+// probe sequence with the same metadata.
+#[derive(Clone, Default)]
+struct Colliding;
+
+#[derive(Default)]
+struct CollidingHasher(u64);
+
+impl Hasher for CollidingHasher {
+    fn finish(&self) -> u64 {
+        self.0 & 0xff
+    }
+
+    fn write(&mut self, _: &[u8]) {
+        unimplemented!()
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = n;
+    }
+}
+
+impl BuildHasher for Colliding {
+    type Hasher = CollidingHasher;
+
+    fn build_hasher(&self) -> CollidingHasher {
+        CollidingHasher::default()
+    }
+}
+
+type Map = HashMap<u64, u64, Colliding, Global>;
+
+// Creates a map with a single group of entries and incremental resizing.
+fn map() -> Map {
+    HashMap::new(
+        8,
+        Colliding,
+        Global,
+        Collector::new(),
+        ResizeMode::Incremental(64),
+        ShrinkMode::default(),
+        CompactMode::default(),
+        ProbeConfig::default(),
+        EntryPool::default(),
+    )
+}
+
+// Allocates an entry that is not yet inserted into the map.
+fn entry(map: &Map, key: u64) -> *mut Entry<u64, u64> {
+    map.alloc_entry(Entry {
+        key,
+        value: key,
+        link: map.collector.link(),
+    })
+}
+
+// Returns the number of entries for the given key in the table.
+fn count(table: Table<u64, u64>, key: u64) -> usize {
+    (0..table.len())
+        .filter(|&i| {
+            let entry = unsafe { table.entry(i) }.load(Ordering::Acquire).unpack();
+            !entry.ptr.is_null() && unsafe { (*entry.ptr).key } == key
+        })
+        .count()
+}
+
+// Inserts and removes a key, leaving a tombstone in the first entry.
+fn tombstone(map: &Map, guard: &impl Guard) {
+    let mut root = map.root(guard);
+    root.insert(0x100, 0, true, guard);
+    root.remove(&0x100, guard);
+    assert_eq!(
+        unsafe { root.table.meta(0) }.load(Ordering::Relaxed),
+        meta::TOMBSTONE
+    );
+}
+
+// An insert that reserved a tombstone while the table starts resizing, racing with an insert
+// of the same key that probes past the reservation into the next table.
+#[test]
+fn reserve_during_resize() {
+    let map = map();
+    let guard = map.collector().enter();
+    tombstone(&map, &guard);
+
+    let mut root = map.root(&guard);
+    let key = 0x200;
+    let (h1, h2) = root.hash(&key);
+
+    // The first insert reserves the tombstone.
+    let reservation = Reservation::tombstone(root.table, 0, h2, guard.thread_id()).unwrap();
+
+    // A resize starts, and the remaining entries are null copied.
+    let next = root.get_or_alloc_next(None);
+    for i in 1..root.table.len() {
+        root.copy_at_incremental(i, next, &guard);
+    }
+
+    // The second insert probes past the reservation and continues in the next table.
+    let new_entry = entry(&map, 0x200);
+    let result = unsafe {
+        root.insert_with(
+            untagged(new_entry),
+            map.hasher.hash_one(key),
+            true,
+            false,
+            &guard,
+        )
+    };
+    assert!(matches!(result, RawInsertResult::Inserted(_)));
+    assert_eq!(count(next, 0x200), 1);
+
+    // The first insert sees the copy and cannot publish.
+    let validation = unsafe { root.validate(h1, h2, 0, &key, &guard) };
+    assert!(matches!(validation, Validation::Copying));
+
+    let new_entry = entry(&map, 0x200);
+    assert!(!reservation.publish(new_entry));
+    unsafe { Entry::dealloc::<Global>(new_entry) };
+
+    // Completing the copy leaves a single entry for the key.
+    root.copy_at_incremental(0, next, &guard);
+    assert_eq!(count(next, 0x200), 1);
+    assert_eq!(root.get(&0x200, &guard), Some((&0x200, &0x200)));
+}
+
+// An insert that validated its reservation before the table starts resizing, racing with an
+// insert of the same key that probes past the reservation into the next table.
+#[test]
+fn validate_before_resize() {
+    let map = map();
+    let guard = map.collector().enter();
+    tombstone(&map, &guard);
+
+    let mut root = map.root(&guard);
+    let (h1, h2) = root.hash(&0x200u64);
+
+    // The first insert reserves the tombstone and validates, sealing the empty entry.
+    let reservation = Reservation::tombstone(root.table, 0, h2, guard.thread_id()).unwrap();
+    let key = 0x200;
+    let validation = unsafe { root.validate(h1, h2, 0, &key, &guard) };
+    assert!(matches!(validation, Validation::Valid));
+    assert_eq!(
+        unsafe { root.table.entry(1) }.load(Ordering::Relaxed),
+        Entry::SEALED
+    );
+
+    // A resize starts, and the remaining entries are null copied.
+    let next = root.get_or_alloc_next(None);
+    for i in 1..root.table.len() {
+        root.copy_at_incremental(i, next, &guard);
+    }
+
+    // The second insert aborts the reservation before continuing in the next table.
+    let new_entry = entry(&map, 0x200);
+    let result = unsafe {
+        root.insert_with(
+            untagged(new_entry),
+            map.hasher.hash_one(key),
+            true,
+            false,
+            &guard,
+        )
+    };
+    assert!(matches!(result, RawInsertResult::Inserted(_)));
+
+    let new_entry = entry(&map, 0x200);
+    assert!(!reservation.publish(new_entry));
+    unsafe { Entry::dealloc::<Global>(new_entry) };
+
+    root.copy_at_incremental(0, next, &guard);
+    assert_eq!(count(root.table, 0x200), 0);
+    assert_eq!(count(next, 0x200), 1);
+}
+
+// Two inserts of the same key that reserved different tombstones.
+#[test]
+fn conflicting_reservations() {
+    let map = map();
+    let guard = map.collector().enter();
+
+    let mut root = map.root(&guard);
+    root.insert(0x100, 0, true, &guard);
+    root.insert(0x300, 0, true, &guard);
+    root.remove(&0x100, &guard);
+    root.remove(&0x300, &guard);
+
+    let (h1, h2) = root.hash(&0x200u64);
+    let first = Reservation::tombstone(root.table, 0, h2, guard.thread_id()).unwrap();
+    let second = Reservation::tombstone(root.table, 1, h2, guard.thread_id()).unwrap();
+
+    // The insert that validates first aborts the other reservation.
+    let key = 0x200;
+    let validation = unsafe { root.validate(h1, h2, 0, &key, &guard) };
+    assert!(matches!(validation, Validation::Valid));
+
+    let new_entry = entry(&map, 0x200);
+    assert!(!second.publish(new_entry));
+    unsafe { Entry::dealloc::<Global>(new_entry) };
+
+    let new_entry = entry(&map, 0x200);
+    assert!(first.publish(new_entry));
+
+    // The aborted entry is never reserved again, but remains a tombstone.
+    assert_eq!(
+        unsafe { root.table.entry(1) }.load(Ordering::Relaxed),
+        Entry::TOMBSTONE
+    );
+    assert!(Reservation::tombstone(root.table, 1, h2, guard.thread_id()).is_none());
+
+    // Retrying the insert finds the published entry.
+    assert!(matches!(
+        root.insert(0x200, 1, false, &guard),
+        InsertResult::Error { .. }
+    ));
+    assert_eq!(count(root.table, 0x200), 1);
+}
+
+// Inserts that find a sealed entry must reserve it, and copies move the seal forward.
+#[test]
+fn sealed_entries() {
+    let map = map();
+    let guard = map.collector().enter();
+    tombstone(&map, &guard);
+
+    let mut root = map.root(&guard);
+    let (h1, h2) = root.hash(&0x200u64);
+
+    let reservation = Reservation::tombstone(root.table, 0, h2, guard.thread_id()).unwrap();
+    let key = 0x200;
+    let validation = unsafe { root.validate(h1, h2, 0, &key, &guard) };
+    assert!(matches!(validation, Validation::Valid));
+
+    // An insert cannot claim the sealed entry directly.
+    let new_entry = entry(&map, 0x300);
+    let status = unsafe { root.insert_at(1, h2, new_entry, &guard) };
+    assert!(matches!(status, InsertStatus::Sealed));
+
+    // A copy claims the sealed entry after sealing the next empty entry.
+    let copied = unsafe { root.insert_copy(untagged(new_entry), false, &guard) };
+    assert_eq!(copied.map(|(_, i)| i), Some(1));
+    assert_eq!(
+        unsafe { root.table.entry(2) }.load(Ordering::Relaxed),
+        Entry::SEALED
+    );
+
+    // An insert of the reserved key that probes past the copy still reaches a seal.
+    let new_entry = entry(&map, 0x200);
+    let status = unsafe { root.insert_at(2, h2, new_entry, &guard) };
+    assert!(matches!(status, InsertStatus::Sealed));
+    unsafe { Entry::dealloc::<Global>(new_entry) };
+
+    let new_entry = entry(&map, 0x200);
+    assert!(reservation.publish(new_entry));
+
+    // Later inserts of the key find the published entry.
+    assert!(matches!(
+        root.insert(0x200, 1, false, &guard),
+        InsertResult::Error { .. }
+    ));
+    assert_eq!(count(root.table, 0x200), 1);
+    assert_eq!(count(root.table, 0x300), 1);
+}
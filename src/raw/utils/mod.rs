@@ -1,9 +1,9 @@
 mod parker;
 pub use parker::Parker;
 
-use std::ops::Deref;
+use std::ops::{Deref, DerefMut};
 use std::ptr::NonNull;
-use std::sync::atomic::{AtomicIsize, AtomicPtr, Ordering};
+use std::sync::atomic::{AtomicIsize, AtomicPtr, AtomicUsize, Ordering};
 
 // Polyfill for the unstable strict-provenance APIs.
 #[allow(clippy::missing_safety_doc)]
@@ -167,17 +167,61 @@ pub struct CachePadded<T> {
     value: T,
 }
 
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for CachePadded<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+// Returns the number of shards used for per-thread state.
+pub fn shards() -> usize {
+    // Per-thread state is allocated for every table, so cache the number of shards as
+    // querying the available parallelism can be expensive.
+    static SHARDS: AtomicUsize = AtomicUsize::new(0);
+
+    let mut num_shards = SHARDS.load(Ordering::Relaxed);
+    if num_shards == 0 {
+        let num_cpus = std::thread::available_parallelism()
+            .map(usize::from)
+            .unwrap_or(1);
+        num_shards = num_cpus.next_power_of_two();
+        SHARDS.store(num_shards, Ordering::Relaxed);
+    }
+
+    num_shards
+}
+
+// Returns a shard index for the current thread.
+//
+// Unlike guard thread IDs, this is available without access to a collector.
+#[inline]
+pub fn thread_shard() -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    thread_local! {
+        static SHARD: usize = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Entries may be reclaimed while thread-locals are being destroyed.
+    SHARD.try_with(|shard| *shard).unwrap_or(0)
+}
+
 // A sharded atomic counter.
 pub struct Counter(Box<[CachePadded<AtomicIsize>]>);
 
 impl Default for Counter {
     fn default() -> Counter {
-        let num_cpus = std::thread::available_parallelism()
-            .map(usize::from)
-            .unwrap_or(1);
-        let shards = (0..num_cpus.next_power_of_two())
-            .map(|_| Default::default())
-            .collect();
+        let shards = (0..shards()).map(|_| Default::default()).collect();
         Counter(shards)
     }
 }
@@ -201,6 +245,37 @@ impl Counter {
             // so assume the map is empty.
             .unwrap_or(0)
     }
+
+    // Resets all counter shards to zero.
+    #[inline]
+    pub fn reset(&mut self) {
+        for shard in self.0.iter_mut() {
+            *shard.value.get_mut() = 0;
+        }
+    }
+}
+
+// Exponential backoff for retrying contended operations.
+#[derive(Default)]
+pub struct Backoff {
+    step: u32,
+}
+
+impl Backoff {
+    // The maximum number of spins, as a power of two.
+    const LIMIT: u32 = 6;
+
+    // Spins for an exponentially increasing number of iterations.
+    #[inline]
+    pub fn spin(&mut self) {
+        for _ in 0..1 << self.step {
+            std::hint::spin_loop();
+        }
+
+        if self.step < Backoff::LIMIT {
+            self.step += 1;
+        }
+    }
 }
 
 // `Box<T>` but aliasable.
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
 use std::sync::Mutex;
 use std::thread::{self, Thread};
+use std::time::Instant;
 
 // A simpler thread parker.
 //
@@ -26,6 +27,19 @@ struct State {
 impl Parker {
     // Block the current thread until the park condition is false.
     pub fn park<T>(&self, key: usize, atomic: &AtomicPtr<T>, should_park: impl Fn(*mut T) -> bool) {
+        self.park_until(key, atomic, should_park, None);
+    }
+
+    // Block the current thread until the park condition is false, or the deadline is reached.
+    //
+    // Returns `false` if the deadline was reached while the park condition was still true.
+    pub fn park_until<T>(
+        &self,
+        key: usize,
+        atomic: &AtomicPtr<T>,
+        should_park: impl Fn(*mut T) -> bool,
+        deadline: Option<Instant>,
+    ) -> bool {
         loop {
             // Insert our thread into the parker.
             let id = {
@@ -52,12 +66,34 @@ impl Parker {
                 {
                     self.pending.fetch_sub(1, Ordering::Relaxed);
                 }
-                return;
+                return true;
             }
 
             // Park until we are unparked.
             loop {
-                thread::park();
+                match deadline {
+                    None => thread::park(),
+                    Some(deadline) => {
+                        let now = Instant::now();
+
+                        // We timed out, remove our thread if it wasn't already unparked.
+                        if now >= deadline {
+                            let mut state = self.state.lock().unwrap();
+                            if state
+                                .threads
+                                .get_mut(&key)
+                                .and_then(|threads| threads.remove(&id))
+                                .is_some()
+                            {
+                                self.pending.fetch_sub(1, Ordering::Relaxed);
+                            }
+
+                            return !should_park(atomic.load(Ordering::Acquire));
+                        }
+
+                        thread::park_timeout(deadline - now);
+                    }
+                }
 
                 let mut state = self.state.lock().unwrap();
                 if !state
@@ -71,7 +107,7 @@ impl Parker {
 
             // Ensure we were unparked for the correct reason.
             if !should_park(atomic.load(Ordering::Acquire)) {
-                return;
+                return true;
             }
         }
     }
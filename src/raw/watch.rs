@@ -0,0 +1,224 @@
+use std::ptr;
+use std::sync::atomic::{self, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Instant;
+
+use super::utils::{self, Parker};
+
+// Threads and tasks watching for changes to keys, registered by `wait_for` or `watch`.
+pub struct Watchers {
+    // The number of registered watchers.
+    //
+    // This allows writers to skip notifications entirely when there are no watchers.
+    count: AtomicUsize,
+    // The registered watchers, bucketed by hash.
+    //
+    // Watchers are expected to be rare, so a simple list for each bucket is sufficient.
+    buckets: Box<[Mutex<Vec<Watcher>>]>,
+    // A thread parker for blocking watchers.
+    parker: Parker,
+}
+
+impl Default for Watchers {
+    fn default() -> Watchers {
+        let buckets = (0..utils::shards()).map(|_| Default::default()).collect();
+
+        Watchers {
+            buckets,
+            count: AtomicUsize::new(0),
+            parker: Parker::default(),
+        }
+    }
+}
+
+// A watcher for a given key.
+struct Watcher {
+    // The hash of the key being watched.
+    //
+    // Watchers are only matched by hash, so a watcher may be spuriously notified of
+    // changes to a different key with the same hash.
+    hash: u64,
+    signal: Arc<Signal>,
+}
+
+// A notification counter, shared between a watcher and any writers.
+struct Signal {
+    // The number of times the watcher was notified.
+    //
+    // This is stored as a pointer to allow parking on it.
+    version: AtomicPtr<()>,
+    // The task waiting for a notification.
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Signal {
+    // Returns the key used to park on this signal.
+    #[inline]
+    fn addr(&self) -> usize {
+        self as *const Signal as usize
+    }
+
+    // Returns the current version of the signal.
+    #[inline]
+    fn version(&self) -> usize {
+        self.version.load(Ordering::SeqCst) as usize
+    }
+}
+
+impl Watchers {
+    // Returns the bucket of watchers for the given hash.
+    #[inline]
+    fn bucket(&self, hash: u64) -> &Mutex<Vec<Watcher>> {
+        &self.buckets[(hash as usize) & (self.buckets.len() - 1)]
+    }
+
+    // Registers a watcher for the given hash.
+    //
+    // Any changes to the key that are not visible after this method returns will
+    // notify the registration.
+    #[inline]
+    pub fn register(&self, hash: u64) -> Registration<'_> {
+        let signal = Arc::new(Signal {
+            version: AtomicPtr::new(ptr::null_mut()),
+            waker: Mutex::new(None),
+        });
+
+        {
+            let mut watchers = self.bucket(hash).lock().unwrap();
+            watchers.push(Watcher {
+                hash,
+                signal: signal.clone(),
+            });
+
+            self.count.fetch_add(1, Ordering::SeqCst);
+        }
+
+        // Synchronize with `notify`.
+        //
+        // Either the writer observes our registration, or we observe the write.
+        atomic::fence(Ordering::SeqCst);
+
+        Registration {
+            watchers: self,
+            hash,
+            seen: signal.version(),
+            signal,
+        }
+    }
+
+    // Notifies any watchers registered for the hash of a key that was changed.
+    //
+    // The change must have been made with a `SeqCst` read-modify-write operation, which
+    // synchronizes with the fence in `register`: either we observe the registration, or
+    // the watcher observes the change. The hash is only computed if there are active watchers.
+    #[inline]
+    pub fn notify(&self, hash: impl FnOnce() -> u64) {
+        // Fast-path, no one is watching.
+        if self.count.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        let hash = hash();
+        let watchers = self.bucket(hash).lock().unwrap();
+        for watcher in watchers.iter().filter(|watcher| watcher.hash == hash) {
+            let signal = &watcher.signal;
+
+            // Increment the version.
+            //
+            // Note that this must be `SeqCst` to be visible to the parker. Writers hold the
+            // lock, so there are no concurrent increments.
+            let version = signal.version.load(Ordering::Relaxed) as usize;
+            signal
+                .version
+                .store(version.wrapping_add(1) as *mut (), Ordering::SeqCst);
+
+            // Wake up any waiting thread or task.
+            self.parker.unpark(signal.addr());
+            if let Some(waker) = signal.waker.lock().unwrap().take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+// A registered watcher, removed when dropped.
+pub struct Registration<'a> {
+    watchers: &'a Watchers,
+    // The hash of the key being watched.
+    hash: u64,
+    signal: Arc<Signal>,
+    // The last version we observed.
+    seen: usize,
+}
+
+impl Registration<'_> {
+    // Returns `true` and marks the notification as seen if the key changed since
+    // the last notification.
+    #[inline]
+    fn take_notification(&mut self) -> bool {
+        let version = self.signal.version();
+        if version == self.seen {
+            return false;
+        }
+
+        self.seen = version;
+        true
+    }
+
+    // Blocks until the key changes or the deadline is reached.
+    //
+    // Returns `false` if the deadline was reached.
+    #[inline]
+    pub fn park(&mut self, deadline: Option<Instant>) -> bool {
+        let seen = self.seen;
+        self.watchers.parker.park_until(
+            self.signal.addr(),
+            &self.signal.version,
+            |version| version as usize == seen,
+            deadline,
+        );
+
+        self.take_notification()
+    }
+
+    // Polls for a change to the key.
+    #[inline]
+    pub fn poll(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.take_notification() {
+            return Poll::Ready(());
+        }
+
+        // Register our waker.
+        {
+            let mut waker = self.signal.waker.lock().unwrap();
+            match &mut *waker {
+                Some(waker) if waker.will_wake(cx.waker()) => {}
+                waker => *waker = Some(cx.waker().clone()),
+            }
+        }
+
+        // Check the version again in case we were notified before we registered.
+        //
+        // Writers increment the version before waking the task, so we either
+        // observe the new version here, or will be woken.
+        if self.take_notification() {
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        let mut watchers = self.watchers.bucket(self.hash).lock().unwrap();
+        let i = watchers
+            .iter()
+            .position(|watcher| Arc::ptr_eq(&watcher.signal, &self.signal))
+            .unwrap();
+
+        watchers.swap_remove(i);
+        self.watchers.count.fetch_sub(1, Ordering::Relaxed);
+    }
+}
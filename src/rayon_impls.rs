@@ -0,0 +1,492 @@
+use rayon::iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer};
+use rayon::iter::{FromParallelIterator, IntoParallelIterator, ParallelExtend, ParallelIterator};
+
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::raw::{self, Allocator};
+use crate::{Guard, HashMap, HashMapRef};
+
+// The number of table slots visited by each task of a parallel bulk operation.
+const CHUNK_SIZE: usize = 1024;
+
+impl<K, V, S, A> HashMap<K, V, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    A: Allocator,
+{
+    /// Returns a parallel iterator visiting all key-value pairs in arbitrary order.
+    ///
+    /// The slots of the table are split across workers, each of which visits a disjoint
+    /// range of the table. Like [`HashMap::iter`], any pending resizes are completed before
+    /// iteration begins, and entries that are inserted or removed concurrently may or may
+    /// not be visited.
+    ///
+    /// The returned references are protected by the given guard, which is shared across
+    /// workers and so must be [`Sync`]. See [`HashMap::owned_guard`]. Unlike
+    /// [`HashMap::par_retain`], workers cannot enter their own guards, as the references they
+    /// yield may be collected and outlive the worker, so they must remain valid for as long as
+    /// the caller's guard. As with any guard, holding it for the duration of a long scan delays
+    /// the reclamation of entries that are removed concurrently.
+    ///
+    /// This method is only available with the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    /// use rayon::prelude::*;
+    ///
+    /// let map: HashMap<usize, usize> = (0..1000).map(|x| (x, x)).collect();
+    ///
+    /// let guard = map.owned_guard();
+    /// let sum: usize = map.par_iter(&guard).map(|(_, v)| v).sum();
+    /// assert_eq!(sum, (0..1000).sum());
+    /// ```
+    #[inline]
+    pub fn par_iter<'g, G>(&self, guard: &'g G) -> ParIter<'g, K, V, G>
+    where
+        K: Sync,
+        V: Sync,
+        G: Guard + Sync,
+    {
+        ParIter {
+            raw: self.raw.root(guard).iter(guard),
+        }
+    }
+
+    /// Returns a parallel iterator visiting all keys in arbitrary order.
+    ///
+    /// This method is only available with the `rayon` feature. See [`HashMap::par_iter`]
+    /// for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    /// use rayon::prelude::*;
+    ///
+    /// let map: HashMap<usize, usize> = (0..1000).map(|x| (x, x)).collect();
+    ///
+    /// let guard = map.owned_guard();
+    /// assert_eq!(map.par_keys(&guard).count(), 1000);
+    /// ```
+    #[inline]
+    pub fn par_keys<'g, G>(&self, guard: &'g G) -> ParKeys<'g, K, V, G>
+    where
+        K: Sync,
+        V: Sync,
+        G: Guard + Sync,
+    {
+        ParKeys {
+            iter: self.par_iter(guard),
+        }
+    }
+
+    /// Returns a parallel iterator visiting all values in arbitrary order.
+    ///
+    /// This method is only available with the `rayon` feature. See [`HashMap::par_iter`]
+    /// for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    /// use rayon::prelude::*;
+    ///
+    /// let map: HashMap<usize, usize> = (0..1000).map(|x| (x, x)).collect();
+    ///
+    /// let guard = map.owned_guard();
+    /// assert_eq!(map.par_values(&guard).max(), Some(&999));
+    /// ```
+    #[inline]
+    pub fn par_values<'g, G>(&self, guard: &'g G) -> ParValues<'g, K, V, G>
+    where
+        K: Sync,
+        V: Sync,
+        G: Guard + Sync,
+    {
+        ParValues {
+            iter: self.par_iter(guard),
+        }
+    }
+
+    /// Retains only the elements specified by the predicate, visiting the table in parallel.
+    ///
+    /// In other words, remove all pairs `(k, v)` for which `f(&k, &v)` returns `false`.
+    ///
+    /// Each worker enters its own guard for every range of the table it visits, so
+    /// a long-running operation does not prevent the reclamation of removed entries.
+    /// As with [`HashMap::retain`], the predicate may be called more than once for a
+    /// given entry if it is modified concurrently or the map is resized.
+    ///
+    /// This method is only available with the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map: HashMap<usize, usize> = (0..1000).map(|x| (x, x)).collect();
+    /// map.par_retain(|&k, _| k % 2 == 0);
+    /// assert_eq!(map.len(), 500);
+    /// ```
+    pub fn par_retain<F>(&self, f: F)
+    where
+        F: Fn(&K, &V) -> bool + Sync,
+        K: Send + Sync,
+        V: Send + Sync,
+        S: Sync,
+        A: Send + Sync,
+    {
+        // Complete any pending resizes and split the table into chunks.
+        let len = {
+            let guard = self.guard();
+            self.raw.root(&guard).linearized_len(&guard)
+        };
+
+        // Whether every entry in the table was visited.
+        let complete = AtomicBool::new(true);
+
+        (0..len.div_ceil(CHUNK_SIZE))
+            .into_par_iter()
+            .for_each(|chunk| {
+                let guard = self.guard();
+                let mut root = self.raw.root(&guard);
+
+                // The table was resized since we split it, so the chunks may no longer
+                // cover every entry.
+                if root.linearized_len(&guard) != len {
+                    complete.store(false, Ordering::Relaxed);
+                    return;
+                }
+
+                let range = chunk * CHUNK_SIZE..(chunk + 1) * CHUNK_SIZE;
+                let mut extract = root.extract_if_range(range, |k, v| !f(k, v), &guard);
+                extract.by_ref().for_each(drop);
+
+                // We skipped entries that were being copied to a new table.
+                if !extract.is_complete() {
+                    complete.store(false, Ordering::Relaxed);
+                }
+            });
+
+        let guard = self.guard();
+
+        // A concurrent resize prevented us from visiting every entry, fallback to
+        // a sequential pass over the new table.
+        if !complete.load(Ordering::Relaxed) {
+            return self.retain(f, &guard);
+        }
+
+        // The table may be mostly empty now, shrink it if necessary.
+        self.raw.root(&guard).check_shrink(&guard);
+    }
+
+    /// Clears the map, removing all key-value pairs in parallel.
+    ///
+    /// This method is only available with the `rayon` feature. See [`HashMap::par_retain`]
+    /// for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map: HashMap<usize, usize> = (0..1000).map(|x| (x, x)).collect();
+    /// map.par_clear();
+    /// assert!(map.is_empty());
+    /// ```
+    #[inline]
+    pub fn par_clear(&self)
+    where
+        K: Send + Sync,
+        V: Send + Sync,
+        S: Sync,
+        A: Send + Sync,
+    {
+        self.par_retain(|_, _| false)
+    }
+}
+
+impl<K, V, S, G, A> HashMapRef<'_, K, V, S, G, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: Guard,
+    A: Allocator,
+{
+    /// Returns a parallel iterator visiting all key-value pairs in arbitrary order.
+    ///
+    /// See [`HashMap::par_iter`] for details.
+    #[inline]
+    pub fn par_iter(&self) -> ParIter<'_, K, V, G>
+    where
+        K: Sync,
+        V: Sync,
+        G: Sync,
+    {
+        self.map.par_iter(&self.guard)
+    }
+
+    /// Returns a parallel iterator visiting all keys in arbitrary order.
+    ///
+    /// See [`HashMap::par_keys`] for details.
+    #[inline]
+    pub fn par_keys(&self) -> ParKeys<'_, K, V, G>
+    where
+        K: Sync,
+        V: Sync,
+        G: Sync,
+    {
+        self.map.par_keys(&self.guard)
+    }
+
+    /// Returns a parallel iterator visiting all values in arbitrary order.
+    ///
+    /// See [`HashMap::par_values`] for details.
+    #[inline]
+    pub fn par_values(&self) -> ParValues<'_, K, V, G>
+    where
+        K: Sync,
+        V: Sync,
+        G: Sync,
+    {
+        self.map.par_values(&self.guard)
+    }
+
+    /// Retains only the elements specified by the predicate, visiting the table in parallel.
+    ///
+    /// See [`HashMap::par_retain`] for details.
+    #[inline]
+    pub fn par_retain<F>(&self, f: F)
+    where
+        F: Fn(&K, &V) -> bool + Sync,
+        K: Send + Sync,
+        V: Send + Sync,
+        S: Sync,
+        A: Send + Sync,
+    {
+        self.map.par_retain(f)
+    }
+
+    /// Clears the map, removing all key-value pairs in parallel.
+    ///
+    /// See [`HashMap::par_clear`] for details.
+    #[inline]
+    pub fn par_clear(&self)
+    where
+        K: Send + Sync,
+        V: Send + Sync,
+        S: Sync,
+        A: Send + Sync,
+    {
+        self.map.par_clear()
+    }
+}
+
+impl<'a, K, V, S, G, A> IntoParallelIterator for &'a HashMapRef<'_, K, V, S, G, A>
+where
+    K: Hash + Eq + Sync,
+    V: Sync,
+    S: BuildHasher,
+    G: Guard + Sync,
+    A: Allocator,
+{
+    type Item = (&'a K, &'a V);
+    type Iter = ParIter<'a, K, V, G>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.par_iter()
+    }
+}
+
+impl<K, V, S, A> ParallelExtend<(K, V)> for &HashMap<K, V, S, A>
+where
+    K: Hash + Eq + Send + Sync,
+    V: Send + Sync,
+    S: BuildHasher + Sync,
+    A: Allocator + Send + Sync,
+{
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        // Each worker enters its own guard.
+        par_iter.into_par_iter().for_each_init(
+            || self.guard(),
+            |guard, (key, value)| {
+                self.insert(key, value, guard);
+            },
+        );
+    }
+}
+
+impl<K, V, S, A> ParallelExtend<(K, V)> for HashMap<K, V, S, A>
+where
+    K: Hash + Eq + Send + Sync,
+    V: Send + Sync,
+    S: BuildHasher + Sync,
+    A: Allocator + Send + Sync,
+{
+    #[inline]
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        (&*self).par_extend(par_iter)
+    }
+}
+
+impl<K, V, S, A> FromParallelIterator<(K, V)> for HashMap<K, V, S, A>
+where
+    K: Hash + Eq + Send + Sync,
+    V: Send + Sync,
+    S: BuildHasher + Default + Sync,
+    A: Allocator + Default + Send + Sync,
+{
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = (K, V)>,
+    {
+        let mut map = HashMap::default();
+        map.par_extend(par_iter);
+        map
+    }
+}
+
+/// A parallel iterator over a map's entries.
+///
+/// This struct is created by the [`par_iter`](HashMap::par_iter) method on [`HashMap`].
+/// See its documentation for details.
+pub struct ParIter<'g, K, V, G> {
+    raw: raw::Iter<'g, K, V, G>,
+}
+
+impl<'g, K: 'g, V: 'g, G> ParallelIterator for ParIter<'g, K, V, G>
+where
+    K: Sync,
+    V: Sync,
+    G: Guard + Sync,
+{
+    type Item = (&'g K, &'g V);
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        bridge_unindexed(IterProducer { raw: self.raw }, consumer)
+    }
+}
+
+impl<K, V, G> fmt::Debug for ParIter<'_, K, V, G>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+    G: Guard,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.raw.clone()).finish()
+    }
+}
+
+// A producer for a range of table slots.
+//
+// Every split borrows the caller's guard, as the yielded references are tied to its lifetime
+// rather than to the lifetime of any worker.
+struct IterProducer<'g, K, V, G> {
+    raw: raw::Iter<'g, K, V, G>,
+}
+
+impl<'g, K: 'g, V: 'g, G> UnindexedProducer for IterProducer<'g, K, V, G>
+where
+    K: Sync,
+    V: Sync,
+    G: Guard + Sync,
+{
+    type Item = (&'g K, &'g V);
+
+    fn split(mut self) -> (Self, Option<Self>) {
+        let split = self.raw.split().map(|raw| IterProducer { raw });
+        (self, split)
+    }
+
+    fn fold_with<F>(self, folder: F) -> F
+    where
+        F: Folder<Self::Item>,
+    {
+        folder.consume_iter(self.raw)
+    }
+}
+
+/// A parallel iterator over a map's keys.
+///
+/// This struct is created by the [`par_keys`](HashMap::par_keys) method on [`HashMap`].
+/// See its documentation for details.
+pub struct ParKeys<'g, K, V, G> {
+    iter: ParIter<'g, K, V, G>,
+}
+
+impl<'g, K: 'g, V: 'g, G> ParallelIterator for ParKeys<'g, K, V, G>
+where
+    K: Sync,
+    V: Sync,
+    G: Guard + Sync,
+{
+    type Item = &'g K;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.iter.map(|(key, _)| key).drive_unindexed(consumer)
+    }
+}
+
+impl<K, V, G> fmt::Debug for ParKeys<'_, K, V, G>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+    G: Guard,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ParKeys").field(&self.iter).finish()
+    }
+}
+
+/// A parallel iterator over a map's values.
+///
+/// This struct is created by the [`par_values`](HashMap::par_values) method on [`HashMap`].
+/// See its documentation for details.
+pub struct ParValues<'g, K, V, G> {
+    iter: ParIter<'g, K, V, G>,
+}
+
+impl<'g, K: 'g, V: 'g, G> ParallelIterator for ParValues<'g, K, V, G>
+where
+    K: Sync,
+    V: Sync,
+    G: Guard + Sync,
+{
+    type Item = &'g V;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.iter.map(|(_, value)| value).drive_unindexed(consumer)
+    }
+}
+
+impl<K, V, G> fmt::Debug for ParValues<'_, K, V, G>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+    G: Guard,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ParValues").field(&self.iter).finish()
+    }
+}
@@ -0,0 +1,352 @@
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::mem;
+
+use crate::raw::Allocator;
+use crate::{Guard, HashMap, HashMapRef, HashSet, HashSetRef};
+
+// The maximum number of bytes to preallocate based on a size hint.
+const MAX_PREALLOC_BYTES: usize = 1024 * 1024;
+
+// Returns the capacity to preallocate for the given size hint.
+//
+// Size hints are provided by the input, so they are capped to avoid allocating
+// excessive memory for malicious inputs.
+fn cautious_capacity<T>(hint: Option<usize>) -> usize {
+    let max = MAX_PREALLOC_BYTES / mem::size_of::<T>().max(1);
+    hint.unwrap_or(0).min(max)
+}
+
+impl<K, V, S, G, A> Serialize for HashMapRef<'_, K, V, S, G, A>
+where
+    K: Serialize + Hash + Eq,
+    V: Serialize,
+    S: BuildHasher,
+    G: Guard,
+    A: Allocator,
+{
+    fn serialize<Sr>(&self, serializer: Sr) -> Result<Sr::Ok, Sr::Error>
+    where
+        Sr: Serializer,
+    {
+        // Collect the entries first, as the length of the map may change concurrently
+        // and some formats require the exact length up front.
+        let entries = self.iter().collect::<Vec<_>>();
+
+        let mut map = serializer.serialize_map(Some(entries.len()))?;
+        for (key, value) in entries {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<K, V, S, A> Serialize for HashMap<K, V, S, A>
+where
+    K: Serialize + Hash + Eq,
+    V: Serialize,
+    S: BuildHasher,
+    A: Allocator,
+{
+    fn serialize<Sr>(&self, serializer: Sr) -> Result<Sr::Ok, Sr::Error>
+    where
+        Sr: Serializer,
+    {
+        self.pin().serialize(serializer)
+    }
+}
+
+impl<'de, K, V, S, A> Deserialize<'de> for HashMap<K, V, S, A>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+    A: Allocator + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(MapVisitor { _map: PhantomData })
+    }
+}
+
+impl<K, V, S, A> HashMap<K, V, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    A: Allocator,
+{
+    /// Deserializes a map and inserts its entries into this map.
+    ///
+    /// Unlike [`HashMap::deserialize`](Deserialize::deserialize), this merges the deserialized
+    /// entries into an existing map, which may be shared with other threads. Existing values
+    /// for any deserialized keys are replaced.
+    ///
+    /// If deserialization fails, any entries deserialized before the error remain in the map.
+    ///
+    /// This method is only available with the `serde` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// map.pin().insert("a".to_owned(), 0);
+    ///
+    /// let mut deserializer = serde_json::Deserializer::from_str(r#"{"a": 1, "b": 2}"#);
+    /// map.deserialize_into(&mut deserializer).unwrap();
+    ///
+    /// assert_eq!(map.pin().get("a"), Some(&1));
+    /// assert_eq!(map.pin().get("b"), Some(&2));
+    /// ```
+    pub fn deserialize_into<'de, D>(&self, deserializer: D) -> Result<(), D::Error>
+    where
+        K: Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(MapIntoVisitor { map: self })
+    }
+}
+
+// Inserts the entries of a serialized map into the given map.
+fn insert_entries<'de, K, V, S, A, M>(
+    map: &HashMap<K, V, S, A>,
+    mut access: M,
+) -> Result<(), M::Error>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    V: Deserialize<'de>,
+    S: BuildHasher,
+    A: Allocator,
+    M: MapAccess<'de>,
+{
+    let map = map.pin();
+    while let Some((key, value)) = access.next_entry()? {
+        map.insert(key, value);
+    }
+
+    Ok(())
+}
+
+// A visitor that deserializes a new map.
+struct MapVisitor<K, V, S, A: Allocator> {
+    _map: PhantomData<HashMap<K, V, S, A>>,
+}
+
+impl<'de, K, V, S, A> Visitor<'de> for MapVisitor<K, V, S, A>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    V: Deserialize<'de>,
+    S: Default + BuildHasher,
+    A: Allocator + Default,
+{
+    type Value = HashMap<K, V, S, A>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<M>(self, access: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        let map = HashMap::builder()
+            .hasher(S::default())
+            .allocator(A::default())
+            .capacity(cautious_capacity::<(K, V)>(access.size_hint()))
+            .build();
+
+        insert_entries(&map, access)?;
+        Ok(map)
+    }
+}
+
+// A visitor that deserializes entries into an existing map.
+struct MapIntoVisitor<'a, K, V, S, A: Allocator> {
+    map: &'a HashMap<K, V, S, A>,
+}
+
+impl<'de, K, V, S, A> Visitor<'de> for MapIntoVisitor<'_, K, V, S, A>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    V: Deserialize<'de>,
+    S: BuildHasher,
+    A: Allocator,
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<M>(self, access: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        insert_entries(self.map, access)
+    }
+}
+
+impl<K, S, G, A> Serialize for HashSetRef<'_, K, S, G, A>
+where
+    K: Serialize + Hash + Eq,
+    S: BuildHasher,
+    G: Guard,
+    A: Allocator,
+{
+    fn serialize<Sr>(&self, serializer: Sr) -> Result<Sr::Ok, Sr::Error>
+    where
+        Sr: Serializer,
+    {
+        // Collect the keys first, as the length of the set may change concurrently
+        // and some formats require the exact length up front.
+        let keys = self.iter().collect::<Vec<_>>();
+
+        let mut seq = serializer.serialize_seq(Some(keys.len()))?;
+        for key in keys {
+            seq.serialize_element(key)?;
+        }
+        seq.end()
+    }
+}
+
+impl<K, S, A> Serialize for HashSet<K, S, A>
+where
+    K: Serialize + Hash + Eq,
+    S: BuildHasher,
+    A: Allocator,
+{
+    fn serialize<Sr>(&self, serializer: Sr) -> Result<Sr::Ok, Sr::Error>
+    where
+        Sr: Serializer,
+    {
+        self.pin().serialize(serializer)
+    }
+}
+
+impl<'de, K, S, A> Deserialize<'de> for HashSet<K, S, A>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    S: Default + BuildHasher,
+    A: Allocator + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(SetVisitor { _set: PhantomData })
+    }
+}
+
+impl<K, S, A> HashSet<K, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    A: Allocator,
+{
+    /// Deserializes a sequence and inserts its elements into this set.
+    ///
+    /// This method is only available with the `serde` feature. See
+    /// [`HashMap::deserialize_into`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// set.pin().insert(0);
+    ///
+    /// let mut deserializer = serde_json::Deserializer::from_str("[1, 2]");
+    /// set.deserialize_into(&mut deserializer).unwrap();
+    ///
+    /// assert_eq!(set.len(), 3);
+    /// ```
+    pub fn deserialize_into<'de, D>(&self, deserializer: D) -> Result<(), D::Error>
+    where
+        K: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(SetIntoVisitor { set: self })
+    }
+}
+
+// Inserts the elements of a serialized sequence into the given set.
+fn insert_keys<'de, K, S, A, Q>(set: &HashSet<K, S, A>, mut access: Q) -> Result<(), Q::Error>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    S: BuildHasher,
+    A: Allocator,
+    Q: SeqAccess<'de>,
+{
+    let set = set.pin();
+    while let Some(key) = access.next_element()? {
+        set.insert(key);
+    }
+
+    Ok(())
+}
+
+// A visitor that deserializes a new set.
+struct SetVisitor<K, S, A: Allocator> {
+    _set: PhantomData<HashSet<K, S, A>>,
+}
+
+impl<'de, K, S, A> Visitor<'de> for SetVisitor<K, S, A>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    S: Default + BuildHasher,
+    A: Allocator + Default,
+{
+    type Value = HashSet<K, S, A>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<Q>(self, access: Q) -> Result<Self::Value, Q::Error>
+    where
+        Q: SeqAccess<'de>,
+    {
+        let set = HashSet::builder()
+            .hasher(S::default())
+            .allocator(A::default())
+            .capacity(cautious_capacity::<K>(access.size_hint()))
+            .build();
+
+        insert_keys(&set, access)?;
+        Ok(set)
+    }
+}
+
+// A visitor that deserializes elements into an existing set.
+struct SetIntoVisitor<'a, K, S, A: Allocator> {
+    set: &'a HashSet<K, S, A>,
+}
+
+impl<'de, K, S, A> Visitor<'de> for SetIntoVisitor<'_, K, S, A>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    S: BuildHasher,
+    A: Allocator,
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<Q>(self, access: Q) -> Result<Self::Value, Q::Error>
+    where
+        Q: SeqAccess<'de>,
+    {
+        insert_keys(self.set, access)
+    }
+}
@@ -0,0 +1,1506 @@
+//! A concurrent hash set and its associated iterator types.
+//!
+//! See [`HashSet`] for details.
+
+use crate::equivalent::Equivalent;
+use crate::map::{CompactMode, EntryPool, ResizeMode, ShrinkMode};
+use crate::raw::{self, Allocator, Global, InsertResult, ProbeConfig};
+use seize::{Collector, Guard, LocalGuard, OwnedGuard};
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+/// A concurrent hash set.
+///
+/// Most hash set operations require a [`Guard`](crate::Guard), which can be acquired through
+/// [`HashSet::guard`] or using the [`HashSet::pin`] API. See the [crate-level documentation](crate#usage)
+/// for details.
+///
+/// A `HashSet` is backed by the same table as a [`HashMap`](crate::HashMap) with unit values, and
+/// shares its consistency and performance characteristics.
+pub struct HashSet<K, S = RandomState, A: Allocator = Global> {
+    raw: raw::HashMap<K, (), S, A>,
+}
+
+// Safety: We only ever hand out &K through shared references to the set,
+// so normal Send/Sync rules apply. We never expose owned or mutable references
+// to keys. The allocator may be used to reclaim entries from any thread that
+// shares the set's collector, so it must be `Send` and `Sync`.
+unsafe impl<K: Send, S: Send, A> Send for HashSet<K, S, A> where A: Allocator + Send + Sync {}
+unsafe impl<K: Sync, S: Sync, A> Sync for HashSet<K, S, A> where A: Allocator + Send + Sync {}
+
+/// A builder for a [`HashSet`].
+///
+/// # Examples
+///
+/// ```rust
+/// use papaya::{HashSet, ResizeMode};
+/// use seize::Collector;
+/// use std::collections::hash_map::RandomState;
+///
+/// let set: HashSet<i32> = HashSet::builder()
+///     // Set the initial capacity.
+///     .capacity(2048)
+///     // Set the hasher.
+///     .hasher(RandomState::new())
+///     // Set the resize mode.
+///     .resize_mode(ResizeMode::Blocking)
+///     // Set a custom garbage collector.
+///     .collector(Collector::new().batch_size(128))
+///     // Construct the hash set.
+///     .build();
+/// ```
+pub struct HashSetBuilder<K, S = RandomState, A = Global> {
+    hasher: S,
+    alloc: A,
+    capacity: usize,
+    collector: Collector,
+    resize_mode: ResizeMode,
+    shrink_mode: ShrinkMode,
+    compact_mode: CompactMode,
+    entry_pool: EntryPool,
+    load_factor: f64,
+    max_probe: Option<usize>,
+    _k: PhantomData<K>,
+}
+
+impl<K, A> HashSetBuilder<K, RandomState, A> {
+    /// Set the hash builder used to hash keys.
+    ///
+    /// Warning: `hash_builder` is normally randomly generated, and is designed
+    /// to allow HashSets to be resistant to attacks that cause many collisions
+    /// and very poor performance. Setting it manually using this function can
+    /// expose a DoS attack vector.
+    ///
+    /// The `hash_builder` passed should implement the [`BuildHasher`] trait for
+    /// the HashSet to be useful, see its documentation for details.
+    pub fn hasher<S>(self, hasher: S) -> HashSetBuilder<K, S, A> {
+        HashSetBuilder {
+            hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _k: PhantomData,
+        }
+    }
+}
+
+impl<K, S> HashSetBuilder<K, S> {
+    /// Set the allocator used for the set's tables and entries.
+    ///
+    /// Memory is returned to the allocator when it is reclaimed, which may happen on any
+    /// thread that shares the set's [`Collector`], and never after the set is dropped.
+    ///
+    /// Custom allocators require the `allocator-api2` feature.
+    pub fn allocator<A>(self, alloc: A) -> HashSetBuilder<K, S, A> {
+        HashSetBuilder {
+            alloc,
+            hasher: self.hasher,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _k: PhantomData,
+        }
+    }
+}
+
+impl<K, S, A> HashSetBuilder<K, S, A> {
+    /// Set the initial capacity of the set.
+    ///
+    /// The table should be able to hold at least `capacity` elements before resizing.
+    /// However, the capacity is an estimate, and the table may prematurely resize due
+    /// to poor hash distribution. If `capacity` is 0, the hash set will not allocate.
+    pub fn capacity(self, capacity: usize) -> HashSetBuilder<K, S, A> {
+        HashSetBuilder {
+            capacity,
+            hasher: self.hasher,
+            alloc: self.alloc,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _k: PhantomData,
+        }
+    }
+
+    /// Set the resizing mode of the set. See [`ResizeMode`] for details.
+    pub fn resize_mode(self, resize_mode: ResizeMode) -> Self {
+        HashSetBuilder {
+            resize_mode,
+            hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _k: PhantomData,
+        }
+    }
+
+    /// Set the shrinking mode of the set. See [`ShrinkMode`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`ShrinkMode::Threshold`] is not between `0.0` and `0.5`, exclusive.
+    pub fn shrink_mode(self, shrink_mode: ShrinkMode) -> Self {
+        shrink_mode.validate();
+
+        HashSetBuilder {
+            shrink_mode,
+            hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _k: PhantomData,
+        }
+    }
+
+    /// Set the compaction mode of the set. See [`CompactMode`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`CompactMode::Threshold`] is not between `0.0` and `1.0`, exclusive.
+    pub fn compact_mode(self, compact_mode: CompactMode) -> Self {
+        compact_mode.validate();
+
+        HashSetBuilder {
+            compact_mode,
+            hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _k: PhantomData,
+        }
+    }
+
+    /// Set the entry pooling mode of the set. See [`EntryPool`] for details.
+    pub fn entry_pool(self, entry_pool: EntryPool) -> Self {
+        HashSetBuilder {
+            entry_pool,
+            hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _k: PhantomData,
+        }
+    }
+
+    /// Set the load factor of the set.
+    ///
+    /// The load factor is the target ratio of entries to table capacity. Tables are sized
+    /// to hold entries up to the load factor before resizing, and the probe limit used to
+    /// detect a full table scales with it. Lower load factors trade memory for shorter
+    /// probe sequences, while higher load factors use less memory at the cost of longer ones.
+    ///
+    /// The default load factor is `0.75`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `load_factor` is not between `0.0` and `1.0`, exclusive.
+    pub fn load_factor(self, load_factor: f64) -> Self {
+        assert!(
+            load_factor > 0.0 && load_factor < 1.0,
+            "load factor must be between 0.0 and 1.0"
+        );
+
+        HashSetBuilder {
+            load_factor,
+            hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            max_probe: self.max_probe,
+            _k: PhantomData,
+        }
+    }
+
+    /// Set the maximum probe length of the set.
+    ///
+    /// Operations probe at most this many entries past an entry's initial position before
+    /// the table is considered full and resized, bounding the latency of lookups. By default,
+    /// the probe limit is only determined by the table capacity and load factor.
+    ///
+    /// Note that a small maximum probe length can cause the table to resize before reaching
+    /// its load factor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_probe` is `0`.
+    pub fn max_probe(self, max_probe: usize) -> Self {
+        assert!(max_probe > 0, "maximum probe length must be non-zero");
+
+        HashSetBuilder {
+            max_probe: Some(max_probe),
+            hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            collector: self.collector,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            _k: PhantomData,
+        }
+    }
+
+    /// Set the [`seize::Collector`] used for garbage collection.
+    ///
+    /// This method may be useful when you want more control over garbage collection.
+    ///
+    /// Note that all `Guard` references used to access the set must be produced by
+    /// the provided `collector`.
+    pub fn collector(self, collector: Collector) -> Self {
+        HashSetBuilder {
+            collector,
+            hasher: self.hasher,
+            alloc: self.alloc,
+            capacity: self.capacity,
+            resize_mode: self.resize_mode,
+            shrink_mode: self.shrink_mode,
+            compact_mode: self.compact_mode,
+            entry_pool: self.entry_pool,
+            load_factor: self.load_factor,
+            max_probe: self.max_probe,
+            _k: PhantomData,
+        }
+    }
+
+    /// Construct a [`HashSet`] from the builder, using the configured options.
+    pub fn build(self) -> HashSet<K, S, A>
+    where
+        A: Allocator,
+    {
+        HashSet {
+            raw: raw::HashMap::new(
+                self.capacity,
+                self.hasher,
+                self.alloc,
+                self.collector,
+                self.resize_mode,
+                self.shrink_mode,
+                self.compact_mode,
+                ProbeConfig::new(self.load_factor, self.max_probe),
+                self.entry_pool,
+            ),
+        }
+    }
+}
+
+impl<K, S, A> fmt::Debug for HashSetBuilder<K, S, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashSetBuilder")
+            .field("capacity", &self.capacity)
+            .field("collector", &self.collector)
+            .field("resize_mode", &self.resize_mode)
+            .field("shrink_mode", &self.shrink_mode)
+            .field("compact_mode", &self.compact_mode)
+            .field("entry_pool", &self.entry_pool)
+            .field("load_factor", &self.load_factor)
+            .field("max_probe", &self.max_probe)
+            .finish()
+    }
+}
+
+impl<K> HashSet<K> {
+    /// Creates an empty `HashSet`.
+    ///
+    /// The hash set is initially created with a capacity of 0, so it will not allocate
+    /// until it is first inserted into.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    /// let set: HashSet<&str> = HashSet::new();
+    /// ```
+    pub fn new() -> HashSet<K> {
+        HashSet::with_capacity_and_hasher(0, RandomState::new())
+    }
+
+    /// Creates an empty `HashSet` with the specified capacity.
+    ///
+    /// The table should be able to hold at least `capacity` elements before resizing.
+    /// However, the capacity is an estimate, and the table may prematurely resize due
+    /// to poor hash distribution. If `capacity` is 0, the hash set will not allocate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    /// let set: HashSet<&str> = HashSet::with_capacity(10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> HashSet<K> {
+        HashSet::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+
+    /// Returns a builder for a `HashSet`.
+    ///
+    /// The builder can be used for more complex configuration, such as using
+    /// a custom [`Collector`], or [`ResizeMode`].
+    pub fn builder() -> HashSetBuilder<K> {
+        HashSetBuilder {
+            capacity: 0,
+            hasher: RandomState::default(),
+            alloc: Global,
+            collector: Collector::new(),
+            resize_mode: ResizeMode::default(),
+            shrink_mode: ShrinkMode::default(),
+            compact_mode: CompactMode::default(),
+            entry_pool: EntryPool::default(),
+            load_factor: ProbeConfig::LOAD_FACTOR,
+            max_probe: None,
+            _k: PhantomData,
+        }
+    }
+}
+
+impl<K, S, A> Default for HashSet<K, S, A>
+where
+    S: Default,
+    A: Allocator + Default,
+{
+    fn default() -> Self {
+        HashSet::builder()
+            .hasher(S::default())
+            .allocator(A::default())
+            .build()
+    }
+}
+
+impl<K, S> HashSet<K, S> {
+    /// Creates an empty `HashSet` which will use the given hash builder to hash
+    /// keys.
+    ///
+    /// Warning: `hash_builder` is normally randomly generated, and is designed
+    /// to allow HashSets to be resistant to attacks that cause many collisions
+    /// and very poor performance. Setting it manually using this function can
+    /// expose a DoS attack vector.
+    ///
+    /// The `hash_builder` passed should implement the [`BuildHasher`] trait for
+    /// the HashSet to be useful, see its documentation for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    /// use std::hash::RandomState;
+    ///
+    /// let s = RandomState::new();
+    /// let set = HashSet::with_hasher(s);
+    /// set.pin().insert(1);
+    /// ```
+    pub fn with_hasher(hash_builder: S) -> HashSet<K, S> {
+        HashSet::with_capacity_and_hasher(0, hash_builder)
+    }
+
+    /// Creates an empty `HashSet` with at least the specified capacity, using
+    /// `hash_builder` to hash the keys.
+    ///
+    /// The table should be able to hold at least `capacity` elements before resizing.
+    /// However, the capacity is an estimate, and the table may prematurely resize due
+    /// to poor hash distribution. If `capacity` is 0, the hash set will not allocate.
+    ///
+    /// Warning: `hash_builder` is normally randomly generated, and is designed
+    /// to allow HashSets to be resistant to attacks that cause many collisions
+    /// and very poor performance. Setting it manually using this function can
+    /// expose a DoS attack vector.
+    ///
+    /// The `hasher` passed should implement the [`BuildHasher`] trait for
+    /// the HashSet to be useful, see its documentation for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    /// use std::hash::RandomState;
+    ///
+    /// let s = RandomState::new();
+    /// let set = HashSet::with_capacity_and_hasher(10, s);
+    /// set.pin().insert(1);
+    /// ```
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> HashSet<K, S> {
+        HashSet {
+            raw: raw::HashMap::new(
+                capacity,
+                hash_builder,
+                Global,
+                Collector::default(),
+                ResizeMode::default(),
+                ShrinkMode::default(),
+                CompactMode::default(),
+                ProbeConfig::default(),
+                EntryPool::default(),
+            ),
+        }
+    }
+}
+
+impl<K, S, A: Allocator> HashSet<K, S, A> {
+    /// Returns a reference to the allocator used for the set's tables and entries.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.raw.allocator()
+    }
+
+    /// Returns a pinned reference to the set.
+    ///
+    /// The returned reference manages a guard internally, preventing garbage collection
+    /// for as long as it is held. See the [crate-level documentation](crate#usage) for details.
+    #[inline]
+    pub fn pin(&self) -> HashSetRef<'_, K, S, LocalGuard<'_>, A> {
+        HashSetRef {
+            guard: self.guard(),
+            set: self,
+        }
+    }
+
+    /// Returns a pinned reference to the set.
+    ///
+    /// Unlike [`HashSet::pin`], the returned reference implements `Send` and `Sync`,
+    /// allowing it to be held across `.await` points in work-stealing schedulers.
+    /// This is especially useful for iterators.
+    ///
+    /// The returned reference manages a guard internally, preventing garbage collection
+    /// for as long as it is held. See the [crate-level documentation](crate#usage) for details.
+    #[inline]
+    pub fn pin_owned(&self) -> HashSetRef<'_, K, S, OwnedGuard<'_>, A> {
+        HashSetRef {
+            guard: self.owned_guard(),
+            set: self,
+        }
+    }
+
+    /// Returns a guard for use with this set.
+    ///
+    /// Note that holding on to a guard prevents garbage collection.
+    /// See the [crate-level documentation](crate#usage) for details.
+    #[inline]
+    pub fn guard(&self) -> LocalGuard<'_> {
+        self.raw.collector().enter()
+    }
+
+    /// Returns an owned guard for use with this set.
+    ///
+    /// Owned guards implement `Send` and `Sync`, allowing them to be held across
+    /// `.await` points in work-stealing schedulers. This is especially useful
+    /// for iterators.
+    ///
+    /// Note that holding on to a guard prevents garbage collection.
+    /// See the [crate-level documentation](crate#usage) for details.
+    #[inline]
+    pub fn owned_guard(&self) -> OwnedGuard<'_> {
+        self.raw.collector().enter_owned()
+    }
+}
+
+impl<K, S, A> HashSet<K, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    A: Allocator,
+{
+    /// Returns the number of entries in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set = HashSet::new();
+    ///
+    /// set.pin().insert(1);
+    /// set.pin().insert(2);
+    /// assert!(set.len() == 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    /// Returns `true` if the set is empty. Otherwise returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// assert!(set.is_empty());
+    /// set.pin().insert("a");
+    /// assert!(!set.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the set contains the specified key.
+    ///
+    /// The key may be any borrowed form of the set's key type, or any other
+    /// [`Equivalent`] type, but [`Hash`] and [`Eq`] on the borrowed form *must*
+    /// match those for the key type.
+    ///
+    /// [`Eq`]: std::cmp::Eq
+    /// [`Hash`]: std::hash::Hash
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// let s = set.pin();
+    /// s.insert(1);
+    /// assert_eq!(s.contains(&1), true);
+    /// assert_eq!(s.contains(&2), false);
+    /// ```
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q, guard: &impl Guard) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.get(key, guard).is_some()
+    }
+
+    /// Returns a reference to the key in the set, if any, that is equal to the given key.
+    ///
+    /// The key may be any borrowed form of the set's key type, or any other
+    /// [`Equivalent`] type, but [`Hash`] and [`Eq`] on the borrowed form *must*
+    /// match those for the key type.
+    ///
+    /// [`Eq`]: std::cmp::Eq
+    /// [`Hash`]: std::hash::Hash
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// let s = set.pin();
+    /// s.insert(1);
+    /// assert_eq!(s.get(&1), Some(&1));
+    /// assert_eq!(s.get(&2), None);
+    /// ```
+    #[inline]
+    pub fn get<'g, Q>(&self, key: &Q, guard: &'g impl Guard) -> Option<&'g K>
+    where
+        K: 'g,
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.raw.root(guard).get(key, guard).map(|(k, _)| k)
+    }
+
+    /// Adds a key to the set.
+    ///
+    /// If the set did not have this key present, `true` is returned.
+    ///
+    /// If the set did have this key present, `false` is returned and the
+    /// set is not modified. In particular, the key that is already present
+    /// is not replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// assert_eq!(set.pin().insert(37), true);
+    /// assert_eq!(set.pin().is_empty(), false);
+    ///
+    /// assert_eq!(set.pin().insert(37), false);
+    /// assert_eq!(set.pin().len(), 1);
+    /// ```
+    #[inline]
+    pub fn insert(&self, key: K, guard: &impl Guard) -> bool {
+        match self.raw.root(guard).insert(key, (), false, guard) {
+            InsertResult::Inserted(_) => true,
+            InsertResult::Error { .. } => false,
+            InsertResult::Replaced(_) => unreachable!(),
+        }
+    }
+
+    /// Removes a key from the set, returning `true` if the key was previously
+    /// in the set.
+    ///
+    /// The key may be any borrowed form of the set's key type, or any other
+    /// [`Equivalent`] type, but [`Hash`] and [`Eq`] on the borrowed form *must*
+    /// match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// set.pin().insert(1);
+    /// assert_eq!(set.pin().remove(&1), true);
+    /// assert_eq!(set.pin().remove(&1), false);
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&self, key: &Q, guard: &impl Guard) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.raw.root(guard).remove(key, guard).is_some()
+    }
+
+    /// Tries to reserve capacity for `additional` more elements to be inserted
+    /// in the `HashSet`.
+    ///
+    /// After calling this method, the table should be able to hold at least `capacity` elements
+    /// before resizing. However, the capacity is an estimate, and the table may prematurely resize
+    /// due to poor hash distribution. The collection may also reserve more space to avoid frequent
+    /// reallocations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new allocation size overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set: HashSet<&str> = HashSet::new();
+    /// set.pin().reserve(10);
+    /// ```
+    #[inline]
+    pub fn reserve(&self, additional: usize, guard: &impl Guard) {
+        self.raw.root(guard).reserve(additional, guard);
+    }
+
+    /// Shrinks the capacity of the set as much as possible.
+    ///
+    /// See [`HashMap::shrink_to_fit`](crate::HashMap::shrink_to_fit) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set: HashSet<i32> = HashSet::with_capacity(1000);
+    /// set.pin().insert(1);
+    /// set.pin().shrink_to_fit();
+    /// assert!(set.pin().contains(&1));
+    /// ```
+    #[inline]
+    pub fn shrink_to_fit(&self, guard: &impl Guard) {
+        self.raw.root(guard).shrink_to(0, guard);
+    }
+
+    /// Shrinks the capacity of the set with a lower limit.
+    ///
+    /// See [`HashMap::shrink_to`](crate::HashMap::shrink_to) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set: HashSet<i32> = HashSet::with_capacity(1000);
+    /// set.pin().insert(1);
+    /// set.pin().shrink_to(10);
+    /// assert!(set.pin().contains(&1));
+    /// ```
+    #[inline]
+    pub fn shrink_to(&self, min_capacity: usize, guard: &impl Guard) {
+        self.raw.root(guard).shrink_to(min_capacity, guard);
+    }
+
+    /// Compacts the set, removing any deleted slots from the table.
+    ///
+    /// See [`HashMap::compact`](crate::HashMap::compact) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// set.pin().insert(1);
+    /// set.pin().insert(2);
+    /// set.pin().remove(&1);
+    /// set.pin().compact();
+    /// assert!(set.pin().contains(&2));
+    /// ```
+    #[inline]
+    pub fn compact(&self, guard: &impl Guard) {
+        self.raw.root(guard).compact(guard);
+    }
+
+    /// Clears the set, removing all keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set = HashSet::new();
+    ///
+    /// set.pin().insert(1);
+    /// set.pin().clear();
+    /// assert!(set.pin().is_empty());
+    /// ```
+    #[inline]
+    pub fn clear(&self, guard: &impl Guard) {
+        self.raw.root(guard).clear(guard)
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// In other words, remove all elements `e` for which `f(&e)` returns `false`.
+    /// The predicate may be called more than once for a given element if the set
+    /// is resized concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set: HashSet<i32> = (0..8).collect();
+    /// set.pin().retain(|&k| k % 2 == 0);
+    /// assert_eq!(set.len(), 4);
+    /// ```
+    #[inline]
+    pub fn retain<F>(&self, mut f: F, guard: &impl Guard)
+    where
+        F: FnMut(&K) -> bool,
+    {
+        self.raw
+            .root(guard)
+            .extract_if(|key, _| !f(key), guard)
+            .for_each(drop)
+    }
+
+    /// An iterator visiting all keys in arbitrary order.
+    /// The iterator element type is `&K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let set = HashSet::from(["a", "b", "c"]);
+    ///
+    /// for key in set.pin().iter() {
+    ///     println!("key: {key}");
+    /// }
+    /// ```
+    #[inline]
+    pub fn iter<'g, G>(&self, guard: &'g G) -> Iter<'g, K, G>
+    where
+        G: Guard,
+    {
+        Iter {
+            raw: self.raw.root(guard).iter(guard),
+        }
+    }
+
+    /// Visits the keys representing the difference, i.e., the keys that are in `self`
+    /// but not in `other`.
+    ///
+    /// Keys in `self` are accessed through `guard`, and keys in `other` are accessed
+    /// through `other_guard`. Like all iterators, the difference reflects a weak snapshot
+    /// of both sets, see the [crate-level documentation](crate#consistency) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let a = HashSet::from([1, 2, 3]);
+    /// let b = HashSet::from([4, 2, 3, 4]);
+    ///
+    /// let (guard_a, guard_b) = (a.guard(), b.guard());
+    /// let diff: Vec<_> = a.difference(&b, &guard_a, &guard_b).collect();
+    /// assert_eq!(diff, [&1]);
+    /// ```
+    #[inline]
+    pub fn difference<'g, G1, G2>(
+        &self,
+        other: &'g HashSet<K, S, A>,
+        guard: &'g G1,
+        other_guard: &'g G2,
+    ) -> Difference<'g, K, S, G1, G2, A>
+    where
+        G1: Guard,
+        G2: Guard,
+    {
+        Difference {
+            iter: self.iter(guard),
+            other,
+            other_guard,
+        }
+    }
+
+    /// Visits the keys representing the intersection, i.e., the keys that are both
+    /// in `self` and `other`.
+    ///
+    /// Keys in `self` are accessed through `guard`, and keys in `other` are accessed
+    /// through `other_guard`. Like all iterators, the intersection reflects a weak snapshot
+    /// of both sets, see the [crate-level documentation](crate#consistency) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let a = HashSet::from([1, 2, 3]);
+    /// let b = HashSet::from([4, 2, 3, 4]);
+    ///
+    /// let (guard_a, guard_b) = (a.guard(), b.guard());
+    /// let mut intersection: Vec<_> = a.intersection(&b, &guard_a, &guard_b).collect();
+    /// intersection.sort();
+    /// assert_eq!(intersection, [&2, &3]);
+    /// ```
+    #[inline]
+    pub fn intersection<'g, G1, G2>(
+        &self,
+        other: &'g HashSet<K, S, A>,
+        guard: &'g G1,
+        other_guard: &'g G2,
+    ) -> Intersection<'g, K, S, G1, G2, A>
+    where
+        G1: Guard,
+        G2: Guard,
+    {
+        Intersection {
+            iter: self.iter(guard),
+            other,
+            other_guard,
+        }
+    }
+
+    /// Visits the keys representing the union, i.e., all the keys in `self` or `other`,
+    /// without duplicates.
+    ///
+    /// Keys in `self` are accessed through `guard`, and keys in `other` are accessed
+    /// through `other_guard`. Like all iterators, the union reflects a weak snapshot
+    /// of both sets, see the [crate-level documentation](crate#consistency) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use papaya::HashSet;
+    ///
+    /// let a = HashSet::from([1, 2, 3]);
+    /// let b = HashSet::from([4, 2, 3, 4]);
+    ///
+    /// let (guard_a, guard_b) = (a.guard(), b.guard());
+    /// let mut union: Vec<_> = a.union(&b, &guard_a, &guard_b).collect();
+    /// union.sort();
+    /// assert_eq!(union, [&1, &2, &3, &4]);
+    /// ```
+    #[inline]
+    pub fn union<'g, G1, G2>(
+        &'g self,
+        other: &'g HashSet<K, S, A>,
+        guard: &'g G1,
+        other_guard: &'g G2,
+    ) -> Union<'g, K, S, G1, G2, A>
+    where
+        G1: Guard,
+        G2: Guard,
+    {
+        Union {
+            iter: self.iter(guard),
+            rest: other.difference(self, other_guard, guard),
+        }
+    }
+}
+
+impl<K, S, A> PartialEq for HashSet<K, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    A: Allocator,
+{
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
+        let (guard1, guard2) = (&self.guard(), &other.guard());
+
+        let mut iter = self.iter(guard1);
+        iter.all(|key| other.contains(key, guard2))
+    }
+}
+
+impl<K, S, A> Eq for HashSet<K, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    A: Allocator,
+{
+}
+
+impl<K, S, A> fmt::Debug for HashSet<K, S, A>
+where
+    K: Hash + Eq + fmt::Debug,
+    S: BuildHasher,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let guard = self.guard();
+        f.debug_set().entries(self.iter(&guard)).finish()
+    }
+}
+
+impl<K, S, A> Extend<K> for &HashSet<K, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    A: Allocator,
+{
+    fn extend<T: IntoIterator<Item = K>>(&mut self, iter: T) {
+        // from `hashbrown::HashSet::extend`:
+        // Keys may be already present or show multiple times in the iterator.
+        // Reserve the entire hint lower bound if the set is empty.
+        // Otherwise reserve half the hint (rounded up), so the set
+        // will only resize twice in the worst case.
+        let iter = iter.into_iter();
+        let reserve = if self.is_empty() {
+            iter.size_hint().0
+        } else {
+            (iter.size_hint().0 + 1) >> 1
+        };
+
+        let guard = self.guard();
+        self.reserve(reserve, &guard);
+
+        for key in iter {
+            self.insert(key, &guard);
+        }
+    }
+}
+
+impl<'a, K, S, A> Extend<&'a K> for &HashSet<K, S, A>
+where
+    K: Copy + Hash + Eq + 'a,
+    S: BuildHasher,
+    A: Allocator,
+{
+    fn extend<T: IntoIterator<Item = &'a K>>(&mut self, iter: T) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<K, const N: usize> From<[K; N]> for HashSet<K, RandomState>
+where
+    K: Hash + Eq,
+{
+    fn from(arr: [K; N]) -> Self {
+        HashSet::from_iter(arr)
+    }
+}
+
+impl<K, S, A> FromIterator<K> for HashSet<K, S, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+    A: Allocator + Default,
+{
+    fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+        let mut iter = iter.into_iter();
+
+        if let Some(key) = iter.next() {
+            // safety: we own `set`
+            let guard = unsafe { seize::unprotected() };
+
+            let (lower, _) = iter.size_hint();
+            let set = HashSet::builder()
+                .capacity(lower.saturating_add(1))
+                .hasher(S::default())
+                .allocator(A::default())
+                .build();
+
+            set.insert(key, &guard);
+
+            for key in iter {
+                set.insert(key, &guard);
+            }
+
+            set
+        } else {
+            Self::default()
+        }
+    }
+}
+
+impl<K, S, A> Clone for HashSet<K, S, A>
+where
+    K: Clone + Hash + Eq,
+    S: BuildHasher + Clone,
+    A: Allocator + Clone,
+{
+    fn clone(&self) -> HashSet<K, S, A> {
+        let other = HashSet::builder()
+            .capacity(self.len())
+            .hasher(self.raw.hasher.clone())
+            .allocator(self.allocator().clone())
+            .collector(self.raw.collector().clone())
+            .build();
+
+        {
+            let (guard1, guard2) = (&self.guard(), &other.guard());
+            for key in self.iter(guard1) {
+                other.insert(key.clone(), guard2);
+            }
+        }
+
+        other
+    }
+}
+
+/// A pinned reference to a [`HashSet`].
+///
+/// This type is created with [`HashSet::pin`] and can be used to easily access a [`HashSet`]
+/// without explicitly managing a guard. See the [crate-level documentation](crate#usage) for details.
+pub struct HashSetRef<'set, K, S, G, A: Allocator = Global> {
+    guard: G,
+    set: &'set HashSet<K, S, A>,
+}
+
+impl<'set, K, S, G, A> HashSetRef<'set, K, S, G, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: Guard,
+    A: Allocator,
+{
+    /// Returns a reference to the inner [`HashSet`].
+    #[inline]
+    pub fn set(&self) -> &'set HashSet<K, S, A> {
+        self.set
+    }
+
+    /// Returns the number of entries in the set.
+    ///
+    /// See [`HashSet::len`] for details.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Returns `true` if the set is empty. Otherwise returns `false`.
+    ///
+    /// See [`HashSet::is_empty`] for details.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Returns `true` if the set contains the specified key.
+    ///
+    /// See [`HashSet::contains`] for details.
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.set.contains(key, &self.guard)
+    }
+
+    /// Returns a reference to the key in the set, if any, that is equal to the given key.
+    ///
+    /// See [`HashSet::get`] for details.
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&K>
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.set.get(key, &self.guard)
+    }
+
+    /// Adds a key to the set.
+    ///
+    /// See [`HashSet::insert`] for details.
+    #[inline]
+    pub fn insert(&self, key: K) -> bool {
+        self.set.insert(key, &self.guard)
+    }
+
+    /// Removes a key from the set, returning `true` if the key was previously
+    /// in the set.
+    ///
+    /// See [`HashSet::remove`] for details.
+    #[inline]
+    pub fn remove<Q>(&self, key: &Q) -> bool
+    where
+        Q: Hash + Equivalent<K> + ?Sized,
+    {
+        self.set.remove(key, &self.guard)
+    }
+
+    /// Clears the set, removing all keys.
+    ///
+    /// See [`HashSet::clear`] for details.
+    #[inline]
+    pub fn clear(&self) {
+        self.set.clear(&self.guard)
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// See [`HashSet::retain`] for details.
+    #[inline]
+    pub fn retain<F>(&self, f: F)
+    where
+        F: FnMut(&K) -> bool,
+    {
+        self.set.retain(f, &self.guard)
+    }
+
+    /// Tries to reserve capacity for `additional` more elements to be inserted
+    /// in the set.
+    ///
+    /// See [`HashSet::reserve`] for details.
+    #[inline]
+    pub fn reserve(&self, additional: usize) {
+        self.set.reserve(additional, &self.guard)
+    }
+
+    /// Shrinks the capacity of the set as much as possible.
+    ///
+    /// See [`HashSet::shrink_to_fit`] for details.
+    #[inline]
+    pub fn shrink_to_fit(&self) {
+        self.set.shrink_to_fit(&self.guard)
+    }
+
+    /// Shrinks the capacity of the set with a lower limit.
+    ///
+    /// See [`HashSet::shrink_to`] for details.
+    #[inline]
+    pub fn shrink_to(&self, min_capacity: usize) {
+        self.set.shrink_to(min_capacity, &self.guard)
+    }
+
+    /// Compacts the set, removing any deleted slots from the table.
+    ///
+    /// See [`HashSet::compact`] for details.
+    #[inline]
+    pub fn compact(&self) {
+        self.set.compact(&self.guard)
+    }
+
+    /// An iterator visiting all keys in arbitrary order.
+    /// The iterator element type is `&K`.
+    ///
+    /// See [`HashSet::iter`] for details.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, G> {
+        self.set.iter(&self.guard)
+    }
+
+    /// Visits the keys representing the difference, i.e., the keys that are in `self`
+    /// but not in `other`.
+    ///
+    /// See [`HashSet::difference`] for details.
+    #[inline]
+    pub fn difference<'a, G2>(
+        &'a self,
+        other: &'a HashSetRef<'_, K, S, G2, A>,
+    ) -> Difference<'a, K, S, G, G2, A>
+    where
+        G2: Guard,
+    {
+        self.set.difference(other.set, &self.guard, &other.guard)
+    }
+
+    /// Visits the keys representing the intersection, i.e., the keys that are both
+    /// in `self` and `other`.
+    ///
+    /// See [`HashSet::intersection`] for details.
+    #[inline]
+    pub fn intersection<'a, G2>(
+        &'a self,
+        other: &'a HashSetRef<'_, K, S, G2, A>,
+    ) -> Intersection<'a, K, S, G, G2, A>
+    where
+        G2: Guard,
+    {
+        self.set.intersection(other.set, &self.guard, &other.guard)
+    }
+
+    /// Visits the keys representing the union, i.e., all the keys in `self` or `other`,
+    /// without duplicates.
+    ///
+    /// See [`HashSet::union`] for details.
+    #[inline]
+    pub fn union<'a, G2>(
+        &'a self,
+        other: &'a HashSetRef<'_, K, S, G2, A>,
+    ) -> Union<'a, K, S, G, G2, A>
+    where
+        G2: Guard,
+    {
+        self.set.union(other.set, &self.guard, &other.guard)
+    }
+}
+
+impl<K, S, G, A> fmt::Debug for HashSetRef<'_, K, S, G, A>
+where
+    K: Hash + Eq + fmt::Debug,
+    S: BuildHasher,
+    G: Guard,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, S, G, A> IntoIterator for &'a HashSetRef<'_, K, S, G, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G: Guard,
+    A: Allocator,
+{
+    type Item = &'a K;
+    type IntoIter = Iter<'a, K, G>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over a set's keys.
+///
+/// This struct is created by the [`iter`](HashSet::iter) method on [`HashSet`]. See its documentation for details.
+pub struct Iter<'g, K, G> {
+    raw: raw::Iter<'g, K, (), G>,
+}
+
+impl<'g, K: 'g, G> Iterator for Iter<'g, K, G>
+where
+    G: Guard,
+{
+    type Item = &'g K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw.next().map(|(k, _)| k)
+    }
+}
+
+impl<K, G> Clone for Iter<'_, K, G> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Iter {
+            raw: self.raw.clone(),
+        }
+    }
+}
+
+impl<K, G> fmt::Debug for Iter<'_, K, G>
+where
+    K: fmt::Debug,
+    G: Guard,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// A lazy iterator producing keys in the difference of two [`HashSet`]s.
+///
+/// This struct is created by the [`difference`](HashSet::difference) method on [`HashSet`].
+/// See its documentation for details.
+pub struct Difference<'g, K, S, G1, G2, A: Allocator = Global> {
+    iter: Iter<'g, K, G1>,
+    other: &'g HashSet<K, S, A>,
+    other_guard: &'g G2,
+}
+
+impl<'g, K: 'g, S, G1, G2, A> Iterator for Difference<'g, K, S, G1, G2, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G1: Guard,
+    G2: Guard,
+    A: Allocator,
+{
+    type Item = &'g K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let key = self.iter.next()?;
+            if !self.other.contains(key, self.other_guard) {
+                return Some(key);
+            }
+        }
+    }
+}
+
+impl<K, S, G1, G2, A: Allocator> Clone for Difference<'_, K, S, G1, G2, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Difference {
+            iter: self.iter.clone(),
+            other: self.other,
+            other_guard: self.other_guard,
+        }
+    }
+}
+
+impl<K, S, G1, G2, A> fmt::Debug for Difference<'_, K, S, G1, G2, A>
+where
+    K: Hash + Eq + fmt::Debug,
+    S: BuildHasher,
+    G1: Guard,
+    G2: Guard,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// A lazy iterator producing keys in the intersection of two [`HashSet`]s.
+///
+/// This struct is created by the [`intersection`](HashSet::intersection) method on [`HashSet`].
+/// See its documentation for details.
+pub struct Intersection<'g, K, S, G1, G2, A: Allocator = Global> {
+    iter: Iter<'g, K, G1>,
+    other: &'g HashSet<K, S, A>,
+    other_guard: &'g G2,
+}
+
+impl<'g, K: 'g, S, G1, G2, A> Iterator for Intersection<'g, K, S, G1, G2, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G1: Guard,
+    G2: Guard,
+    A: Allocator,
+{
+    type Item = &'g K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let key = self.iter.next()?;
+            if self.other.contains(key, self.other_guard) {
+                return Some(key);
+            }
+        }
+    }
+}
+
+impl<K, S, G1, G2, A: Allocator> Clone for Intersection<'_, K, S, G1, G2, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Intersection {
+            iter: self.iter.clone(),
+            other: self.other,
+            other_guard: self.other_guard,
+        }
+    }
+}
+
+impl<K, S, G1, G2, A> fmt::Debug for Intersection<'_, K, S, G1, G2, A>
+where
+    K: Hash + Eq + fmt::Debug,
+    S: BuildHasher,
+    G1: Guard,
+    G2: Guard,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// A lazy iterator producing keys in the union of two [`HashSet`]s.
+///
+/// This struct is created by the [`union`](HashSet::union) method on [`HashSet`].
+/// See its documentation for details.
+pub struct Union<'g, K, S, G1, G2, A: Allocator = Global> {
+    // The keys in the first set.
+    iter: Iter<'g, K, G1>,
+    // The keys in the second set that are not in the first.
+    rest: Difference<'g, K, S, G2, G1, A>,
+}
+
+impl<'g, K: 'g, S, G1, G2, A> Iterator for Union<'g, K, S, G1, G2, A>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    G1: Guard,
+    G2: Guard,
+    A: Allocator,
+{
+    type Item = &'g K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().or_else(|| self.rest.next())
+    }
+}
+
+impl<K, S, G1, G2, A: Allocator> Clone for Union<'_, K, S, G1, G2, A> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Union {
+            iter: self.iter.clone(),
+            rest: self.rest.clone(),
+        }
+    }
+}
+
+impl<K, S, G1, G2, A> fmt::Debug for Union<'_, K, S, G1, G2, A>
+where
+    K: Hash + Eq + fmt::Debug,
+    S: BuildHasher,
+    G1: Guard,
+    G2: Guard,
+    A: Allocator,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
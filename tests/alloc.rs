@@ -0,0 +1,263 @@
+#![cfg(feature = "allocator-api2")]
+
+use allocator_api2::alloc::{AllocError, Allocator, Global};
+use papaya::{Collector, EntryPool, HashMap, HashSet, ResizeMode};
+
+use std::alloc::Layout;
+use std::collections::hash_map::RandomState;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+const ENTRIES: usize = if cfg!(miri) { 64 } else { 10_000 };
+
+// An allocator that counts live allocations.
+#[derive(Clone, Default)]
+struct Counting {
+    live: Arc<AtomicUsize>,
+    total: Arc<AtomicUsize>,
+}
+
+impl Counting {
+    fn live(&self) -> usize {
+        self.live.load(Ordering::SeqCst)
+    }
+
+    fn total(&self) -> usize {
+        self.total.load(Ordering::SeqCst)
+    }
+}
+
+unsafe impl Allocator for Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = Global.allocate(layout)?;
+        self.live.fetch_add(1, Ordering::SeqCst);
+        self.total.fetch_add(1, Ordering::SeqCst);
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.live.fetch_sub(1, Ordering::SeqCst);
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+// The number of live allocations made by `Static`.
+static STATIC_LIVE: AtomicUsize = AtomicUsize::new(0);
+
+// A zero-sized allocator that counts live allocations.
+#[derive(Clone, Copy, Default)]
+struct Static;
+
+unsafe impl Allocator for Static {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = Global.allocate(layout)?;
+        STATIC_LIVE.fetch_add(1, Ordering::SeqCst);
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        STATIC_LIVE.fetch_sub(1, Ordering::SeqCst);
+        unsafe { Global.deallocate(ptr, layout) }
+    }
+}
+
+// Run the test with each resize mode.
+fn with_resize_mode(mut test: impl FnMut(ResizeMode)) {
+    test(ResizeMode::Blocking);
+    test(ResizeMode::Incremental(1));
+    test(ResizeMode::Incremental(128));
+}
+
+#[test]
+fn alloc_tables_and_entries() {
+    with_resize_mode(|mode| {
+        let alloc = Counting::default();
+        let map = HashMap::builder()
+            .resize_mode(mode)
+            .allocator(alloc.clone())
+            .build();
+
+        // The table is allocated lazily.
+        assert_eq!(alloc.total(), 0);
+
+        let m = map.pin();
+        for i in 0..ENTRIES {
+            m.insert(i, i);
+        }
+
+        // Every entry and at least one table was allocated.
+        assert!(alloc.total() > ENTRIES);
+
+        for i in 0..ENTRIES {
+            assert_eq!(m.get(&i), Some(&i));
+        }
+
+        drop(m);
+        drop(map);
+        assert_eq!(alloc.live(), 0);
+    });
+}
+
+#[test]
+fn alloc_reclaim() {
+    with_resize_mode(|mode| {
+        let alloc = Counting::default();
+        let map = HashMap::builder()
+            .resize_mode(mode)
+            .allocator(alloc.clone())
+            .build();
+
+        for i in 0..ENTRIES {
+            map.pin().insert(i, i);
+        }
+
+        // Replace and remove entries, retiring the old ones.
+        for i in 0..ENTRIES {
+            map.pin().insert(i, i + 1);
+        }
+        for i in (0..ENTRIES).step_by(2) {
+            map.pin().remove(&i);
+        }
+
+        map.pin().clear();
+        assert!(map.is_empty());
+
+        // Retired entries are returned to the allocator when they are reclaimed.
+        drop(map);
+        assert_eq!(alloc.live(), 0);
+    });
+}
+
+#[test]
+fn alloc_concurrent() {
+    const THREADS: usize = 4;
+
+    let alloc = Counting::default();
+    let map = HashMap::builder()
+        .resize_mode(ResizeMode::Incremental(1))
+        .allocator(alloc.clone())
+        .build();
+    let barrier = Barrier::new(THREADS);
+
+    thread::scope(|s| {
+        for t in 0..THREADS {
+            let (map, barrier) = (&map, &barrier);
+            s.spawn(move || {
+                barrier.wait();
+                let m = map.pin();
+                for i in (t..ENTRIES).step_by(THREADS) {
+                    m.insert(i, i);
+                    m.update(i, |v| v + 1);
+                    if i % 3 == 0 {
+                        m.remove(&i);
+                    }
+                }
+            });
+        }
+    });
+
+    let m = map.pin();
+    for i in 0..ENTRIES {
+        let expected = if i % 3 == 0 { None } else { Some(i + 1) };
+        assert_eq!(m.get(&i).copied(), expected);
+    }
+
+    drop(m);
+    drop(map);
+    assert_eq!(alloc.live(), 0);
+}
+
+#[test]
+fn alloc_owned() {
+    let alloc = Counting::default();
+    let mut map = HashMap::builder().allocator(alloc.clone()).build();
+
+    for i in 0..ENTRIES {
+        map.pin().insert(i, i);
+    }
+
+    // Entries are moved out of the table and deallocated directly.
+    let drained = map.drain().count();
+    assert_eq!(drained, ENTRIES);
+
+    for i in 0..ENTRIES {
+        map.pin().insert(i, i);
+    }
+
+    let mut entries: Vec<_> = map.clone().into_iter().collect();
+    entries.sort_unstable();
+    assert_eq!(entries, (0..ENTRIES).map(|i| (i, i)).collect::<Vec<_>>());
+
+    assert!(std::ptr::eq(
+        Arc::as_ptr(&map.allocator().live),
+        Arc::as_ptr(&alloc.live)
+    ));
+
+    drop(map);
+    assert_eq!(alloc.live(), 0);
+}
+
+#[test]
+fn alloc_zero_sized() {
+    let map = HashMap::<usize, usize, RandomState, Static>::default();
+
+    for i in 0..ENTRIES {
+        map.pin().insert(i, i);
+    }
+    for i in 0..ENTRIES {
+        map.pin().insert(i, i + 1);
+    }
+    assert!(STATIC_LIVE.load(Ordering::SeqCst) > ENTRIES);
+
+    drop(map);
+    assert_eq!(STATIC_LIVE.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn alloc_set() {
+    let alloc = Counting::default();
+    let set = HashSet::builder().allocator(alloc.clone()).build();
+
+    let s = set.pin();
+    for i in 0..ENTRIES {
+        s.insert(i);
+    }
+    for i in (0..ENTRIES).step_by(2) {
+        s.remove(&i);
+    }
+    assert_eq!(s.len(), ENTRIES / 2);
+    assert!(alloc.total() > ENTRIES);
+
+    drop(s);
+    drop(set);
+    assert_eq!(alloc.live(), 0);
+}
+
+#[test]
+fn alloc_entry_pool() {
+    with_resize_mode(|mode| {
+        let alloc = Counting::default();
+        let map = HashMap::builder()
+            .resize_mode(mode)
+            .entry_pool(EntryPool::PerThread(1024))
+            .collector(Collector::new().batch_size(1))
+            .allocator(alloc.clone())
+            .build();
+
+        map.pin().insert(0, 0);
+        let initial = alloc.total();
+
+        // Reclaimed entries are reused by later updates.
+        for i in 0..ENTRIES {
+            map.pin().insert(0, i);
+        }
+        assert!(alloc.total() - initial < ENTRIES / 2);
+        assert_eq!(map.pin().get(&0), Some(&(ENTRIES - 1)));
+
+        // Pooled entries are returned to the allocator when the map is dropped.
+        drop(map);
+        assert_eq!(alloc.live(), 0);
+    });
+}
@@ -0,0 +1,266 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+mod common;
+use common::with_map;
+
+// Run a future to completion on a single-threaded runtime.
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+// A waker that records whether it was woken.
+#[derive(Default)]
+struct Flag(AtomicBool);
+
+impl Wake for Flag {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn get_or_insert_async() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        block_on(async {
+            let m = map.pin_owned();
+            assert_eq!(m.get_or_insert_async(42, || async { 0 }).await, &0);
+            assert_eq!(
+                m.get_or_insert_async(42, || async { unreachable!() }).await,
+                &0
+            );
+            assert_eq!(m.len(), 1);
+        });
+    });
+}
+
+#[test]
+fn try_get_or_insert_async() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        block_on(async {
+            let m = map.pin_owned();
+            let result = m.try_get_or_insert_async(42, || async { Err(()) }).await;
+            assert_eq!(result, Err(()));
+            assert!(m.get(&42).is_none());
+
+            let result = m
+                .try_get_or_insert_async(42, || async { Ok::<_, ()>(1) })
+                .await;
+            assert_eq!(result, Ok(&1));
+            assert_eq!(m.get(&42), Some(&1));
+        });
+    });
+}
+
+#[test]
+fn coalesced() {
+    with_map::<usize, usize>(|map| {
+        let map = Arc::new(map());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let tasks = if cfg!(miri) { 4 } else { 64 };
+
+        block_on(async {
+            // Every task races to load the same key, yielding while loading.
+            let handles: Vec<_> = (0..tasks)
+                .map(|_| {
+                    let (map, calls) = (map.clone(), calls.clone());
+                    tokio::spawn(async move {
+                        let m = map.pin_owned();
+                        let value = m
+                            .get_or_insert_async(42, || async {
+                                calls.fetch_add(1, Ordering::Relaxed);
+                                for _ in 0..8 {
+                                    tokio::task::yield_now().await;
+                                }
+                                7
+                            })
+                            .await;
+                        *value
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                assert_eq!(handle.await.unwrap(), 7);
+            }
+        });
+
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert_eq!(map.len(), 1);
+    });
+}
+
+#[test]
+fn failed_leader() {
+    with_map::<usize, usize>(|map| {
+        let map = Arc::new(map());
+
+        block_on(async {
+            // The leader fails after the waiter starts waiting.
+            let leader = tokio::spawn({
+                let map = map.clone();
+                async move {
+                    let m = map.pin_owned();
+                    let result = m
+                        .try_get_or_insert_async(42, || async {
+                            for _ in 0..8 {
+                                tokio::task::yield_now().await;
+                            }
+                            Err(())
+                        })
+                        .await;
+                    result.copied()
+                }
+            });
+
+            tokio::task::yield_now().await;
+
+            let waiter = tokio::spawn({
+                let map = map.clone();
+                async move {
+                    let m = map.pin_owned();
+                    let result = m.try_get_or_insert_async(42, || async { Ok::<_, ()>(1) });
+                    result.await.copied()
+                }
+            });
+
+            // The waiter takes over the initialization.
+            assert_eq!(leader.await.unwrap(), Err(()));
+            assert_eq!(waiter.await.unwrap(), Ok(1));
+        });
+
+        assert_eq!(map.pin().get(&42), Some(&1));
+    });
+}
+
+#[test]
+fn cancelled_leader() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let m = map.pin_owned();
+
+        let leader_flag = Arc::new(Flag::default());
+        let leader_waker = Waker::from(leader_flag.clone());
+        let waiter_flag = Arc::new(Flag::default());
+        let waiter_waker = Waker::from(waiter_flag.clone());
+
+        // The leader installs a placeholder and never completes.
+        let mut leader = Box::pin(m.get_or_insert_async(42, std::future::pending));
+        let poll = leader
+            .as_mut()
+            .poll(&mut Context::from_waker(&leader_waker));
+        assert!(poll.is_pending());
+
+        // The waiter waits on the leader.
+        let mut waiter = pin!(m.get_or_insert_async(42, || async { 1 }));
+        let poll = waiter
+            .as_mut()
+            .poll(&mut Context::from_waker(&waiter_waker));
+        assert!(poll.is_pending());
+        assert!(!waiter_flag.0.load(Ordering::SeqCst));
+
+        // Cancelling the leader wakes the waiter, which takes over.
+        drop(leader);
+        assert!(waiter_flag.0.load(Ordering::SeqCst));
+
+        let poll = waiter
+            .as_mut()
+            .poll(&mut Context::from_waker(&waiter_waker));
+        assert_eq!(poll, Poll::Ready(&1));
+        assert_eq!(m.get(&42), Some(&1));
+    });
+}
+
+#[test]
+fn watch() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let m = map.pin_owned();
+
+        let flag = Arc::new(Flag::default());
+        let waker = Waker::from(flag.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut watch = m.watch(&42);
+        assert!(watch.poll_changed(&mut cx).is_pending());
+
+        // Changes to other keys are ignored.
+        m.insert(1, 1);
+        assert!(watch.poll_changed(&mut cx).is_pending());
+        assert!(!flag.0.load(Ordering::SeqCst));
+
+        // Insertion.
+        m.insert(42, 1);
+        assert!(flag.0.load(Ordering::SeqCst));
+        assert!(watch.poll_changed(&mut cx).is_ready());
+        assert!(watch.poll_changed(&mut cx).is_pending());
+
+        // Update.
+        m.update(42, |v| v + 1);
+        assert!(watch.poll_changed(&mut cx).is_ready());
+        assert_eq!(m.get(&42), Some(&2));
+
+        // Removal.
+        m.remove(&42);
+        assert!(watch.poll_changed(&mut cx).is_ready());
+
+        // Multiple changes are coalesced.
+        m.insert(42, 3);
+        m.insert(42, 4);
+        assert!(watch.poll_changed(&mut cx).is_ready());
+        assert!(watch.poll_changed(&mut cx).is_pending());
+
+        // Failed updates do not notify.
+        assert!(m.try_insert(42, 5).is_err());
+        assert!(m.remove_if(&42, |_| false).is_err());
+        assert!(watch.poll_changed(&mut cx).is_pending());
+    });
+}
+
+#[test]
+fn watch_spawned() {
+    with_map::<usize, usize>(|map| {
+        let map = Arc::new(map());
+
+        block_on(async {
+            // The watcher waits for the final value.
+            let watcher = tokio::spawn({
+                let map = map.clone();
+                async move {
+                    let m = map.pin_owned();
+                    let mut watch = m.watch(&42);
+
+                    while m.get(&42) != Some(&3) {
+                        watch.changed().await;
+                    }
+                }
+            });
+
+            tokio::task::yield_now().await;
+
+            let producer = tokio::spawn({
+                let map = map.clone();
+                async move {
+                    let m = map.pin_owned();
+                    for i in 1..=3 {
+                        m.insert(42, i);
+                        tokio::task::yield_now().await;
+                    }
+                }
+            });
+
+            producer.await.unwrap();
+            watcher.await.unwrap();
+        });
+
+        assert_eq!(map.pin().get(&42), Some(&3));
+    });
+}
@@ -1,9 +1,13 @@
 // Adapted from: https://github.com/jonhoo/flurry/blob/main/tests/basic.rs
 
-use papaya::{Compute, HashMap, Operation};
+use papaya::{
+    CompactMode, Compute, Cursor, Equivalent, HashMap, MapStats, Operation, ResizeMode, ShrinkMode,
+};
 
+use std::cell::Cell;
 use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
 use std::sync::Arc;
+use std::time::Duration;
 
 mod common;
 use common::with_map;
@@ -30,6 +34,94 @@ fn clear() {
     });
 }
 
+#[test]
+fn retain() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.pin().insert(i, i + 1);
+        }
+
+        map.pin().retain(|k, v| {
+            assert_eq!(*v, k + 1);
+            k % 3 == 0
+        });
+
+        let guard = map.guard();
+        assert_eq!(map.len(), len.div_ceil(3));
+        for i in 0..len {
+            assert_eq!(map.get(&i, &guard).is_some(), i % 3 == 0);
+        }
+    });
+}
+
+#[test]
+fn extract_if() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.pin().insert(i, i + 1);
+        }
+
+        let guard = map.guard();
+        let mut extracted: Vec<_> = map
+            .extract_if(|k, _| k % 2 == 0, &guard)
+            .map(|(&k, &v)| (k, v))
+            .collect();
+        extracted.sort();
+
+        let expected: Vec<_> = (0..len).step_by(2).map(|i| (i, i + 1)).collect();
+        assert_eq!(extracted, expected);
+        assert_eq!(map.len(), len / 2);
+        for i in 0..len {
+            assert_eq!(map.get(&i, &guard).is_some(), i % 2 != 0);
+        }
+
+        // Dropping the iterator early keeps the remaining entries.
+        let first = map.extract_if(|_, _| true, &guard).next();
+        assert!(first.is_some());
+        assert_eq!(map.len(), len / 2 - 1);
+    });
+}
+
+#[test]
+fn concurrent_retain() {
+    if cfg!(papaya_stress) {
+        return;
+    }
+
+    with_map::<usize, usize>(|map| {
+        let map = Arc::new(map());
+        let len = if cfg!(miri) { 64 } else { 1024 };
+
+        // Insert the even keys while concurrently removing the odd keys.
+        let map1 = map.clone();
+        let t1 = std::thread::spawn(move || {
+            for i in 0..len {
+                map1.pin().insert(i, i);
+            }
+        });
+        let map2 = map.clone();
+        let t2 = std::thread::spawn(move || {
+            for _ in 0..8 {
+                map2.pin().retain(|k, _| k % 2 == 0);
+            }
+        });
+
+        t1.join().unwrap();
+        t2.join().unwrap();
+        map.pin().retain(|k, _| k % 2 == 0);
+
+        let guard = map.guard();
+        assert_eq!(map.len(), len / 2);
+        for i in 0..len {
+            assert_eq!(map.get(&i, &guard).is_some(), i % 2 == 0);
+        }
+    });
+}
+
 #[test]
 fn insert() {
     with_map::<usize, usize>(|map| {
@@ -82,6 +174,439 @@ fn insert_and_remove() {
     });
 }
 
+#[test]
+fn with_hash() {
+    #[derive(Hash, PartialEq, Eq, Debug)]
+    struct Key {
+        id: usize,
+        name: String,
+    }
+
+    let len = if cfg!(miri) { 100 } else { 10_000 };
+
+    with_map::<Key, usize>(|map| {
+        let map = map();
+        let guard = map.guard();
+        let hash = |id: usize| map.hasher().hash_one((id, id.to_string().as_str()));
+        let eq = |id: usize| move |k: &Key| k.id == id && k.name == id.to_string();
+
+        assert_eq!(map.get_with_hash(hash(0), eq(0), &guard), None);
+        assert_eq!(map.remove_with_hash(hash(0), eq(0), &guard), None);
+
+        for i in 0..len {
+            let key = Key {
+                id: i,
+                name: i.to_string(),
+            };
+            assert_eq!(map.insert_with_hash(hash(i), key, i, &guard), None);
+        }
+
+        // Keys inserted with a precomputed hash are found by regular lookups, including
+        // after the table was resized.
+        for i in 0..len {
+            let key = Key {
+                id: i,
+                name: i.to_string(),
+            };
+            assert_eq!(map.get(&key, &guard), Some(&i));
+            assert_eq!(map.get_with_hash(hash(i), eq(i), &guard), Some(&i));
+            assert_eq!(map.get_with_hash(hash(i), eq(len + i), &guard), None);
+        }
+
+        for i in (0..len).step_by(2) {
+            assert_eq!(map.remove_with_hash(hash(i), eq(i), &guard), Some(&i));
+            assert_eq!(map.remove_with_hash(hash(i), eq(i), &guard), None);
+        }
+
+        assert_eq!(map.len(), len / 2);
+        for i in 0..len {
+            let expected = (i % 2 != 0).then_some(&i);
+            assert_eq!(map.get_with_hash(hash(i), eq(i), &guard), expected);
+        }
+    });
+}
+
+#[test]
+fn equivalent() {
+    #[derive(Hash, PartialEq, Eq, Debug)]
+    struct Key {
+        id: usize,
+        name: String,
+    }
+
+    #[derive(Hash)]
+    struct KeyRef<'a> {
+        id: usize,
+        name: &'a str,
+    }
+
+    impl Equivalent<Key> for KeyRef<'_> {
+        fn equivalent(&self, key: &Key) -> bool {
+            self.id == key.id && self.name == key.name
+        }
+    }
+
+    let len = if cfg!(miri) { 100 } else { 10_000 };
+
+    with_map::<Key, usize>(|map| {
+        let map = map();
+        let guard = map.guard();
+        let names = (0..len).map(|i| i.to_string()).collect::<Vec<_>>();
+        let key = |i: usize| KeyRef {
+            id: i,
+            name: &names[i],
+        };
+        let to_owned = |key: &KeyRef<'_>| Key {
+            id: key.id,
+            name: key.name.to_owned(),
+        };
+
+        // Missing keys are never converted.
+        assert_eq!(
+            map.update_ref(&key(0), |_| unreachable!(), |v| v + 1, &guard),
+            None
+        );
+
+        for i in 0..len {
+            let compute = |entry: Option<_>| match entry {
+                Some(_) => Operation::Abort(()),
+                None => Operation::Insert(i),
+            };
+
+            assert!(matches!(
+                map.compute_ref(&key(i), to_owned, compute, &guard),
+                Compute::Inserted(_, &v) if v == i
+            ));
+            assert_eq!(
+                map.compute_ref(&key(i), |_| unreachable!(), compute, &guard),
+                Compute::Aborted(())
+            );
+        }
+
+        for i in 0..len {
+            assert_eq!(map.get(&key(i), &guard), Some(&i));
+            assert!(map.contains_key(&key(i), &guard));
+            assert_eq!(
+                map.update_ref(&key(i), to_owned, |v| v + 1, &guard),
+                Some(&(i + 1))
+            );
+        }
+
+        for i in (0..len).step_by(2) {
+            assert_eq!(map.remove(&key(i), &guard), Some(&(i + 1)));
+            assert_eq!(map.remove(&key(i), &guard), None);
+        }
+
+        assert_eq!(map.len(), len / 2);
+        for i in 0..len {
+            let expected = (i % 2 != 0).then_some(i + 1);
+            assert_eq!(map.get(&key(i), &guard).copied(), expected);
+        }
+    });
+}
+
+#[test]
+fn remove_if() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let guard = map.guard();
+        assert_eq!(map.remove_if(&42, |_| true, &guard), Ok(None));
+
+        map.insert(42, 0, &guard);
+        assert_eq!(map.remove_if(&42, |v| *v == 1, &guard), Err(&0));
+        assert_eq!(map.get(&42, &guard), Some(&0));
+        assert_eq!(map.remove_if(&42, |v| *v == 0, &guard), Ok(Some(&0)));
+        assert!(map.get(&42, &guard).is_none());
+    });
+}
+
+#[test]
+fn compare_exchange() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let guard = map.guard();
+        let err = map.compare_exchange(42, &0, 1, &guard).unwrap_err();
+        assert_eq!(err.current, None);
+        assert_eq!(err.new, 1);
+        assert!(map.get(&42, &guard).is_none());
+
+        map.insert(42, 0, &guard);
+        assert_eq!(map.compare_exchange(42, &0, 1, &guard), Ok(&1));
+        let err = map.compare_exchange(42, &0, 2, &guard).unwrap_err();
+        assert_eq!(err.current, Some(&1));
+        assert_eq!(err.new, 2);
+        assert_eq!(map.get(&42, &guard), Some(&1));
+    });
+}
+
+#[test]
+fn entry_ref() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let guard = map.guard();
+        assert!(map.get_entry(&42, &guard).is_none());
+
+        map.insert(42, 0, &guard);
+        let entry = map.get_entry(&42, &guard).unwrap();
+        assert_eq!((entry.key(), entry.value()), (&42, &0));
+
+        // An equal value is still a different entry.
+        map.insert(42, 0, &guard);
+        assert_eq!(map.replace_if_unchanged(entry, 42, 1, &guard), Err(1));
+        assert!(!map.remove_if_unchanged(entry, &guard));
+        assert_eq!(map.get(&42, &guard), Some(&0));
+
+        let entry = map.get_entry(&42, &guard).unwrap();
+        assert_eq!(map.replace_if_unchanged(entry, 42, 1, &guard), Ok(&1));
+        assert!(!map.remove_if_unchanged(entry, &guard));
+
+        let entry = map.get_entry(&42, &guard).unwrap();
+        assert!(map.remove_if_unchanged(entry, &guard));
+        assert!(map.get(&42, &guard).is_none());
+        assert_eq!(map.replace_if_unchanged(entry, 42, 2, &guard), Err(2));
+
+        // The entry can still be replaced after it is copied to a new table.
+        map.insert(42, 0, &guard);
+        let entry = map.get_entry(&42, &guard).unwrap();
+        for i in 0..1024 {
+            map.insert(i + 100, i, &guard);
+        }
+        assert_eq!(map.replace_if_unchanged(entry, 42, 1, &guard), Ok(&1));
+
+        // A removed and reinserted key is a different entry.
+        let entry = map.get_entry(&42, &guard).unwrap();
+        map.remove(&42, &guard);
+        map.insert(42, 1, &guard);
+        assert_eq!(map.replace_if_unchanged(entry, 42, 2, &guard), Err(2));
+        assert!(!map.remove_if_unchanged(entry, &guard));
+        assert_eq!(map.get(&42, &guard), Some(&1));
+    });
+}
+
+#[test]
+fn concurrent_entry_ref() {
+    with_map::<usize, usize>(|map| {
+        let map = Arc::new(map());
+        let len = if cfg!(miri) { 16 } else { 1024 };
+        let threads = 4;
+
+        // Every thread increments every key, inserting keys and resizing concurrently.
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let map = map.clone();
+                std::thread::spawn(move || {
+                    let guard = map.guard();
+                    for i in 0..len {
+                        map.try_insert(i, 0, &guard).ok();
+
+                        loop {
+                            let entry = map.get_entry(&i, &guard).unwrap();
+                            let value = *entry.value() + 1;
+                            if map.replace_if_unchanged(entry, i, value, &guard).is_ok() {
+                                break;
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let guard = map.guard();
+        for i in 0..len {
+            assert_eq!(map.get(&i, &guard), Some(&threads));
+        }
+    });
+}
+
+#[test]
+fn get_or_init_once() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let guard = map.guard();
+        assert_eq!(map.get_or_init_once(42, || 0, &guard), &0);
+        assert_eq!(map.get_or_init_once(42, || unreachable!(), &guard), &0);
+        assert_eq!(map.len(), 1);
+    });
+}
+
+#[test]
+fn concurrent_get_or_init_once() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Barrier;
+
+    with_map::<usize, usize>(|map| {
+        let map = Arc::new(map());
+        let len = if cfg!(miri) { 8 } else { 256 };
+        let threads = 4;
+        let calls = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(threads));
+
+        // Every thread races to initialize every key.
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let (map, calls, barrier) = (map.clone(), calls.clone(), barrier.clone());
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    let guard = map.guard();
+                    for i in 0..len {
+                        let value = map.get_or_init_once(
+                            i,
+                            || {
+                                calls.fetch_add(1, Ordering::Relaxed);
+                                std::thread::yield_now();
+                                i + 1
+                            },
+                            &guard,
+                        );
+                        assert_eq!(*value, i + 1);
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(calls.load(Ordering::Relaxed), len);
+        assert_eq!(map.len(), len);
+    });
+}
+
+#[test]
+fn get_or_init_once_panic() {
+    use std::sync::mpsc;
+
+    with_map::<usize, usize>(|map| {
+        let map = Arc::new(map());
+        let (started, wait_started) = mpsc::channel();
+        let (resume, wait_resume) = mpsc::channel::<()>();
+
+        // Start an initializer that panics after another thread starts waiting on it.
+        let map1 = map.clone();
+        let t1 = std::thread::spawn(move || {
+            map1.pin().get_or_init_once(42, || {
+                started.send(()).unwrap();
+                wait_resume.recv().unwrap();
+                panic!("initializer panicked");
+            });
+        });
+
+        wait_started.recv().unwrap();
+
+        let map2 = map.clone();
+        let t2 = std::thread::spawn(move || *map2.pin().get_or_init_once(42, || 1));
+
+        drop(resume);
+        assert!(t1.join().is_err());
+
+        // The waiting thread takes over the initialization.
+        assert_eq!(t2.join().unwrap(), 1);
+        assert_eq!(map.pin().get(&42), Some(&1));
+    });
+}
+
+#[test]
+fn concurrent_compare_exchange() {
+    with_map::<usize, usize>(|map| {
+        let map = Arc::new(map());
+        let len = if cfg!(miri) { 16 } else { 1024 };
+        let threads = 4;
+
+        // Every thread increments every key, inserting keys and resizing concurrently.
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let map = map.clone();
+                std::thread::spawn(move || {
+                    let guard = map.guard();
+                    for i in 0..len {
+                        map.try_insert(i, 0, &guard).ok();
+
+                        let mut current = *map.get(&i, &guard).unwrap();
+                        while let Err(err) = map.compare_exchange(i, &current, current + 1, &guard)
+                        {
+                            current = *err.current.unwrap();
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let guard = map.guard();
+        for i in 0..len {
+            assert_eq!(map.get(&i, &guard), Some(&threads));
+        }
+    });
+}
+
+#[test]
+fn wait_for() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+
+        std::thread::scope(|s| {
+            // The consumer blocks until the producer inserts the key.
+            let consumer = s.spawn(|| *map.pin().wait_for(&42));
+
+            std::thread::sleep(Duration::from_millis(10));
+            map.pin().insert(42, 7);
+
+            assert_eq!(consumer.join().unwrap(), 7);
+        });
+
+        // The key is already present.
+        assert_eq!(map.pin().wait_for(&42), &7);
+    });
+}
+
+#[test]
+fn wait_for_timeout() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let m = map.pin();
+
+        assert_eq!(m.wait_for_timeout(&42, Duration::from_millis(10)), None);
+
+        m.insert(42, 7);
+        assert_eq!(m.wait_for_timeout(&42, Duration::from_millis(10)), Some(&7));
+        assert_eq!(m.wait_for_timeout(&42, Duration::MAX), Some(&7));
+    });
+}
+
+#[test]
+fn concurrent_wait_for() {
+    const CONSUMERS: usize = if cfg!(miri) { 4 } else { 16 };
+
+    with_map::<usize, usize>(|map| {
+        let map = map();
+
+        std::thread::scope(|s| {
+            let consumers: Vec<_> = (0..CONSUMERS)
+                .map(|i| {
+                    let map = &map;
+                    s.spawn(move || *map.pin().wait_for(&i))
+                })
+                .collect();
+
+            for i in 0..CONSUMERS {
+                map.pin().insert(i, i + 1);
+            }
+
+            for (i, consumer) in consumers.into_iter().enumerate() {
+                assert_eq!(consumer.join().unwrap(), i + 1);
+            }
+        });
+    });
+}
+
 #[test]
 fn insert_and_get() {
     with_map::<usize, usize>(|map| {
@@ -270,24 +795,68 @@ fn get_or_insert_with() {
 }
 
 #[test]
-fn compute() {
-    with_map::<usize, usize>(|map| {
+fn get_or_insert_ref() {
+    with_map::<String, usize>(|map| {
         let map = map();
-        let map = map.pin();
+        let guard = map.guard();
 
-        for i in 0..100 {
-            let compute = |entry| match entry {
-                Some((_, value)) if value % 2 == 0 => Operation::Remove,
-                Some((_, value)) => Operation::Insert(value + 1),
-                None => Operation::Abort(()),
-            };
+        // Count the number of times the key is converted.
+        let converted = Cell::new(0);
+        let to_owned = |key: &str| {
+            converted.set(converted.get() + 1);
+            key.to_owned()
+        };
 
-            assert_eq!(map.len(), 0);
-            assert_eq!(map.compute(i, compute), Compute::Aborted(()));
-            assert_eq!(map.get(&i), None);
+        let result = map.get_or_insert_ref("a", to_owned, 0, &guard);
+        assert_eq!(result, &0);
+        assert_eq!(converted.get(), 1);
 
-            map.compute(i, |_| Operation::Insert::<_, ()>(1));
-            assert_eq!(map.len(), 1);
+        let result = map.get_or_insert_with_ref("a", to_owned, || 1, &guard);
+        assert_eq!(result, &0);
+        assert_eq!(converted.get(), 1);
+
+        let result = map.get_or_insert_with_ref("b", to_owned, || 1, &guard);
+        assert_eq!(result, &1);
+        assert_eq!(converted.get(), 2);
+        assert_eq!(map.len(), 2);
+
+        let result = map.update_or_insert_ref("c", to_owned, |v| v + 1, 2, &guard);
+        assert_eq!(result, &2);
+        assert_eq!(converted.get(), 3);
+
+        let result = map.update_or_insert_with_ref("c", to_owned, |v| v + 1, || 2, &guard);
+        assert_eq!(result, &3);
+        assert_eq!(converted.get(), 4);
+        assert_eq!(map.len(), 3);
+
+        {
+            let guard = map.guard();
+            assert_eq!(map.get("a", &guard), Some(&0));
+            assert_eq!(map.get("b", &guard), Some(&1));
+            assert_eq!(map.get("c", &guard), Some(&3));
+        }
+    });
+}
+
+#[test]
+fn compute() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let map = map.pin();
+
+        for i in 0..100 {
+            let compute = |entry| match entry {
+                Some((_, value)) if value % 2 == 0 => Operation::Remove,
+                Some((_, value)) => Operation::Insert(value + 1),
+                None => Operation::Abort(()),
+            };
+
+            assert_eq!(map.len(), 0);
+            assert_eq!(map.compute(i, compute), Compute::Aborted(()));
+            assert_eq!(map.get(&i), None);
+
+            map.compute(i, |_| Operation::Insert::<_, ()>(1));
+            assert_eq!(map.len(), 1);
 
             assert_eq!(
                 map.compute(i, compute),
@@ -473,6 +1042,345 @@ fn concurrent_resize_and_get() {
     });
 }
 
+#[test]
+fn shrink_to_fit() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        for i in 10..len {
+            assert_eq!(map.pin().remove(&i), Some(&i));
+        }
+
+        map.pin().shrink_to_fit();
+        assert_eq!(map.len(), 10);
+
+        for i in 0..len {
+            assert_eq!(map.pin().get(&i), (i < 10).then_some(&i));
+        }
+
+        // The map is still usable after shrinking.
+        for i in 10..len {
+            map.pin().insert(i, i);
+        }
+
+        map.pin().shrink_to(len * 2);
+
+        for i in 0..len {
+            assert_eq!(map.pin().get(&i), Some(&i));
+        }
+    });
+}
+
+#[test]
+fn shrink_threshold() {
+    let len = if cfg!(miri) { 100 } else { 10_000 };
+
+    for resize in [ResizeMode::Blocking, ResizeMode::Incremental(64)] {
+        let map = HashMap::builder()
+            .resize_mode(resize)
+            .shrink_mode(ShrinkMode::Threshold(0.25))
+            .build();
+
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        for i in 10..len {
+            assert_eq!(map.pin().remove(&i), Some(&i));
+        }
+
+        assert_eq!(map.len(), 10);
+        for i in 0..len {
+            assert_eq!(map.pin().get(&i), (i < 10).then_some(&i));
+        }
+
+        map.pin().clear();
+        assert!(map.is_empty());
+    }
+}
+
+#[test]
+fn shrink_threshold_uneven_shards() {
+    let len = if cfg!(miri) { 1000 } else { 10_000 };
+
+    let map = HashMap::builder()
+        .resize_mode(ResizeMode::Blocking)
+        .shrink_mode(ShrinkMode::Threshold(0.25))
+        .build();
+
+    for i in 0..len {
+        map.pin().insert(i, i);
+    }
+
+    let capacity = map.stats(&map.guard()).capacity;
+
+    // The entries were all counted by the main thread, so remove them from threads with
+    // uneven shares of the removals.
+    let bounds = [10, len / 2, len * 3 / 4, len * 7 / 8, len];
+    std::thread::scope(|s| {
+        for range in bounds.windows(2) {
+            let map = &map;
+            s.spawn(move || {
+                for i in range[0]..range[1] {
+                    assert_eq!(map.pin().remove(&i), Some(&i));
+                }
+            });
+        }
+    });
+
+    assert_eq!(map.len(), 10);
+    assert!(map.stats(&map.guard()).capacity < capacity);
+    for i in 0..len {
+        assert_eq!(map.pin().get(&i), (i < 10).then_some(&i));
+    }
+}
+
+#[test]
+#[should_panic]
+fn shrink_threshold_invalid() {
+    let _map: HashMap<usize, usize> = HashMap::builder()
+        .shrink_mode(ShrinkMode::Threshold(0.75))
+        .build();
+}
+
+#[test]
+fn compact() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        for i in 0..len / 2 {
+            assert_eq!(map.pin().remove(&i), Some(&i));
+        }
+
+        // Complete any in-progress resizes.
+        assert_eq!(map.pin().iter().count(), len / 2);
+
+        let stats = map.pin().stats();
+        assert!(stats.tombstones > 0);
+
+        map.pin().compact();
+
+        let compacted = map.pin().stats();
+        assert_eq!(compacted.tombstones, 0);
+        assert_eq!(compacted.capacity, stats.capacity);
+        assert_eq!(compacted.resizes, stats.resizes);
+        assert_eq!(compacted.compactions, stats.compactions + 1);
+
+        assert_eq!(map.len(), len / 2);
+        for i in 0..len {
+            assert_eq!(map.pin().get(&i), (i >= len / 2).then_some(&i));
+        }
+
+        // There is nothing left to compact.
+        map.pin().compact();
+        assert_eq!(map.pin().stats().compactions, compacted.compactions);
+    });
+}
+
+#[test]
+fn compact_threshold() {
+    let len = if cfg!(miri) { 1_000 } else { 100_000 };
+
+    for resize in [ResizeMode::Blocking, ResizeMode::Incremental(64)] {
+        let map = HashMap::builder()
+            .resize_mode(resize)
+            .capacity(1000)
+            .compact_mode(CompactMode::Threshold(0.25))
+            .build();
+
+        let capacity = map.pin().stats().capacity;
+
+        // Churn through batches of keys without growing the map.
+        //
+        // Inserts reuse tombstones in their probe sequence, so we remove entire batches at
+        // once to leave tombstones behind.
+        for batch in (0..len).collect::<Vec<_>>().chunks(600) {
+            for &i in batch {
+                map.pin().insert(i, i);
+            }
+            for &i in batch {
+                assert_eq!(map.pin().remove(&i), Some(&i));
+            }
+
+            // Tombstones are removed before they can accumulate.
+            let stats = map.pin().stats();
+            assert!(stats.pending_tables > 0 || stats.tombstones <= capacity / 2);
+        }
+
+        let stats = map.pin().stats();
+        assert!(map.is_empty());
+        assert_eq!(stats.capacity, capacity);
+        assert!(stats.compactions > 0);
+    }
+}
+
+#[test]
+fn tombstone_reuse() {
+    let (capacity, live) = if cfg!(miri) { (128, 32) } else { (1024, 256) };
+
+    for resize in [ResizeMode::Blocking, ResizeMode::Incremental(64)] {
+        let map = HashMap::builder()
+            .resize_mode(resize)
+            .capacity(capacity)
+            .build();
+
+        for i in 0..live {
+            map.pin().insert(i, i);
+        }
+
+        let stats = map.pin().stats();
+
+        // Cycle through distinct keys, keeping the length of the map constant.
+        for i in 0..stats.capacity {
+            assert_eq!(map.pin().remove(&i), Some(&i));
+            assert_eq!(map.pin().insert(i + live, i + live), None);
+        }
+
+        // Deleted slots were reused instead of exhausting the probe budget.
+        let churned = map.pin().stats();
+        assert_eq!(churned.resizes, stats.resizes);
+        assert_eq!(churned.compactions, stats.compactions);
+        assert_eq!(churned.capacity, stats.capacity);
+        assert!(churned.tombstones < stats.capacity - live);
+
+        assert_eq!(map.len(), live);
+        for i in 0..stats.capacity + live {
+            assert_eq!(map.pin().get(&i), (i >= stats.capacity).then_some(&i));
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn compact_threshold_invalid() {
+    let _map: HashMap<usize, usize> = HashMap::builder()
+        .compact_mode(CompactMode::Threshold(1.5))
+        .build();
+}
+
+#[test]
+fn default_growth() {
+    let len = if cfg!(miri) { 100 } else { 10_000 };
+
+    for mode in [ResizeMode::Blocking, ResizeMode::Incremental(1)] {
+        let map = HashMap::builder().resize_mode(mode).build();
+
+        let mut capacity = 0;
+        let mut growth = Vec::new();
+        for i in 0..len {
+            map.pin().insert(i, i);
+
+            let stats = map.pin().stats();
+            if stats.capacity != capacity {
+                capacity = stats.capacity;
+                growth.push((i, capacity));
+            }
+        }
+
+        // Without a configured load factor or maximum probe length, tables start at the
+        // minimum capacity and are only ever doubled, once they are at least half full.
+        assert_eq!(growth[0], (0, 32));
+        for window in growth.windows(2) {
+            let ((_, old), (i, new)) = (window[0], window[1]);
+            assert_eq!(new, old * 2);
+            assert!(i >= old / 2);
+        }
+
+        // The last resize may still be in progress in incremental mode.
+        assert!(capacity * 2 >= len);
+    }
+}
+
+#[test]
+fn load_factor() {
+    let capacity = |map: HashMap<usize, usize>| map.stats(&map.guard()).capacity;
+
+    // Tables are sized for the configured load factor.
+    assert_eq!(capacity(HashMap::builder().capacity(1600).build()), 4096);
+    assert_eq!(
+        capacity(HashMap::builder().capacity(1600).load_factor(0.9).build()),
+        2048
+    );
+
+    let map = HashMap::<usize, usize>::builder().load_factor(0.9).build();
+    map.pin().reserve(1600);
+    assert_eq!(capacity(map), 2048);
+
+    // The probe limit scales with the load factor.
+    let limit = |load_factor| {
+        let map = HashMap::<usize, usize>::builder()
+            .capacity(1024)
+            .load_factor(load_factor)
+            .build();
+        let stats = map.stats(&map.guard());
+        stats.probe_limit
+    };
+    assert!(limit(0.5) < limit(0.75));
+    assert!(limit(0.75) < limit(0.9));
+
+    let len = if cfg!(miri) { 100 } else { 10_000 };
+    for load_factor in [0.25, 0.5, 0.9] {
+        for mode in [ResizeMode::Blocking, ResizeMode::Incremental(1)] {
+            let map = HashMap::builder()
+                .resize_mode(mode)
+                .load_factor(load_factor)
+                .build();
+
+            for i in 0..len {
+                map.pin().insert(i, i);
+            }
+            for i in 0..len {
+                assert_eq!(map.pin().get(&i), Some(&i));
+            }
+        }
+    }
+}
+
+#[test]
+fn max_probe() {
+    let len = if cfg!(miri) { 100 } else { 10_000 };
+
+    for mode in [ResizeMode::Blocking, ResizeMode::Incremental(1)] {
+        let map = HashMap::builder().resize_mode(mode).max_probe(4).build();
+
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        // Complete any in-progress resizes.
+        assert_eq!(map.pin().iter().count(), len);
+
+        let stats = map.pin().stats();
+        assert_eq!(stats.probe_limit, 4);
+        assert_eq!(stats.probe_lengths.len(), 5);
+
+        for i in 0..len {
+            assert_eq!(map.pin().get(&i), Some(&i));
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn load_factor_invalid() {
+    let _map: HashMap<usize, usize> = HashMap::builder().load_factor(1.0).build();
+}
+
+#[test]
+#[should_panic]
+fn max_probe_invalid() {
+    let _map: HashMap<usize, usize> = HashMap::builder().max_probe(0).build();
+}
+
 #[test]
 fn current_kv_dropped() {
     let dropped1 = Arc::new(0);
@@ -703,6 +1611,546 @@ fn iter() {
     });
 }
 
+#[test]
+fn iter_shards() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            assert_eq!(map.pin().insert(i, i + 1), None);
+        }
+
+        let v: Vec<_> = (0..len).map(|i| (i, i + 1)).collect();
+        for shards in [1, 3, 8, 1000] {
+            let m = map.pin();
+            assert_eq!(m.iter_shards(shards).len(), shards);
+
+            let mut got: Vec<_> = m
+                .iter_shards(shards)
+                .flatten()
+                .map(|(&k, &v)| (k, v))
+                .collect();
+            got.sort();
+            assert_eq!(v, got);
+        }
+
+        // Shards of an empty map are empty.
+        let empty = HashMap::<usize, usize>::new();
+        assert_eq!(empty.pin().iter_shards(2).flatten().count(), 0);
+    });
+}
+
+#[test]
+fn iter_shards_resize() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        let guard = map.guard();
+        let mut shards = map.iter_shards(4, &guard);
+        let mut got: Vec<_> = shards.next().unwrap().map(|(&k, _)| k).collect();
+
+        // Resize the map before the remaining shards are created.
+        let capacity = map.stats(&guard).capacity;
+        for i in len..len * 4 {
+            map.pin().insert(i, i);
+        }
+        assert_eq!(map.pin().iter().count(), len * 4);
+        assert!(map.stats(&guard).capacity > capacity);
+
+        // The shards still cover the original table exactly once.
+        got.extend(shards.flatten().map(|(&k, _)| k).filter(|&k| k < len));
+        got.sort();
+        assert_eq!(got, (0..len).collect::<Vec<_>>());
+    });
+}
+
+#[test]
+#[should_panic]
+fn iter_shards_zero() {
+    let map = HashMap::<usize, usize>::new();
+    map.pin().iter_shards(0);
+}
+
+#[test]
+fn iter_split() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            assert_eq!(map.pin().insert(i, i + 1), None);
+        }
+
+        // Recursively split the iterator as far as possible.
+        let m = map.pin();
+        let mut iters = vec![m.iter()];
+        let mut i = 0;
+        while i < iters.len() {
+            while let Some(split) = iters[i].split() {
+                iters.push(split);
+            }
+            i += 1;
+        }
+
+        if !cfg!(miri) {
+            assert!(iters.len() > 1);
+        }
+
+        let v: Vec<_> = (0..len).map(|i| (i, i + 1)).collect();
+        let mut got: Vec<_> = iters.into_iter().flatten().map(|(&k, &v)| (k, v)).collect();
+        got.sort();
+        assert_eq!(v, got);
+    });
+}
+
+#[test]
+fn iter_split_concurrent_resize() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        let guard = map.owned_guard();
+        let mut first = map.iter(&guard);
+        let second = first.split();
+
+        // Resize the map after splitting.
+        for i in len..len * 4 {
+            map.pin().insert(i, i);
+        }
+
+        // The split iterators still observe the original table.
+        let mut got: Vec<_> = first
+            .chain(second.into_iter().flatten())
+            .map(|(&k, _)| k)
+            .collect();
+        got.sort();
+
+        // Every original entry is visited exactly once.
+        let original: Vec<_> = got.iter().copied().filter(|&k| k < len).collect();
+        assert_eq!(original, (0..len).collect::<Vec<_>>());
+        assert!(got.windows(2).all(|w| w[0] != w[1]));
+    });
+}
+
+#[test]
+fn iter_from() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        // Visit the map in steps, each with a fresh guard.
+        let mut got = Vec::new();
+        let mut cursor = Cursor::default();
+        loop {
+            let guard = map.guard();
+            let mut iter = map.iter_from(cursor, &guard);
+            let step: Vec<_> = iter.by_ref().take(7).map(|(&k, _)| k).collect();
+            if step.is_empty() {
+                break;
+            }
+
+            got.extend(step);
+            cursor = iter.cursor();
+        }
+
+        // Without a resize, every entry is visited exactly once.
+        got.sort();
+        assert_eq!(got, (0..len).collect::<Vec<_>>());
+
+        // Resuming from an exhausted cursor yields nothing.
+        assert_eq!(map.pin().iter_from(cursor).count(), 0);
+    });
+}
+
+#[test]
+fn iter_from_resize() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        let cursor = {
+            let m = map.pin();
+            let mut iter = m.iter();
+            iter.by_ref().take(len / 2).for_each(drop);
+            iter.cursor()
+        };
+
+        // Resize the map between steps.
+        for i in len..len * 4 {
+            map.pin().insert(i, i);
+        }
+
+        // Iteration restarts from the beginning of the new table.
+        let mut got: Vec<_> = map.pin().iter_from(cursor).map(|(&k, _)| k).collect();
+        got.sort();
+        assert_eq!(got, (0..len * 4).collect::<Vec<_>>());
+    });
+}
+
+#[test]
+fn iter_incremental() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        // Any in-progress resize is left in place.
+        let mut got: Vec<_> = map
+            .pin()
+            .iter_incremental()
+            .map(|(&k, &v)| (k, v))
+            .collect();
+        got.sort();
+        assert_eq!(got, (0..len).map(|i| (i, i)).collect::<Vec<_>>());
+    });
+}
+
+#[test]
+fn iter_incremental_concurrent_resize() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        std::thread::scope(|s| {
+            // Resize the map and update the original entries during iteration.
+            s.spawn(|| {
+                let m = map.pin();
+                for i in len..len * 4 {
+                    m.insert(i, i);
+                    m.insert(i - len, i);
+                }
+            });
+
+            for _ in 0..if cfg!(miri) { 1 } else { 8 } {
+                let mut got: Vec<_> = map
+                    .pin()
+                    .iter_incremental()
+                    .map(|(&k, _)| k)
+                    .filter(|&k| k < len)
+                    .collect();
+                got.sort();
+
+                // Every original entry is visited exactly once.
+                assert_eq!(got, (0..len).collect::<Vec<_>>());
+            }
+        });
+    });
+}
+
+#[test]
+fn stats() {
+    with_map::<usize, usize>(|map| {
+        let stats = map().pin().stats();
+        assert_eq!(stats.len, 0);
+        assert_eq!(stats.capacity, 0);
+        assert!(stats.probe_lengths.is_empty());
+
+        // The probe length histogram is exact for small tables, and estimated otherwise.
+        let assert_probe_lengths = |stats: &MapStats, len: usize| {
+            let sum = stats.probe_lengths.iter().sum::<usize>();
+            if stats.capacity <= 1024 {
+                assert_eq!(sum, len);
+            } else {
+                assert!(sum.abs_diff(len) <= len / 4);
+            }
+        };
+
+        for len in [100, if cfg!(miri) { 200 } else { 10_000 }] {
+            let map = map();
+            let map = map.pin();
+            for i in 0..len {
+                map.insert(i, i);
+            }
+
+            // Complete any in-progress resizes.
+            assert_eq!(map.iter().count(), len);
+
+            let stats = map.stats();
+            assert_eq!(stats.len, len);
+            assert_eq!(stats.tombstones, 0);
+            assert_eq!(stats.pending_tables, 0);
+            assert_eq!((stats.copied, stats.claimed), (0, 0));
+            assert_eq!(stats.load_factor, len as f64 / stats.capacity as f64);
+            assert_eq!(stats.probe_lengths.len(), stats.probe_limit + 1);
+            assert_probe_lengths(&stats, len);
+
+            for i in 0..len / 2 {
+                map.remove(&i);
+            }
+
+            let stats = map.stats();
+            assert_eq!(stats.len, len - len / 2);
+            assert_eq!(stats.tombstones, len / 2);
+            assert_probe_lengths(&stats, len - len / 2);
+        }
+    });
+}
+
+#[test]
+fn stats_resizes() {
+    let len = if cfg!(miri) { 100 } else { 10_000 };
+
+    for mode in [ResizeMode::Blocking, ResizeMode::Incremental(1)] {
+        let map = HashMap::builder().resize_mode(mode).build();
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        // Complete any in-progress resizes.
+        assert_eq!(map.pin().iter().count(), len);
+
+        let stats = map.pin().stats();
+        assert!(stats.resizes > 0);
+        assert_eq!(stats.shrinks, 0);
+
+        // Only growing the table is counted as a resize.
+        for i in 0..len - 10 {
+            map.pin().remove(&i);
+        }
+
+        map.pin().compact();
+        map.pin().shrink_to_fit();
+
+        let shrunk = map.pin().stats();
+        assert_eq!(shrunk.resizes, stats.resizes);
+        assert_eq!(shrunk.shrinks, 1);
+        assert_eq!(shrunk.compactions, stats.compactions + 1);
+
+        map.pin().reserve(len);
+        assert_eq!(map.pin().stats().resizes, stats.resizes + 1);
+    }
+}
+
+#[test]
+fn stats_retired() {
+    with_map::<usize, usize>(|map| {
+        let (map, other) = (map(), map());
+        other.pin().insert(0, 0);
+
+        // Old tables are retired while a guard is held.
+        let guard = map.guard();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.insert(i, i, &guard);
+        }
+
+        // Complete any in-progress resizes.
+        assert_eq!(map.pin().iter().count(), len);
+
+        assert!(map.stats(&guard).retired_bytes > 0);
+        assert_eq!(other.pin().stats().retired_bytes, 0);
+    });
+}
+
+#[test]
+fn stats_retired_entries() {
+    let len = if cfg!(miri) { 100 } else { 10_000 };
+
+    let map = HashMap::builder().capacity(len * 2).build();
+    for i in 0..len {
+        map.pin().insert(i, i);
+    }
+
+    // Removed entries are retired while a guard is held.
+    let guard = map.guard();
+    for i in 0..len / 2 {
+        map.remove(&i, &guard);
+    }
+
+    let size = std::mem::size_of::<(usize, usize)>();
+    let retired = map.stats(&guard).retired_bytes;
+    assert!(retired >= len / 2 * size);
+
+    // Replaced entries are retired as well.
+    for i in len / 2..len {
+        map.insert(i, i + 1, &guard);
+    }
+
+    assert!(map.stats(&guard).retired_bytes >= retired + len / 2 * size);
+}
+
+#[test]
+fn stats_concurrent_resize() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for i in 0..len {
+                    map.pin().insert(i, i);
+                }
+            });
+
+            for _ in 0..if cfg!(miri) { 4 } else { 100 } {
+                let stats = map.pin().stats();
+                assert!(stats.copied <= stats.capacity);
+                assert!(stats.claimed <= stats.capacity);
+                assert!(stats.tombstones <= stats.capacity);
+                assert!(stats.probe_lengths.iter().sum::<usize>() <= stats.capacity);
+            }
+        });
+
+        assert_eq!(map.pin().stats().len, len);
+    });
+}
+
+#[test]
+fn get_mut() {
+    with_map::<usize, usize>(|map| {
+        let mut map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        for i in 0..len {
+            *map.get_mut(&i).unwrap() += 1;
+        }
+        assert_eq!(map.get_mut(&len), None);
+
+        let guard = map.guard();
+        for i in 0..len {
+            assert_eq!(map.get(&i, &guard), Some(&(i + 1)));
+        }
+    });
+}
+
+#[test]
+fn iter_mut() {
+    with_map::<usize, usize>(|map| {
+        let mut map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            map.pin().insert(i, i);
+        }
+
+        for (k, v) in map.iter_mut() {
+            assert_eq!(k, v);
+            *v += 1;
+        }
+        for v in map.values_mut() {
+            *v *= 2;
+        }
+
+        let v: Vec<_> = (0..len).map(|i| (i, (i + 1) * 2)).collect();
+        let mut got: Vec<_> = map.pin().iter().map(|(&k, &v)| (k, v)).collect();
+        got.sort();
+        assert_eq!(v, got);
+    });
+}
+
+#[test]
+fn into_iter() {
+    with_map::<usize, Arc<usize>>(|map| {
+        let map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        let value = Arc::new(0);
+        for i in 0..len {
+            map.pin().insert(i, value.clone());
+        }
+
+        let mut got: Vec<_> = map.into_iter().map(|(k, _)| k).collect();
+        got.sort();
+        assert_eq!(got, (0..len).collect::<Vec<_>>());
+        assert_eq!(Arc::strong_count(&value), 1);
+    });
+}
+
+#[test]
+fn into_iter_partial() {
+    with_map::<usize, Arc<usize>>(|map| {
+        let map = map();
+        let value = Arc::new(0);
+        for i in 0..64 {
+            map.pin().insert(i, value.clone());
+        }
+
+        // The remaining entries are dropped with the iterator.
+        let mut iter = map.into_iter();
+        let (_, first) = iter.next().unwrap();
+        drop(iter);
+        assert_eq!(Arc::strong_count(&value), 2);
+
+        drop(first);
+        assert_eq!(Arc::strong_count(&value), 1);
+    });
+}
+
+#[test]
+fn drain() {
+    with_map::<usize, Arc<usize>>(|map| {
+        let mut map = map();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        let value = Arc::new(0);
+        for i in 0..len {
+            map.pin().insert(i, value.clone());
+        }
+
+        let mut got: Vec<_> = map.drain().map(|(k, _)| k).collect();
+        got.sort();
+        assert_eq!(got, (0..len).collect::<Vec<_>>());
+        assert_eq!(Arc::strong_count(&value), 1);
+        assert!(map.is_empty());
+
+        // Dropping the iterator early removes the remaining entries.
+        for i in 0..len {
+            map.pin().insert(i, value.clone());
+        }
+        assert!(map.drain().next().is_some());
+        assert!(map.is_empty());
+        assert_eq!(Arc::strong_count(&value), 1);
+
+        // The map is still usable after being drained.
+        let guard = map.guard();
+        for i in 0..len {
+            assert!(map.get(&i, &guard).is_none());
+            map.insert(i, value.clone(), &guard);
+        }
+        assert_eq!(map.len(), len);
+    });
+}
+
+#[test]
+fn drain_compact() {
+    with_map::<usize, usize>(|map| {
+        let mut map = map();
+        for i in 0..500 {
+            map.pin().insert(i, i);
+        }
+
+        // Remove some entries before draining to leave tombstones behind.
+        for i in 0..100 {
+            assert_eq!(map.pin().remove(&i), Some(&i));
+        }
+
+        assert_eq!(map.drain().count(), 400);
+        assert_eq!(map.pin().stats().tombstones, 0);
+
+        // There is nothing left to compact after draining.
+        let compactions = map.pin().stats().compactions;
+        map.pin().compact();
+        assert_eq!(map.pin().stats().compactions, compactions);
+    });
+}
+
 #[test]
 fn mixed() {
     const LEN: usize = if cfg!(miri) { 48 } else { 1024 };
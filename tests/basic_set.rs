@@ -0,0 +1,318 @@
+use papaya::HashSet;
+
+use std::sync::Arc;
+
+mod common;
+use common::with_set;
+
+#[test]
+fn new() {
+    with_set::<usize>(|set| drop(set()));
+}
+
+#[test]
+fn clear() {
+    with_set::<usize>(|set| {
+        let set = set();
+        let guard = set.guard();
+        {
+            set.insert(0, &guard);
+            set.insert(1, &guard);
+            set.insert(2, &guard);
+            set.insert(3, &guard);
+            set.insert(4, &guard);
+        }
+        set.clear(&guard);
+        assert!(set.is_empty());
+    });
+}
+
+#[test]
+fn retain() {
+    with_set::<usize>(|set| {
+        let set = set();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            set.pin().insert(i);
+        }
+
+        set.pin().retain(|k| k % 3 == 0);
+
+        let guard = set.guard();
+        assert_eq!(set.len(), len.div_ceil(3));
+        for i in 0..len {
+            assert_eq!(set.contains(&i, &guard), i % 3 == 0);
+        }
+    });
+}
+
+#[test]
+fn insert() {
+    with_set::<usize>(|set| {
+        let set = set();
+        let guard = set.guard();
+        assert!(set.insert(42, &guard));
+        assert!(!set.insert(42, &guard));
+        assert_eq!(set.len(), 1);
+    });
+}
+
+#[test]
+fn get_empty() {
+    with_set::<usize>(|set| {
+        let set = set();
+        let guard = set.guard();
+        assert!(set.get(&42, &guard).is_none());
+        assert!(!set.contains(&42, &guard));
+    });
+}
+
+#[test]
+fn remove_empty() {
+    with_set::<usize>(|set| {
+        let set = set();
+        let guard = set.guard();
+        assert!(!set.remove(&42, &guard));
+    });
+}
+
+#[test]
+fn insert_and_remove() {
+    with_set::<usize>(|set| {
+        let set = set();
+        let guard = set.guard();
+        set.insert(42, &guard);
+        assert!(set.remove(&42, &guard));
+        assert!(set.get(&42, &guard).is_none());
+    });
+}
+
+#[test]
+fn insert_and_get() {
+    with_set::<usize>(|set| {
+        let set = set();
+        set.insert(42, &set.guard());
+
+        {
+            let guard = set.guard();
+            assert_eq!(set.get(&42, &guard), Some(&42));
+            assert!(set.contains(&42, &guard));
+        }
+    });
+}
+
+#[test]
+fn concurrent_insert() {
+    with_set::<usize>(|set| {
+        let set = set();
+        let set = Arc::new(set);
+
+        let set1 = set.clone();
+        let t1 = std::thread::spawn(move || {
+            for i in 0..64 {
+                set1.insert(i, &set1.guard());
+            }
+        });
+        let set2 = set.clone();
+        let t2 = std::thread::spawn(move || {
+            for i in 0..64 {
+                set2.insert(i, &set2.guard());
+            }
+        });
+
+        t1.join().unwrap();
+        t2.join().unwrap();
+
+        let guard = set.guard();
+        for i in 0..64 {
+            assert!(set.contains(&i, &guard));
+        }
+        assert_eq!(set.len(), 64);
+    });
+}
+
+#[test]
+fn concurrent_remove() {
+    with_set::<usize>(|set| {
+        let set = set();
+        let set = Arc::new(set);
+
+        {
+            let guard = set.guard();
+            for i in 0..64 {
+                set.insert(i, &guard);
+            }
+        }
+
+        let set1 = set.clone();
+        let t1 = std::thread::spawn(move || {
+            let guard = set1.guard();
+            (0..64).filter(|i| set1.remove(i, &guard)).count()
+        });
+        let set2 = set.clone();
+        let t2 = std::thread::spawn(move || {
+            let guard = set2.guard();
+            (0..64).filter(|i| set2.remove(i, &guard)).count()
+        });
+
+        // Every key is removed exactly once.
+        assert_eq!(t1.join().unwrap() + t2.join().unwrap(), 64);
+        assert!(set.is_empty());
+    });
+}
+
+#[test]
+fn current_key_dropped() {
+    let dropped = Arc::new(0);
+
+    with_set::<Arc<usize>>(|set| {
+        let set = set();
+        set.insert(dropped.clone(), &set.guard());
+        assert_eq!(Arc::strong_count(&dropped), 2);
+
+        drop(set);
+
+        // dropping the set should immediately drop (not deferred) all keys
+        assert_eq!(Arc::strong_count(&dropped), 1);
+    });
+}
+
+#[test]
+fn sets_equal() {
+    with_set::<usize>(|set1| {
+        let set1 = set1();
+        with_set::<usize>(|set2| {
+            let set2 = set2();
+            assert_eq!(set1, set2);
+
+            set1.pin().insert(1);
+            assert_ne!(set1, set2);
+
+            set2.pin().insert(1);
+            assert_eq!(set1, set2);
+
+            set2.pin().insert(2);
+            assert_ne!(set1, set2);
+
+            set1.pin().clear();
+            set2.pin().clear();
+        });
+    });
+}
+
+#[test]
+fn clone_set_filled() {
+    with_set::<&'static str>(|set| {
+        let set = set();
+        set.insert("FooKey", &set.guard());
+        set.insert("BarKey", &set.guard());
+        let cloned_set = set.clone();
+        assert_eq!(set.len(), cloned_set.len());
+        assert_eq!(&set, &cloned_set);
+
+        // test that we are not mapping the same tables
+        set.insert("NewItem", &set.guard());
+        assert_ne!(&set, &cloned_set);
+    });
+}
+
+#[test]
+fn debug() {
+    with_set::<usize>(|set| {
+        let set = set();
+        let guard = set.guard();
+        set.insert(42, &guard);
+        set.insert(16, &guard);
+
+        let formatted = format!("{:?}", set);
+
+        assert!(formatted == "{42, 16}" || formatted == "{16, 42}");
+    });
+}
+
+#[test]
+fn extend() {
+    if cfg!(papaya_stress) {
+        return;
+    }
+
+    with_set::<usize>(|set| {
+        let set = set();
+        let guard = set.guard();
+
+        let mut entries: Vec<usize> = vec![42, 16, 38];
+        entries.sort_unstable();
+
+        (&set).extend(entries.clone());
+
+        let mut collected: Vec<usize> = set.iter(&guard).copied().collect();
+        collected.sort_unstable();
+
+        assert_eq!(entries, collected);
+    });
+}
+
+#[test]
+fn from_iter_empty() {
+    let entries: Vec<usize> = Vec::new();
+    let set: HashSet<usize> = HashSet::from_iter(entries);
+
+    assert_eq!(set.len(), 0)
+}
+
+#[test]
+fn iter() {
+    if cfg!(papaya_stress) {
+        return;
+    }
+
+    with_set::<usize>(|set| {
+        let set = set();
+        let len = if cfg!(miri) { 100 } else { 10_000 };
+        for i in 0..len {
+            assert!(set.pin().insert(i));
+        }
+
+        let v: Vec<_> = (0..len).collect();
+        let mut got: Vec<_> = set.pin().iter().copied().collect();
+        got.sort();
+        assert_eq!(v, got);
+    });
+}
+
+#[test]
+fn set_algebra() {
+    with_set::<usize>(|a| {
+        let a = a();
+        with_set::<usize>(|b| {
+            let b = b();
+            let len = if cfg!(miri) { 64 } else { 1024 };
+
+            // `a` holds the multiples of 2 and `b` the multiples of 3.
+            for i in 0..len {
+                a.pin().insert(i * 2);
+                b.pin().insert(i * 3);
+            }
+
+            let (a, b) = (a.pin(), b.pin());
+
+            let mut union: Vec<_> = a.union(&b).copied().collect();
+            union.sort();
+            let mut expected: Vec<_> = (0..len * 3)
+                .filter(|i| (i % 2 == 0 && *i < len * 2) || i % 3 == 0)
+                .collect();
+            expected.sort();
+            assert_eq!(union, expected);
+
+            let mut intersection: Vec<_> = a.intersection(&b).copied().collect();
+            intersection.sort();
+            let expected: Vec<_> = (0..len * 2).filter(|i| i % 6 == 0).collect();
+            assert_eq!(intersection, expected);
+
+            let mut difference: Vec<_> = a.difference(&b).copied().collect();
+            difference.sort();
+            let expected: Vec<_> = (0..len * 2).filter(|i| i % 2 == 0 && i % 3 != 0).collect();
+            assert_eq!(difference, expected);
+        });
+    });
+}
@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use papaya::{HashMap, ResizeMode};
+use papaya::{EntryPool, HashMap, HashSet, ResizeMode};
 
 // Run the test on different configurations of a `HashMap`.
 pub fn with_map<K, V>(mut test: impl FnMut(&dyn Fn() -> HashMap<K, V>)) {
@@ -27,6 +27,53 @@ pub fn with_map<K, V>(mut test: impl FnMut(&dyn Fn() -> HashMap<K, V>)) {
                 .build()
         }),
     );
+
+    // Incremental resize mode with pooled entries, to stress entry reuse across threads.
+    test(
+        &(|| {
+            HashMap::builder()
+                .resize_mode(ResizeMode::Incremental(128))
+                .entry_pool(EntryPool::PerThread(64))
+                .build()
+        }),
+    );
+}
+
+// Run the test on different configurations of a `HashSet`.
+pub fn with_set<K>(mut test: impl FnMut(&dyn Fn() -> HashSet<K>)) {
+    // Blocking resize mode.
+    if !cfg!(papaya_stress) {
+        test(&(|| HashSet::builder().resize_mode(ResizeMode::Blocking).build()));
+    }
+
+    // Incremental resize mode with a small chunk to stress operations on nested tables.
+    test(
+        &(|| {
+            HashSet::builder()
+                .resize_mode(ResizeMode::Incremental(1))
+                .build()
+        }),
+    );
+
+    // Incremental resize mode with a medium-sized chunk to promote interference with incremental
+    // resizing.
+    test(
+        &(|| {
+            HashSet::builder()
+                .resize_mode(ResizeMode::Incremental(128))
+                .build()
+        }),
+    );
+
+    // Incremental resize mode with pooled entries, to stress entry reuse across threads.
+    test(
+        &(|| {
+            HashSet::builder()
+                .resize_mode(ResizeMode::Incremental(128))
+                .entry_pool(EntryPool::PerThread(64))
+                .build()
+        }),
+    );
 }
 
 // Prints a log message if `RUST_LOG=debug` is set.
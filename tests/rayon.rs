@@ -0,0 +1,133 @@
+#![cfg(feature = "rayon")]
+
+use papaya::HashMap;
+use rayon::prelude::*;
+
+mod common;
+use common::with_map;
+
+const ENTRIES: usize = if cfg!(miri) { 128 } else { 100_000 };
+
+#[test]
+fn par_iter() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let m = map.pin_owned();
+        for i in 0..ENTRIES {
+            m.insert(i, i + 1);
+        }
+
+        let mut entries: Vec<_> = m.par_iter().map(|(&k, &v)| (k, v)).collect();
+        entries.sort_unstable();
+        assert_eq!(
+            entries,
+            (0..ENTRIES).map(|i| (i, i + 1)).collect::<Vec<_>>()
+        );
+
+        let mut keys: Vec<_> = m.par_keys().copied().collect();
+        keys.sort_unstable();
+        assert_eq!(keys, (0..ENTRIES).collect::<Vec<_>>());
+
+        let sum: usize = m.par_values().sum();
+        assert_eq!(sum, (1..=ENTRIES).sum());
+
+        let count = (&m).into_par_iter().count();
+        assert_eq!(count, ENTRIES);
+    });
+}
+
+#[test]
+fn par_iter_empty() {
+    let map = HashMap::<usize, usize>::new();
+    let guard = map.owned_guard();
+    assert_eq!(map.par_iter(&guard).count(), 0);
+}
+
+#[test]
+fn par_extend() {
+    with_map::<usize, usize>(|map| {
+        let mut map = map();
+        map.par_extend((0..ENTRIES).into_par_iter().map(|i| (i, i)));
+        (&map).par_extend((ENTRIES..ENTRIES * 2).into_par_iter().map(|i| (i, i)));
+
+        assert_eq!(map.len(), ENTRIES * 2);
+        let m = map.pin();
+        for i in 0..ENTRIES * 2 {
+            assert_eq!(m.get(&i), Some(&i));
+        }
+    });
+}
+
+#[test]
+fn from_par_iter() {
+    let map: HashMap<usize, usize> = (0..ENTRIES).into_par_iter().map(|i| (i, i)).collect();
+
+    assert_eq!(map.len(), ENTRIES);
+    let m = map.pin();
+    for i in 0..ENTRIES {
+        assert_eq!(m.get(&i), Some(&i));
+    }
+}
+
+#[test]
+fn par_retain() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        let m = map.pin();
+        for i in 0..ENTRIES {
+            m.insert(i, i);
+        }
+
+        m.par_retain(|&k, _| k % 3 == 0);
+
+        assert_eq!(m.len(), ENTRIES.div_ceil(3));
+        for i in 0..ENTRIES {
+            assert_eq!(m.contains_key(&i), i % 3 == 0);
+        }
+    });
+}
+
+#[test]
+fn par_clear() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        for i in 0..ENTRIES {
+            map.pin().insert(i, i);
+        }
+
+        map.par_clear();
+        assert!(map.is_empty());
+        assert_eq!(map.pin().iter().count(), 0);
+    });
+}
+
+#[test]
+fn par_retain_concurrent_resize() {
+    with_map::<usize, usize>(|map| {
+        let map = map();
+        for i in 0..ENTRIES {
+            map.pin().insert(i, i);
+        }
+
+        std::thread::scope(|s| {
+            // Force resizes while the parallel retain is running.
+            s.spawn(|| {
+                let m = map.pin();
+                for i in ENTRIES..ENTRIES * 2 {
+                    m.insert(i, i);
+                }
+            });
+
+            map.par_retain(|&k, _| k % 2 == 0 || k >= ENTRIES);
+        });
+
+        // Every entry that was present throughout the operation was visited.
+        let m = map.pin();
+        for i in 0..ENTRIES {
+            assert_eq!(m.contains_key(&i), i % 2 == 0);
+        }
+        for i in ENTRIES..ENTRIES * 2 {
+            assert!(m.contains_key(&i));
+        }
+    });
+}
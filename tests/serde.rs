@@ -0,0 +1,123 @@
+#![cfg(feature = "serde")]
+
+use papaya::{HashMap, HashSet};
+
+mod common;
+use common::{with_map, with_set};
+
+#[test]
+fn map_json() {
+    with_map::<String, usize>(|map| {
+        let map = map();
+        let m = map.pin();
+        m.insert("a".to_owned(), 1);
+        m.insert("b".to_owned(), 2);
+
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(serde_json::to_string(&m).unwrap(), json);
+
+        let deserialized: HashMap<String, usize> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, map);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, serde_json::json!({ "a": 1, "b": 2 }));
+    });
+}
+
+#[test]
+fn map_bincode() {
+    with_map::<usize, String>(|map| {
+        let map = map();
+        let m = map.pin();
+        for i in 0..64 {
+            m.insert(i, i.to_string());
+        }
+
+        let bytes = bincode::serialize(&map).unwrap();
+        assert_eq!(bincode::serialize(&m).unwrap(), bytes);
+
+        let deserialized: HashMap<usize, String> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(deserialized, map);
+    });
+}
+
+#[test]
+fn map_empty() {
+    let map = HashMap::<usize, usize>::new();
+    let json = serde_json::to_string(&map).unwrap();
+    assert_eq!(json, "{}");
+
+    let deserialized: HashMap<usize, usize> = serde_json::from_str(&json).unwrap();
+    assert!(deserialized.is_empty());
+}
+
+#[test]
+fn map_deserialize_into() {
+    with_map::<String, usize>(|map| {
+        let map = map();
+        map.pin().insert("a".to_owned(), 0);
+        map.pin().insert("c".to_owned(), 3);
+
+        let mut deserializer = serde_json::Deserializer::from_str(r#"{"a": 1, "b": 2}"#);
+        map.deserialize_into(&mut deserializer).unwrap();
+
+        let m = map.pin();
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.get("a"), Some(&1));
+        assert_eq!(m.get("b"), Some(&2));
+        assert_eq!(m.get("c"), Some(&3));
+
+        // Invalid input is rejected.
+        let mut deserializer = serde_json::Deserializer::from_str("[1, 2]");
+        assert!(map.deserialize_into(&mut deserializer).is_err());
+    });
+}
+
+#[test]
+fn set_json() {
+    with_set::<usize>(|set| {
+        let set = set();
+        let s = set.pin();
+        for i in 0..8 {
+            s.insert(i);
+        }
+
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!(serde_json::to_string(&s).unwrap(), json);
+
+        let deserialized: HashSet<usize> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, set);
+    });
+}
+
+#[test]
+fn set_bincode() {
+    with_set::<usize>(|set| {
+        let set = set();
+        let s = set.pin();
+        for i in 0..64 {
+            s.insert(i);
+        }
+
+        let bytes = bincode::serialize(&set).unwrap();
+        let deserialized: HashSet<usize> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(deserialized, set);
+    });
+}
+
+#[test]
+fn set_deserialize_into() {
+    with_set::<usize>(|set| {
+        let set = set();
+        set.pin().insert(0);
+
+        let mut deserializer = serde_json::Deserializer::from_str("[1, 2]");
+        set.deserialize_into(&mut deserializer).unwrap();
+
+        let s = set.pin();
+        assert_eq!(s.len(), 3);
+        for i in 0..3 {
+            assert!(s.contains(&i));
+        }
+    });
+}
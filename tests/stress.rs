@@ -578,6 +578,81 @@ fn insert_remove_stress() {
     });
 }
 
+// Call `insert` and `remove` in parallel for a small shared set of keys, with tombstones being
+// reused by concurrent inserts of the same key.
+#[test]
+#[ignore]
+fn insert_remove_reuse_stress() {
+    const ENTRIES: usize = if cfg!(miri) { 16 } else { 64 };
+    const OPERATIONS: usize = match () {
+        _ if cfg!(miri) => 1,
+        _ if cfg!(papaya_stress) || cfg!(papaya_asan) => 1 << 7,
+        _ => 1 << 10,
+    };
+    const ITERATIONS: usize = if cfg!(miri) { 1 } else { 32 };
+
+    let entries = || {
+        let mut entries = (0..OPERATIONS).flat_map(|_| 0..ENTRIES).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        entries.shuffle(&mut rng);
+        entries
+    };
+
+    let threads = threads();
+    with_map(|map| {
+        for _ in (0..ITERATIONS).inspect(|e| debug!("{e}/{ITERATIONS}")) {
+            let map = map();
+
+            let group = threads.checked_div(2).unwrap();
+            let barrier = Barrier::new(threads);
+            thread::scope(|s| {
+                for _ in 0..group {
+                    s.spawn(|| {
+                        let entries = entries();
+                        barrier.wait();
+
+                        let guard = map.guard();
+                        for i in entries {
+                            map.insert(i, i, &guard);
+                        }
+                    });
+                }
+
+                for _ in 0..group {
+                    s.spawn(|| {
+                        let entries = entries();
+                        barrier.wait();
+
+                        let guard = map.guard();
+                        for i in entries {
+                            map.remove(&i, &guard);
+                        }
+                    });
+                }
+            });
+
+            // Every key is present at most once.
+            let guard = map.guard();
+            let mut keys = map.keys(&guard).copied().collect::<Vec<_>>();
+            keys.sort_unstable();
+            let len = keys.len();
+            keys.dedup();
+            assert_eq!(keys.len(), len);
+            assert_eq!(map.len(), len);
+
+            // Removing each key once leaves the map empty.
+            for i in keys {
+                assert_eq!(map.remove(&i, &guard), Some(&i));
+            }
+
+            assert!(map.is_empty());
+            for i in 0..ENTRIES {
+                assert_eq!(map.get(&i, &guard), None);
+            }
+        }
+    });
+}
+
 // Call `remove` in parallel for a shared set of keys with other threads calling `update`,
 // and a dedicated thread for inserting unrelated keys. This is likely to cause interference
 // with incremental resizing.